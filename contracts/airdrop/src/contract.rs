@@ -84,6 +84,11 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
         }
     }
 
+    let mut messages = vec![];
+    if let Some(hook) = msg.upgrade {
+        messages.push(hook.register_msg(&env)?);
+    }
+
     let config = Config {
         admin: msg.admin.unwrap_or(env.message.sender),
         contract: env.contract.address,
@@ -108,7 +113,7 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
     decay_claimed_w(&mut deps.storage).save(&false)?;
 
     Ok(InitResponse {
-        messages: vec![],
+        messages,
         log: vec![],
     })
 }
@@ -2,7 +2,7 @@ use crate::{
     handle,
     proposal_state::total_proposals_w,
     query,
-    state::{admin_commands_list_w, config_w, supported_contracts_list_w},
+    state::{admin_commands_list_w, config_w, self_address_w, supported_contracts_list_w},
 };
 use cosmwasm_std::{
     to_binary, Api, Binary, Env, Extern, HandleResponse, InitResponse, Querier, StdResult, Storage,
@@ -24,12 +24,20 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
         staker: msg.staker,
         funding_token: msg.funding_token.clone(),
         funding_amount: msg.funding_amount,
+        funding_policy: msg.funding_policy.unwrap_or_default(),
         funding_deadline: msg.funding_deadline,
         voting_deadline: msg.voting_deadline,
-        minimum_votes: msg.quorum,
+        vote_settings: msg.vote_settings,
+        guardian: msg.guardian,
+        timelock: msg.timelock.unwrap_or(0),
+        execution_window: msg.execution_window,
     };
 
+    handle::validate_vote_settings(&state.vote_settings)?;
+
     config_w(&mut deps.storage).save(&state)?;
+    // Used to validate voter permits
+    self_address_w(&mut deps.storage).save(&env.contract.address)?;
 
     // Initialize total proposal counter
     total_proposals_w(&mut deps.storage).save(&Uint128(0))?;
@@ -61,20 +69,37 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             target_contract,
             proposal,
             description,
+            metadata,
         } => handle::try_create_proposal(
             deps,
             &env,
             target_contract,
             Binary::from(proposal.as_bytes()),
             description,
+            metadata,
         ),
 
+        HandleMsg::CreateMultiProposal {
+            actions,
+            description,
+            metadata,
+        } => handle::try_create_multi_proposal(deps, &env, actions, description, metadata),
+        HandleMsg::CreateEmergencyProposal {
+            actions,
+            description,
+            metadata,
+        } => handle::try_create_emergency_proposal(deps, &env, actions, description, metadata),
+
         HandleMsg::Receive {
             sender,
             amount,
             msg,
         } => handle::try_fund_proposal(deps, &env, sender, amount, msg),
 
+        HandleMsg::ClaimFunding { proposal_id } => {
+            handle::try_claim_funding(deps, &env, proposal_id)
+        }
+
         // Self interactions
         // Config
         HandleMsg::UpdateConfig {
@@ -82,8 +107,12 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             staker,
             proposal_deadline,
             funding_amount,
+            funding_policy,
             funding_deadline,
-            minimum_votes,
+            vote_settings,
+            guardian,
+            timelock,
+            execution_window,
         } => handle::try_update_config(
             deps,
             &env,
@@ -91,10 +120,30 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             staker,
             proposal_deadline,
             funding_amount,
+            funding_policy,
             funding_deadline,
-            minimum_votes,
+            vote_settings,
+            guardian,
+            timelock,
+            execution_window,
         ),
 
+        HandleMsg::SetVoteSettings { target, settings } => {
+            handle::try_set_vote_settings(deps, &env, target, settings)
+        }
+
+        HandleMsg::SetTimelock { target, delay } => {
+            handle::try_set_timelock(deps, &env, target, delay)
+        }
+
+        HandleMsg::SetContractSchema { target, schema } => {
+            handle::try_set_contract_schema(deps, &env, target, schema)
+        }
+
+        HandleMsg::SetEmergencySettings { settings } => {
+            handle::try_set_emergency_settings(deps, &env, settings)
+        }
+
         HandleMsg::DisableStaker {} => handle::try_disable_staker(deps, &env),
 
         // Supported contract
@@ -111,17 +160,21 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
         }
 
         // Admin command
-        HandleMsg::AddAdminCommand { name, proposal } => {
-            handle::try_add_admin_command(deps, &env, name, proposal)
-        }
+        HandleMsg::AddAdminCommand {
+            name,
+            proposal,
+            params,
+        } => handle::try_add_admin_command(deps, &env, name, proposal, params),
 
         HandleMsg::RemoveAdminCommand { name } => {
             handle::try_remove_admin_command(deps, &env, name)
         }
 
-        HandleMsg::UpdateAdminCommand { name, proposal } => {
-            handle::try_update_admin_command(deps, &env, name, proposal)
-        }
+        HandleMsg::UpdateAdminCommand {
+            name,
+            proposal,
+            params,
+        } => handle::try_update_admin_command(deps, &env, name, proposal, params),
 
         // User interaction
         HandleMsg::MakeVote {
@@ -134,6 +187,22 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             handle::try_trigger_proposal(deps, &env, proposal_id)
         }
 
+        HandleMsg::CancelProposal { proposal_id } => {
+            handle::try_cancel_proposal(deps, &env, proposal_id)
+        }
+
+        HandleMsg::RegisterUpgrade { label, code_hash } => {
+            handle::try_register_upgrade(deps, &env, label, code_hash)
+        }
+
+        HandleMsg::ConfirmExecution { proposal_id, error } => {
+            handle::try_confirm_execution(deps, &env, proposal_id, error)
+        }
+
+        // Voter authentication
+        HandleMsg::SetViewingKey { key } => handle::try_set_viewing_key(deps, &env, key),
+        HandleMsg::DisablePermitKey { key } => handle::try_disable_permit_key(deps, &env, key),
+
         // Admin interactions
         HandleMsg::TriggerAdminCommand {
             target,
@@ -155,12 +224,29 @@ pub fn query<S: Storage, A: Api, Q: Querier>(
 
         QueryMsg::GetProposal { proposal_id } => to_binary(&query::proposal(deps, proposal_id)?),
 
+        QueryMsg::SearchProposals {
+            filter,
+            cursor,
+            limit,
+        } => to_binary(&query::search_proposals(deps, filter, cursor, limit)?),
+
         QueryMsg::GetTotalProposals {} => to_binary(&query::total_proposals(deps)?),
 
         QueryMsg::GetProposalVotes { proposal_id } => {
             to_binary(&query::proposal_votes(deps, proposal_id)?)
         }
 
+        QueryMsg::GetVoterVote {
+            proposal_id,
+            voter,
+            key,
+        } => to_binary(&query::voter_vote_with_key(deps, proposal_id, voter, key)?),
+
+        QueryMsg::GetVoterVoteWithPermit {
+            proposal_id,
+            permit,
+        } => to_binary(&query::voter_vote_with_permit(deps, proposal_id, permit)?),
+
         QueryMsg::GetSupportedContracts {} => to_binary(&query::supported_contracts(deps)?),
 
         QueryMsg::GetSupportedContract { name } => {
@@ -170,5 +256,18 @@ pub fn query<S: Storage, A: Api, Q: Querier>(
         QueryMsg::GetAdminCommands {} => to_binary(&query::admin_commands(deps)?),
 
         QueryMsg::GetAdminCommand { name } => to_binary(&query::admin_command(deps, name)?),
+
+        QueryMsg::GetAdminCommandParams { name } => {
+            to_binary(&query::admin_command_params(deps, name)?)
+        }
+
+        QueryMsg::GetVoteSettings { target } => to_binary(&query::vote_settings(deps, target)?),
+
+        QueryMsg::GetTimelock { target } => to_binary(&query::timelock(deps, target)?),
+
+        QueryMsg::GetContractSchema { target } => to_binary(&query::contract_schema(deps, target)?),
+
+        QueryMsg::GetPendingUpgrade { label } => to_binary(&query::pending_upgrade(deps, label)?),
+        QueryMsg::GetEmergencySettings {} => to_binary(&query::emergency_settings(deps)?),
     }
 }
@@ -1,49 +1,109 @@
 use crate::{
     proposal_state::{
-        proposal_funding_batch_w, proposal_funding_deadline_r, proposal_funding_deadline_w,
-        proposal_funding_r, proposal_funding_w, proposal_r, proposal_run_status_w,
-        proposal_status_r, proposal_status_w, proposal_votes_r, proposal_votes_w,
-        proposal_voting_deadline_r, proposal_voting_deadline_w, proposal_w, total_proposal_votes_r,
-        total_proposal_votes_w, total_proposals_w,
+        category_index, proposal_action_status_w, proposal_emergency_r, proposal_emergency_w,
+        proposal_execution_time_r, proposal_execution_time_w, proposal_expiration_time_r,
+        proposal_expiration_time_w, proposal_funding_batch_r, proposal_funding_batch_w,
+        proposal_funding_deadline_r, proposal_funding_deadline_w, proposal_funding_r,
+        proposal_funding_w, proposal_r, proposal_run_error_w, proposal_run_status_r,
+        proposal_run_status_w, proposal_snapshot_r, proposal_snapshot_w, proposal_status_r,
+        proposal_votes_r, proposal_votes_w, proposal_voting_deadline_r, proposal_voting_deadline_w,
+        proposal_w, proposer_index, push_proposal_index, save_proposal_status, target_index,
+        total_proposal_votes_r, total_proposal_votes_w, total_proposals_w,
     },
     state::{
         admin_commands_list_w, admin_commands_r, admin_commands_w, config_r, config_w,
-        supported_contract_r, supported_contract_w, supported_contracts_list_w,
+        contract_schema_r, contract_schema_w, emergency_settings_r, emergency_settings_w,
+        pending_upgrade_r, pending_upgrade_w, supported_contract_r, supported_contract_w,
+        supported_contracts_list_w, timelock_r, timelock_w, vote_settings_r, vote_settings_w,
+        voter_permit_key_w, voter_viewing_key_w,
     },
 };
 use cosmwasm_std::{
-    from_binary, to_binary, Api, Binary, CosmosMsg, Env, Extern, HandleResponse, HumanAddr,
-    Querier, StdError, StdResult, Storage, Uint128, WasmMsg,
+    from_binary, from_slice, to_binary, to_vec, Api, Binary, Coin, CosmosMsg, Env, Extern,
+    HandleResponse, HumanAddr, Querier, StdError, StdResult, Storage, Uint128, WasmMsg,
 };
-use secret_toolkit::snip20::{batch::SendAction, batch_send_msg, send_msg};
+use query_authentication::viewing_keys::ViewingKey;
+use secret_toolkit::{
+    snip20::{batch::SendAction, batch_send_msg, send_msg},
+    utils::{HandleCallback, Query},
+};
+use serde::de::IgnoredAny;
 use shade_protocol::governance::{
-    proposal::{Proposal, ProposalStatus},
-    vote::VoteTally,
-    AdminCommand, HandleAnswer, ADMIN_COMMAND_VARIABLE, GOVERNANCE_SELF,
+    proposal::{
+        InstantiateAction, PendingUpgrade, Proposal, ProposalAction, ProposalMetadata,
+        ProposalStatus,
+    },
+    schema::MessageSchema,
+    upgrade::UpgradeInitMsg,
+    vote::{VoteTally, VoterKey},
+    AdminCommand, AdminCommandParam, AdminCommandParamType, EmergencySettings, FundingPolicy,
+    HandleAnswer, HandleMsg, VoteSettings, ADMIN_COMMAND_PARAM_CLOSE, ADMIN_COMMAND_PARAM_OPEN,
+    GOVERNANCE_SELF, ONE_HUNDRED_PERCENT,
 };
+use shade_protocol::staking;
+use shade_protocol::treasury::{self, TreasuryReceiveMsg};
 use shade_protocol::utils::asset::Contract;
 use shade_protocol::utils::generic_response::{
     ResponseStatus,
     ResponseStatus::{Failure, Success},
 };
+use std::collections::BTreeMap;
 
 pub fn create_proposal<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: &Env,
-    target_contract: String,
-    proposal: Binary,
+    actions: Vec<ProposalAction>,
     description: String,
+    metadata: Option<ProposalMetadata>,
 ) -> StdResult<Uint128> {
-    // Check that the target contract is neither the governance or a supported contract
-    if supported_contract_r(&deps.storage)
-        .may_load(target_contract.as_bytes())?
-        .is_none()
-        && target_contract != *GOVERNANCE_SELF
-    {
-        return Err(StdError::NotFound {
-            kind: "contract is not found".to_string(),
-            backtrace: None,
-        });
+    if actions.is_empty() {
+        return Err(StdError::generic_err("Proposal must have an action"));
+    }
+
+    // Check that every target contract is either the governance or a supported contract
+    for action in actions.iter() {
+        if supported_contract_r(&deps.storage)
+            .may_load(action.target.as_bytes())?
+            .is_none()
+            && action.target != *GOVERNANCE_SELF
+        {
+            return Err(StdError::NotFound {
+                kind: "contract is not found".to_string(),
+                backtrace: None,
+            });
+        }
+
+        match &action.instantiate {
+            // Upgrades replace a supported contract
+            Some(_) if action.target == GOVERNANCE_SELF => {
+                return Err(StdError::generic_err("Governance cannot be upgraded"));
+            }
+            Some(instantiate) => {
+                if pending_upgrade_r(&deps.storage)
+                    .may_load(instantiate.label.as_bytes())?
+                    .is_some()
+                {
+                    return Err(StdError::generic_err("Upgrade label is already used"));
+                }
+                // The new contract must register itself with this governance on init
+                let hook = from_slice::<UpgradeInitMsg>(action.msg.as_slice())
+                    .ok()
+                    .and_then(|msg| msg.upgrade);
+                match hook {
+                    Some(hook)
+                        if hook.governance.address == env.contract.address
+                            && hook.governance.code_hash == env.contract_code_hash
+                            && hook.label == instantiate.label => {}
+                    _ => {
+                        return Err(StdError::generic_err(
+                            "Upgrade init msg must set the upgrade hook for its label",
+                        ))
+                    }
+                }
+            }
+            // Catch malformed messages before they are voted on
+            None => validate_proposal_msg(&deps.storage, &action.target, &action.msg)?,
+        }
     }
 
     // Create new proposal ID
@@ -55,22 +115,23 @@ pub fn create_proposal<S: Storage, A: Api, Q: Querier>(
     // Create proposal
     let proposal = Proposal {
         id: proposal_id,
-        target: target_contract,
-        msg: proposal,
+        actions,
         description,
+        proposer: env.message.sender.clone(),
+        metadata: metadata.unwrap_or_default(),
     };
 
     let config = config_r(&deps.storage).load()?;
 
     // Store the proposal
     proposal_w(&mut deps.storage).save(proposal_id.to_string().as_bytes(), &proposal)?;
+    index_proposal(&mut deps.storage, &proposal)?;
     // Initialize deadline
     proposal_funding_deadline_w(&mut deps.storage).save(
         proposal_id.to_string().as_bytes(),
         &(env.block.time + config.funding_deadline),
     )?;
-    proposal_status_w(&mut deps.storage)
-        .save(proposal_id.to_string().as_bytes(), &ProposalStatus::Funding)?;
+    save_proposal_status(&mut deps.storage, proposal_id, &ProposalStatus::Funding)?;
 
     // Initialize total funding
     proposal_funding_w(&mut deps.storage)
@@ -86,6 +147,7 @@ pub fn create_proposal<S: Storage, A: Api, Q: Querier>(
             yes: Uint128::zero(),
             no: Uint128::zero(),
             abstain: Uint128::zero(),
+            veto: Uint128::zero(),
         },
     )?;
 
@@ -119,10 +181,9 @@ pub fn try_fund_proposal<S: Storage, A: Api, Q: Querier>(
     if env.block.time
         >= proposal_funding_deadline_r(&deps.storage).load(proposal_id.to_string().as_bytes())?
     {
-        proposal_status_w(&mut deps.storage)
-            .save(proposal_id.to_string().as_bytes(), &ProposalStatus::Expired)?;
+        save_proposal_status(&mut deps.storage, proposal_id, &ProposalStatus::Expired)?;
 
-        // Send back amount
+        // Send back amount, previous funders can claim theirs
         messages.push(send_msg(
             sender,
             amount,
@@ -134,8 +195,6 @@ pub fn try_fund_proposal<S: Storage, A: Api, Q: Querier>(
             config.funding_token.address,
         )?);
 
-        // TODO: send total over to treasury
-
         return Ok(HandleResponse {
             messages,
             log: vec![],
@@ -171,7 +230,7 @@ pub fn try_fund_proposal<S: Storage, A: Api, Q: Querier>(
     }
 
     // Update list of people that funded
-    let amounts = proposal_funding_batch_w(&mut deps.storage).update(
+    proposal_funding_batch_w(&mut deps.storage).update(
         proposal_id.to_string().as_bytes(),
         |amounts| {
             if let Some(mut amounts) = amounts {
@@ -193,22 +252,15 @@ pub fn try_fund_proposal<S: Storage, A: Api, Q: Querier>(
     // Update proposal status
     if total == config.funding_amount {
         // Update proposal status
-        proposal_status_w(&mut deps.storage)
-            .save(proposal_id.to_string().as_bytes(), &ProposalStatus::Voting)?;
+        save_proposal_status(&mut deps.storage, proposal_id, &ProposalStatus::Voting)?;
         // Set vote deadline
         proposal_voting_deadline_w(&mut deps.storage).save(
             proposal_id.to_string().as_bytes(),
             &(env.block.time + config.voting_deadline),
         )?;
-
-        // Send back all of the invested prop amount
-        messages.push(batch_send_msg(
-            amounts,
-            None,
-            1,
-            config.funding_token.code_hash,
-            config.funding_token.address,
-        )?)
+        // Voting power is taken from the stake held before this block
+        proposal_snapshot_w(&mut deps.storage)
+            .save(proposal_id.to_string().as_bytes(), &env.block.height)?;
     }
 
     proposal_funding_w(&mut deps.storage).save(proposal_id.to_string().as_bytes(), &total)?;
@@ -230,10 +282,17 @@ pub fn try_trigger_proposal<S: Storage, A: Api, Q: Querier>(
 ) -> StdResult<HandleResponse> {
     // Get proposal
     let proposal = proposal_r(&deps.storage).load(proposal_id.to_string().as_bytes())?;
-    let run_status: ResponseStatus;
     let mut vote_status =
         proposal_status_r(&deps.storage).load(proposal_id.to_string().as_bytes())?;
 
+    match vote_status {
+        ProposalStatus::Queued => return run_queued_proposal(deps, env, proposal),
+        ProposalStatus::Executed | ProposalStatus::Cancelled => {
+            return Err(StdError::unauthorized());
+        }
+        _ => {}
+    }
+
     // Check if proposal has run
     // TODO: This might not be needed
     // if proposal_run_status_r(&deps.storage).may_load(proposal_id.to_string().as_bytes())?.is_some() {
@@ -242,8 +301,10 @@ pub fn try_trigger_proposal<S: Storage, A: Api, Q: Querier>(
 
     // Change proposal behavior according to stake availability
     let config = config_r(&deps.storage).load()?;
+    let emergency =
+        proposal_emergency_r(&deps.storage).may_load(proposal_id.to_string().as_bytes())?;
     vote_status = match config.staker {
-        Some(_) => {
+        Some(staker) => {
             // When staking is enabled funding is required
             if vote_status != ProposalStatus::Voting {
                 return Err(StdError::unauthorized());
@@ -257,15 +318,17 @@ pub fn try_trigger_proposal<S: Storage, A: Api, Q: Querier>(
                 .may_load(proposal_id.to_string().as_bytes())?
                 .ok_or_else(|| StdError::generic_err("No deadline set"))?;
             if voting_deadline > env.block.time {
-                Err(StdError::unauthorized())
-            } else if total_votes.yes + total_votes.no + total_votes.abstain < config.minimum_votes
-            {
-                Ok(ProposalStatus::Expired)
-            } else if total_votes.yes > total_votes.no {
-                Ok(ProposalStatus::Passed)
-            } else {
-                Ok(ProposalStatus::Rejected)
+                return Err(StdError::unauthorized());
             }
+
+            let total_staked = snapshot_total_staked(deps, staker, proposal_id)?;
+            let mut settings =
+                proposal_vote_settings(&deps.storage, &proposal, config.vote_settings)?;
+            if let Some(emergency) = &emergency {
+                settings = strictest_vote_settings(settings, emergency.vote_settings.clone());
+            }
+
+            Ok(tally_status(&total_votes, total_staked, &settings))
         }
         None => {
             // Check if user is an admin in order to trigger the proposal
@@ -277,37 +340,385 @@ pub fn try_trigger_proposal<S: Storage, A: Api, Q: Querier>(
         }
     }?;
 
-    let mut messages: Vec<CosmosMsg> = vec![];
+    // Deposits are returned or slashed once the outcome is known
+    let funding_messages = settle_funding(deps, proposal_id, &vote_status)?;
 
-    let target: Option<Contract>;
-    if proposal.target == GOVERNANCE_SELF {
-        target = Some(Contract {
-            address: env.contract.address.clone(),
-            code_hash: env.contract_code_hash.clone(),
-        })
-    } else {
-        target = supported_contract_r(&deps.storage).may_load(proposal.target.as_bytes())?;
+    // Passed proposals wait for their timelock before running
+    if vote_status == ProposalStatus::Passed {
+        let delay = match &emergency {
+            Some(emergency) => emergency.timelock,
+            None => proposal_timelock(&deps.storage, &proposal, config.timelock)?,
+        };
+
+        if delay > 0 {
+            let execution_time = env.block.time + delay;
+            proposal_execution_time_w(&mut deps.storage)
+                .save(proposal_id.to_string().as_bytes(), &execution_time)?;
+            if let Some(window) = config.execution_window {
+                proposal_expiration_time_w(&mut deps.storage).save(
+                    proposal_id.to_string().as_bytes(),
+                    &(execution_time + window),
+                )?;
+            }
+            save_proposal_status(&mut deps.storage, proposal_id, &ProposalStatus::Queued)?;
+
+            return Ok(HandleResponse {
+                messages: funding_messages,
+                log: vec![],
+                data: Some(to_binary(&HandleAnswer::TriggerProposal {
+                    status: Success,
+                })?),
+            });
+        }
+    }
+
+    let mut response = run_proposal(deps, env, proposal, vote_status)?;
+    response.messages.extend(funding_messages);
+
+    Ok(response)
+}
+
+// Adds the proposal to the proposer, target and category indexes
+fn index_proposal<S: Storage>(storage: &mut S, proposal: &Proposal) -> StdResult<()> {
+    push_proposal_index(storage, &proposer_index(&proposal.proposer), proposal.id)?;
+    for action in proposal.actions.iter() {
+        push_proposal_index(storage, &target_index(&action.target), proposal.id)?;
+    }
+    if let Some(category) = &proposal.metadata.category {
+        push_proposal_index(storage, &category_index(category), proposal.id)?;
+    }
+
+    Ok(())
+}
+
+// Actions run as one batch so they all share the outcome of the run
+fn save_run_status<S: Storage>(
+    storage: &mut S,
+    proposal_id: Uint128,
+    status: ResponseStatus,
+    action_count: usize,
+) -> StdResult<()> {
+    proposal_action_status_w(storage).save(
+        proposal_id.to_string().as_bytes(),
+        &vec![status.clone(); action_count],
+    )?;
+    proposal_run_status_w(storage).save(proposal_id.to_string().as_bytes(), &status)
+}
+
+fn confirm_execution_msg(env: &Env, proposal_id: Uint128) -> StdResult<CosmosMsg> {
+    HandleMsg::ConfirmExecution {
+        proposal_id,
+        error: None,
+    }
+    .to_cosmos_msg(
+        env.contract_code_hash.clone(),
+        env.contract.address.clone(),
+        None,
+    )
+}
+
+pub fn try_confirm_execution<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    proposal_id: Uint128,
+    error: Option<String>,
+) -> StdResult<HandleResponse> {
+    // Governance confirms successful runs, the admin reports failed ones
+    let config = config_r(&deps.storage).load()?;
+    match error {
+        None if env.message.sender == env.contract.address => {}
+        Some(_) if env.message.sender == config.admin => {}
+        _ => return Err(StdError::unauthorized()),
+    }
+
+    let status = proposal_status_r(&deps.storage)
+        .may_load(proposal_id.to_string().as_bytes())?
+        .ok_or_else(|| StdError::not_found("Proposal"))?;
+    if proposal_run_status_r(&deps.storage).may_load(proposal_id.to_string().as_bytes())?
+        == Some(Success)
+    {
+        return Err(StdError::generic_err(
+            "Proposal execution is already confirmed",
+        ));
+    }
+
+    let actions = proposal_r(&deps.storage)
+        .load(proposal_id.to_string().as_bytes())?
+        .actions;
+    let action_count = actions.len();
+
+    match error {
+        None => {
+            // New contracts must have registered by the end of the run
+            for action in actions.iter() {
+                if let Some(instantiate) = &action.instantiate {
+                    if pending_upgrade_r(&deps.storage)
+                        .may_load(instantiate.label.as_bytes())?
+                        .is_some()
+                    {
+                        return Err(StdError::generic_err(format!(
+                            "Upgrade {} was not registered",
+                            instantiate.label
+                        )));
+                    }
+                }
+            }
+            save_run_status(&mut deps.storage, proposal_id, Success, action_count)?;
+            proposal_run_error_w(&mut deps.storage).remove(proposal_id.to_string().as_bytes());
+            if status == ProposalStatus::Passed {
+                save_proposal_status(&mut deps.storage, proposal_id, &ProposalStatus::Executed)?;
+            }
+        }
+        Some(error) => {
+            // A failing action reverts the whole trigger so failed runs can only be reported,
+            // this is limited to queued proposals past their timelock and leaves them runnable
+            if status != ProposalStatus::Queued
+                || proposal_execution_time_r(&deps.storage)
+                    .load(proposal_id.to_string().as_bytes())?
+                    > env.block.time
+            {
+                return Err(StdError::generic_err(
+                    "Only queued proposals past their timelock can be reported",
+                ));
+            }
+            save_run_status(&mut deps.storage, proposal_id, Failure, action_count)?;
+            proposal_run_error_w(&mut deps.storage)
+                .save(proposal_id.to_string().as_bytes(), &error)?;
+        }
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::ConfirmExecution {
+            status: Success,
+        })?),
+    })
+}
+
+// Treasury only receives registered assets, sending anything else would revert the trigger
+fn treasury_accepts<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    contract: &Contract,
+    token: &HumanAddr,
+) -> bool {
+    treasury::QueryMsg::Income {
+        asset: token.clone(),
+    }
+    .query::<Q, treasury::QueryAnswer>(
+        &deps.querier,
+        contract.code_hash.clone(),
+        contract.address.clone(),
+    )
+    .is_ok()
+}
+
+fn settle_funding<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    proposal_id: Uint128,
+    status: &ProposalStatus,
+) -> StdResult<Vec<CosmosMsg>> {
+    let config = config_r(&deps.storage).load()?;
+    let funders = proposal_funding_batch_r(&deps.storage)
+        .may_load(proposal_id.to_string().as_bytes())?
+        .unwrap_or_default();
+    if funders.is_empty() {
+        return Ok(vec![]);
+    }
+
+    proposal_funding_batch_w(&mut deps.storage)
+        .save(proposal_id.to_string().as_bytes(), &vec![])?;
+
+    match config.funding_policy.treasury {
+        Some(treasury)
+            if config.funding_policy.slash_on.contains(status)
+                && treasury_accepts(deps, &treasury, &config.funding_token.address) =>
+        {
+            let mut total = Uint128::zero();
+            for funder in funders.iter() {
+                total += funder.amount;
+            }
+
+            Ok(vec![send_msg(
+                treasury.address,
+                total,
+                Some(to_binary(&TreasuryReceiveMsg::FeeIncome {
+                    source: "governance".to_string(),
+                })?),
+                None,
+                None,
+                1,
+                config.funding_token.code_hash,
+                config.funding_token.address,
+            )?])
+        }
+        _ => Ok(vec![batch_send_msg(
+            funders,
+            None,
+            1,
+            config.funding_token.code_hash,
+            config.funding_token.address,
+        )?]),
+    }
+}
+
+pub fn try_claim_funding<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    proposal_id: Uint128,
+) -> StdResult<HandleResponse> {
+    let status = proposal_status_r(&deps.storage)
+        .may_load(proposal_id.to_string().as_bytes())?
+        .ok_or_else(|| StdError::not_found("Proposal"))?;
+
+    // Only proposals that were never fully funded can be claimed from
+    let funding_deadline =
+        proposal_funding_deadline_r(&deps.storage).load(proposal_id.to_string().as_bytes())?;
+    if !(status == ProposalStatus::Funding || status == ProposalStatus::Expired)
+        || env.block.time < funding_deadline
+        || proposal_snapshot_r(&deps.storage)
+            .may_load(proposal_id.to_string().as_bytes())?
+            .is_some()
+    {
+        return Err(StdError::unauthorized());
     }
 
-    // Check if proposal passed or has a valid target contract
+    let funders = proposal_funding_batch_r(&deps.storage)
+        .may_load(proposal_id.to_string().as_bytes())?
+        .unwrap_or_default();
+    let (claimed, remaining): (Vec<SendAction>, Vec<SendAction>) = funders
+        .into_iter()
+        .partition(|funder| funder.recipient == env.message.sender);
+
+    let mut amount = Uint128::zero();
+    for funder in claimed.iter() {
+        amount += funder.amount;
+    }
+    if amount.is_zero() {
+        return Err(StdError::generic_err("Nothing to claim"));
+    }
+
+    proposal_funding_batch_w(&mut deps.storage)
+        .save(proposal_id.to_string().as_bytes(), &remaining)?;
+    save_proposal_status(&mut deps.storage, proposal_id, &ProposalStatus::Expired)?;
+
+    let config = config_r(&deps.storage).load()?;
+
+    Ok(HandleResponse {
+        messages: vec![send_msg(
+            env.message.sender.clone(),
+            amount,
+            None,
+            None,
+            None,
+            1,
+            config.funding_token.code_hash,
+            config.funding_token.address,
+        )?],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::ClaimFunding {
+            status: Success,
+            amount,
+        })?),
+    })
+}
+
+fn run_queued_proposal<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    proposal: Proposal,
+) -> StdResult<HandleResponse> {
+    let proposal_id = proposal.id;
+
+    let execution_time =
+        proposal_execution_time_r(&deps.storage).load(proposal_id.to_string().as_bytes())?;
+    if execution_time > env.block.time {
+        return Err(StdError::generic_err("Proposal timelock has not ended"));
+    }
+
+    // Execution is no longer allowed
+    if let Some(expiration_time) =
+        proposal_expiration_time_r(&deps.storage).may_load(proposal_id.to_string().as_bytes())?
+    {
+        if env.block.time > expiration_time {
+            return run_proposal(deps, env, proposal, ProposalStatus::Expired);
+        }
+    }
+
+    run_proposal(deps, env, proposal, ProposalStatus::Passed)
+}
+
+fn run_proposal<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    proposal: Proposal,
+    vote_status: ProposalStatus,
+) -> StdResult<HandleResponse> {
+    let proposal_id = proposal.id;
+    let run_status: ResponseStatus;
+    let action_count = proposal.actions.len();
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut valid = true;
+    let mut upgrades = vec![];
+
+    // Check if proposal passed
     if vote_status != ProposalStatus::Passed {
         run_status = Failure;
-    } else if let Some(target) = target {
-        run_status = match try_execute_msg(target, proposal.msg) {
-            Ok(msg) => {
-                messages.push(msg);
-                Success
-            }
-            Err(_) => Failure,
-        };
     } else {
-        run_status = Failure;
+        for action in proposal.actions {
+            let target: Option<Contract>;
+            if action.target == GOVERNANCE_SELF {
+                target = Some(Contract {
+                    address: env.contract.address.clone(),
+                    code_hash: env.contract_code_hash.clone(),
+                })
+            } else {
+                target = supported_contract_r(&deps.storage).may_load(action.target.as_bytes())?;
+            }
+
+            // Check that the action has a valid target contract
+            let msg = match (target, action.instantiate) {
+                (Some(_), Some(instantiate)) if action.target != GOVERNANCE_SELF => {
+                    upgrades.push((
+                        instantiate.label.clone(),
+                        PendingUpgrade {
+                            proposal_id,
+                            target: action.target,
+                            code_hash: instantiate.code_hash.clone(),
+                        },
+                    ));
+                    try_instantiate_msg(instantiate, action.msg, action.funds)
+                }
+                (Some(target), None) => try_execute_msg(target, action.msg, action.funds),
+                _ => Err(StdError::not_found("Supported contract")),
+            };
+            match msg {
+                Ok(msg) => messages.push(msg),
+                Err(_) => valid = false,
+            }
+        }
+
+        // Actions run as one batch so a single invalid action stops all of them
+        if !valid {
+            messages.clear();
+            run_status = Failure;
+        } else {
+            // Only reached when every action succeeded, otherwise the whole run reverts
+            messages.push(confirm_execution_msg(env, proposal_id)?);
+            run_status = Success;
+
+            // New contracts replace their targets once their address is known
+            for (label, upgrade) in upgrades {
+                pending_upgrade_w(&mut deps.storage).save(label.as_bytes(), &upgrade)?;
+            }
+        }
     }
 
-    // Overwrite
-    proposal_run_status_w(&mut deps.storage)
-        .save(proposal_id.to_string().as_bytes(), &run_status)?;
-    proposal_status_w(&mut deps.storage).save(proposal_id.to_string().as_bytes(), &vote_status)?;
+    // Success is recorded once execution is confirmed
+    if run_status == Failure {
+        save_run_status(&mut deps.storage, proposal_id, Failure, action_count)?;
+    }
+    save_proposal_status(&mut deps.storage, proposal_id, &vote_status)?;
 
     Ok(HandleResponse {
         messages,
@@ -318,16 +729,187 @@ pub fn try_trigger_proposal<S: Storage, A: Api, Q: Querier>(
     })
 }
 
-pub fn try_execute_msg(contract: Contract, msg: Binary) -> StdResult<CosmosMsg> {
+pub fn try_cancel_proposal<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    proposal_id: Uint128,
+) -> StdResult<HandleResponse> {
+    // Check if guardian or admin
+    let config = config_r(&deps.storage).load()?;
+    if env.message.sender != config.admin && Some(&env.message.sender) != config.guardian.as_ref() {
+        return Err(StdError::unauthorized());
+    }
+
+    let status = proposal_status_r(&deps.storage)
+        .may_load(proposal_id.to_string().as_bytes())?
+        .ok_or_else(|| StdError::not_found("Proposal"))?;
+    if status != ProposalStatus::Queued {
+        return Err(StdError::generic_err(
+            "Only queued proposals can be cancelled",
+        ));
+    }
+
+    save_proposal_status(&mut deps.storage, proposal_id, &ProposalStatus::Cancelled)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::CancelProposal {
+            status: Success,
+        })?),
+    })
+}
+
+// Proposals touching several targets wait for the longest of their timelocks
+fn proposal_timelock<S: Storage>(storage: &S, proposal: &Proposal, default: u64) -> StdResult<u64> {
+    let mut delay = 0;
+
+    for action in proposal.actions.iter() {
+        delay = delay.max(
+            timelock_r(storage)
+                .may_load(action.target.as_bytes())?
+                .unwrap_or(default),
+        );
+    }
+
+    Ok(delay)
+}
+
+// Proposals touching several targets use the strictest of their settings
+fn proposal_vote_settings<S: Storage>(
+    storage: &S,
+    proposal: &Proposal,
+    default: VoteSettings,
+) -> StdResult<VoteSettings> {
+    let mut settings: Option<VoteSettings> = None;
+
+    for action in proposal.actions.iter() {
+        let target = vote_settings_r(storage)
+            .may_load(action.target.as_bytes())?
+            .unwrap_or_else(|| default.clone());
+
+        settings = Some(match settings {
+            None => target,
+            Some(current) => strictest_vote_settings(current, target),
+        });
+    }
+
+    Ok(settings.unwrap_or(default))
+}
+
+fn strictest_vote_settings(a: VoteSettings, b: VoteSettings) -> VoteSettings {
+    VoteSettings {
+        quorum: a.quorum.max(b.quorum),
+        threshold: a.threshold.max(b.threshold),
+        veto: match (a.veto, b.veto) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        },
+    }
+}
+
+// Quorum is measured against the stake at the voting snapshot
+fn snapshot_total_staked<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    staker: Contract,
+    proposal_id: Uint128,
+) -> StdResult<Uint128> {
+    let height = proposal_snapshot_r(&deps.storage).load(proposal_id.to_string().as_bytes())?;
+
+    match (staking::QueryMsg::TotalStakedAt { height }).query(
+        &deps.querier,
+        staker.code_hash,
+        staker.address,
+    )? {
+        staking::QueryAnswer::TotalStakedAt { total } => Ok(total),
+        _ => Err(StdError::generic_err("Unexpected staking response")),
+    }
+}
+
+pub(crate) fn tally_status(
+    votes: &VoteTally,
+    total_staked: Uint128,
+    settings: &VoteSettings,
+) -> ProposalStatus {
+    let total = votes.yes + votes.no + votes.abstain + votes.veto;
+    let non_abstain = votes.yes + votes.no + votes.veto;
+
+    if total < total_staked.multiply_ratio(settings.quorum, ONE_HUNDRED_PERCENT) {
+        return ProposalStatus::Expired;
+    }
+
+    if let Some(veto) = settings.veto {
+        if votes.veto > total.multiply_ratio(veto, ONE_HUNDRED_PERCENT) {
+            return ProposalStatus::Vetoed;
+        }
+    }
+
+    if votes.yes > non_abstain.multiply_ratio(settings.threshold, ONE_HUNDRED_PERCENT) {
+        ProposalStatus::Passed
+    } else {
+        ProposalStatus::Rejected
+    }
+}
+
+pub fn try_execute_msg(contract: Contract, msg: Binary, funds: Vec<Coin>) -> StdResult<CosmosMsg> {
     let execute = WasmMsg::Execute {
         msg,
         contract_addr: contract.address,
         callback_code_hash: contract.code_hash,
-        send: vec![],
+        send: funds,
     };
     Ok(execute.into())
 }
 
+pub fn try_instantiate_msg(
+    instantiate: InstantiateAction,
+    msg: Binary,
+    funds: Vec<Coin>,
+) -> StdResult<CosmosMsg> {
+    let instantiate = WasmMsg::Instantiate {
+        code_id: instantiate.code_id,
+        callback_code_hash: instantiate.code_hash,
+        msg,
+        send: funds,
+        label: instantiate.label,
+    };
+    Ok(instantiate.into())
+}
+
+pub fn try_register_upgrade<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    label: String,
+    code_hash: String,
+) -> StdResult<HandleResponse> {
+    // Upgrades are saved by the run that instantiates them and the run's ConfirmExecution reverts
+    // while any is still pending, so they never outlive the instantiating transaction and only
+    // the contract it created through the upgrade hook can register
+    let upgrade = pending_upgrade_r(&deps.storage)
+        .may_load(label.as_bytes())?
+        .ok_or_else(|| StdError::not_found("Pending upgrade"))?;
+    if upgrade.code_hash != code_hash {
+        return Err(StdError::unauthorized());
+    }
+
+    supported_contract_w(&mut deps.storage).save(
+        upgrade.target.as_bytes(),
+        &Contract {
+            address: env.message.sender.clone(),
+            code_hash,
+        },
+    )?;
+    pending_upgrade_w(&mut deps.storage).remove(label.as_bytes());
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::RegisterUpgrade {
+            status: Success,
+        })?),
+    })
+}
+
 pub fn try_vote<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: &Env,
@@ -357,7 +939,7 @@ pub fn try_vote<S: Storage, A: Api, Q: Querier>(
     let mut proposal_voting_state =
         total_proposal_votes_r(&deps.storage).load(proposal_id.to_string().as_bytes())?;
 
-    // Check if user has already voted
+    // A new vote replaces the voter's previous one
     match proposal_votes_r(&deps.storage, proposal_id).may_load(voter.to_string().as_bytes())? {
         None => {}
         Some(old_votes) => {
@@ -365,6 +947,7 @@ pub fn try_vote<S: Storage, A: Api, Q: Querier>(
             proposal_voting_state.yes = (proposal_voting_state.yes - old_votes.yes)?;
             proposal_voting_state.no = (proposal_voting_state.no - old_votes.no)?;
             proposal_voting_state.abstain = (proposal_voting_state.abstain - old_votes.abstain)?;
+            proposal_voting_state.veto = (proposal_voting_state.veto - old_votes.veto)?;
         }
     }
 
@@ -372,6 +955,7 @@ pub fn try_vote<S: Storage, A: Api, Q: Querier>(
     proposal_voting_state.yes += votes.yes;
     proposal_voting_state.no += votes.no;
     proposal_voting_state.abstain += votes.abstain;
+    proposal_voting_state.veto += votes.veto;
 
     // Save staker info
     total_proposal_votes_w(&mut deps.storage)
@@ -385,6 +969,40 @@ pub fn try_vote<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+pub fn try_set_viewing_key<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    key: String,
+) -> StdResult<HandleResponse> {
+    voter_viewing_key_w(&mut deps.storage).save(
+        env.message.sender.to_string().as_bytes(),
+        &VoterKey(key).hash(),
+    )?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetViewingKey { status: Success })?),
+    })
+}
+
+pub fn try_disable_permit_key<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    key: String,
+) -> StdResult<HandleResponse> {
+    voter_permit_key_w(&mut deps.storage, env.message.sender.to_string())
+        .save(key.as_bytes(), &false)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::DisablePermitKey {
+            status: Success,
+        })?),
+    })
+}
+
 pub fn try_trigger_admin_command<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: &Env,
@@ -421,18 +1039,34 @@ pub fn try_trigger_admin_command<S: Storage, A: Api, Q: Querier>(
     };
 
     // With command validate that number of variables is equal
-    if admin_command.total_arguments != variables.len() as u16 {
+    if admin_command.params.len() != variables.len() {
         return Err(StdError::GenericErr {
             msg: "Variable number doesnt match up".to_string(),
             backtrace: None,
         });
     }
 
-    // Replace variable spaces
-    let mut finished_command = admin_command.msg;
-    for item in variables.iter() {
-        finished_command = finished_command.replacen(ADMIN_COMMAND_VARIABLE, item, 1);
+    // Type check and encode every argument before it touches the template
+    let mut arguments = vec![];
+    for (param, value) in admin_command.params.iter().zip(variables.iter()) {
+        arguments.push((
+            param.name.clone(),
+            encode_admin_argument(&deps.api, &param.param_type, value)?,
+        ));
+    }
+
+    let finished_command = render_admin_command(&admin_command.msg, |name| {
+        arguments
+            .iter()
+            .find(|(param, _)| param == name)
+            .map(|(_, value)| value.clone())
+            .ok_or_else(|| StdError::not_found(format!("Admin command param {}", name)))
+    })?;
+    if from_slice::<IgnoredAny>(finished_command.as_bytes()).is_err() {
+        return Err(StdError::generic_err("Admin command is not valid JSON"));
     }
+    let finished_command = Binary::from(finished_command.as_bytes());
+    validate_proposal_msg(&deps.storage, &target, &finished_command)?;
 
     let mut messages = vec![];
 
@@ -445,31 +1079,43 @@ pub fn try_trigger_admin_command<S: Storage, A: Api, Q: Querier>(
     // Try to run
     let proposal = Proposal {
         id: proposal_id,
-        target,
-        msg: Binary::from(finished_command.as_bytes()),
+        actions: vec![ProposalAction {
+            target,
+            msg: finished_command.clone(),
+            funds: vec![],
+            instantiate: None,
+        }],
         description,
+        proposer: env.message.sender.clone(),
+        metadata: ProposalMetadata {
+            title: command,
+            ..Default::default()
+        },
     };
 
     // Store the proposal
     proposal_w(&mut deps.storage).save(proposal_id.to_string().as_bytes(), &proposal)?;
+    index_proposal(&mut deps.storage, &proposal)?;
     proposal_funding_deadline_w(&mut deps.storage)
         .save(proposal_id.to_string().as_bytes(), &env.block.time)?;
     proposal_voting_deadline_w(&mut deps.storage)
         .save(proposal_id.to_string().as_bytes(), &env.block.time)?;
-    proposal_status_w(&mut deps.storage).save(
-        proposal_id.to_string().as_bytes(),
+    save_proposal_status(
+        &mut deps.storage,
+        proposal_id,
         &ProposalStatus::AdminRequested,
     )?;
-    let run_status =
-        match try_execute_msg(target_contract, Binary::from(finished_command.as_bytes())) {
-            Ok(executed_msg) => {
-                messages.push(executed_msg);
-                Success
-            }
-            Err(_) => Failure,
-        };
-    proposal_run_status_w(&mut deps.storage)
-        .save(proposal_id.to_string().as_bytes(), &run_status)?;
+    let run_status = match try_execute_msg(target_contract, finished_command, vec![]) {
+        Ok(executed_msg) => {
+            messages.push(executed_msg);
+            messages.push(confirm_execution_msg(env, proposal_id)?);
+            Success
+        }
+        Err(_) => Failure,
+    };
+    if run_status == Failure {
+        save_run_status(&mut deps.storage, proposal_id, Failure, 1)?;
+    }
 
     Ok(HandleResponse {
         messages,
@@ -481,6 +1127,84 @@ pub fn try_trigger_admin_command<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+// Replaces every {{param}} in a single pass so arguments are never rescanned
+fn render_admin_command<F>(msg: &str, mut value: F) -> StdResult<String>
+where
+    F: FnMut(&str) -> StdResult<String>,
+{
+    let mut rendered = String::new();
+    let mut rest = msg;
+
+    while let Some(start) = rest.find(ADMIN_COMMAND_PARAM_OPEN) {
+        rendered.push_str(&rest[..start]);
+        rest = &rest[start + ADMIN_COMMAND_PARAM_OPEN.len()..];
+
+        let end = rest
+            .find(ADMIN_COMMAND_PARAM_CLOSE)
+            .ok_or_else(|| StdError::generic_err("Admin command param is not closed"))?;
+        rendered.push_str(&value(&rest[..end])?);
+        rest = &rest[end + ADMIN_COMMAND_PARAM_CLOSE.len()..];
+    }
+    rendered.push_str(rest);
+
+    Ok(rendered)
+}
+
+fn encode_admin_argument<A: Api>(
+    api: &A,
+    param_type: &AdminCommandParamType,
+    value: &str,
+) -> StdResult<String> {
+    let invalid = || StdError::generic_err(format!("{} is not a valid {:?}", value, param_type));
+
+    let encoded = match param_type {
+        AdminCommandParamType::Address => {
+            api.canonical_address(&HumanAddr::from(value))?;
+            to_vec(&value)?
+        }
+        AdminCommandParamType::Uint128 => {
+            to_vec(&Uint128(value.parse::<u128>().map_err(|_| invalid())?))?
+        }
+        AdminCommandParamType::String => to_vec(&value)?,
+        AdminCommandParamType::Bool => to_vec(&value.parse::<bool>().map_err(|_| invalid())?)?,
+        AdminCommandParamType::Json => {
+            from_slice::<IgnoredAny>(value.as_bytes()).map_err(|_| invalid())?;
+            value.as_bytes().to_vec()
+        }
+    };
+
+    String::from_utf8(encoded).map_err(|_| invalid())
+}
+
+// Every param must be named once and every placeholder must be a param
+pub fn validate_admin_command(msg: &str, params: &[AdminCommandParam]) -> StdResult<()> {
+    for (i, param) in params.iter().enumerate() {
+        if param.name.is_empty() || params[..i].iter().any(|p| p.name == param.name) {
+            return Err(StdError::generic_err(
+                "Admin command params must have unique names",
+            ));
+        }
+    }
+
+    let mut used = vec![];
+    render_admin_command(msg, |name| {
+        if !params.iter().any(|param| param.name == name) {
+            return Err(StdError::not_found(format!("Admin command param {}", name)));
+        }
+        used.push(name.to_string());
+        Ok(String::new())
+    })?;
+
+    if let Some(param) = params.iter().find(|param| !used.contains(&param.name)) {
+        return Err(StdError::generic_err(format!(
+            "Admin command param {} is never used",
+            param.name
+        )));
+    }
+
+    Ok(())
+}
+
 /// SELF only interactions
 
 pub fn try_create_proposal<S: Storage, A: Api, Q: Querier>(
@@ -489,8 +1213,78 @@ pub fn try_create_proposal<S: Storage, A: Api, Q: Querier>(
     target_contract: String,
     proposal: Binary,
     description: String,
+    metadata: Option<ProposalMetadata>,
+) -> StdResult<HandleResponse> {
+    let proposal_id = create_proposal(
+        deps,
+        env,
+        vec![ProposalAction {
+            target: target_contract,
+            msg: proposal,
+            funds: vec![],
+            instantiate: None,
+        }],
+        description,
+        metadata,
+    )?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::CreateProposal {
+            status: Success,
+            proposal_id,
+        })?),
+    })
+}
+
+pub fn try_create_multi_proposal<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    actions: Vec<ProposalAction>,
+    description: String,
+    metadata: Option<ProposalMetadata>,
+) -> StdResult<HandleResponse> {
+    let proposal_id = create_proposal(deps, env, actions, description, metadata)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::CreateProposal {
+            status: Success,
+            proposal_id,
+        })?),
+    })
+}
+
+pub fn try_create_emergency_proposal<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    actions: Vec<ProposalAction>,
+    description: String,
+    metadata: Option<ProposalMetadata>,
 ) -> StdResult<HandleResponse> {
-    let proposal_id = create_proposal(deps, env, target_contract, proposal, description)?;
+    let settings = emergency_settings_r(&deps.storage)
+        .may_load()?
+        .flatten()
+        .ok_or_else(|| StdError::generic_err("Emergency proposals are disabled"))?;
+
+    // Only the council can skip funding
+    if !settings.council.contains(&env.message.sender) {
+        return Err(StdError::unauthorized());
+    }
+
+    let proposal_id = create_proposal(deps, env, actions, description, metadata)?;
+    let key = proposal_id.to_string();
+
+    // Voting starts right away
+    proposal_funding_deadline_w(&mut deps.storage).save(key.as_bytes(), &env.block.time)?;
+    save_proposal_status(&mut deps.storage, proposal_id, &ProposalStatus::Voting)?;
+    proposal_voting_deadline_w(&mut deps.storage)
+        .save(key.as_bytes(), &(env.block.time + settings.voting_deadline))?;
+    proposal_snapshot_w(&mut deps.storage).save(key.as_bytes(), &env.block.height)?;
+    // Later settings changes do not affect running proposals
+    proposal_emergency_w(&mut deps.storage).save(key.as_bytes(), &settings)?;
 
     Ok(HandleResponse {
         messages: vec![],
@@ -510,14 +1304,22 @@ pub fn try_update_config<S: Storage, A: Api, Q: Querier>(
     staker: Option<Contract>,
     proposal_deadline: Option<u64>,
     funding_amount: Option<Uint128>,
+    funding_policy: Option<FundingPolicy>,
     funding_deadline: Option<u64>,
-    minimum_votes: Option<Uint128>,
+    vote_settings: Option<VoteSettings>,
+    guardian: Option<HumanAddr>,
+    timelock: Option<u64>,
+    execution_window: Option<u64>,
 ) -> StdResult<HandleResponse> {
     // It has to be self
     if env.contract.address != env.message.sender {
         return Err(StdError::Unauthorized { backtrace: None });
     }
 
+    if let Some(vote_settings) = &vote_settings {
+        validate_vote_settings(vote_settings)?;
+    }
+
     config_w(&mut deps.storage).update(|mut state| {
         if let Some(admin) = admin {
             state.admin = admin;
@@ -531,11 +1333,23 @@ pub fn try_update_config<S: Storage, A: Api, Q: Querier>(
         if let Some(funding_amount) = funding_amount {
             state.funding_amount = funding_amount;
         }
+        if let Some(funding_policy) = funding_policy {
+            state.funding_policy = funding_policy;
+        }
         if let Some(funding_deadline) = funding_deadline {
             state.funding_deadline = funding_deadline;
         }
-        if let Some(minimum_votes) = minimum_votes {
-            state.minimum_votes = minimum_votes;
+        if let Some(vote_settings) = vote_settings {
+            state.vote_settings = vote_settings;
+        }
+        if guardian.is_some() {
+            state.guardian = guardian;
+        }
+        if let Some(timelock) = timelock {
+            state.timelock = timelock;
+        }
+        if execution_window.is_some() {
+            state.execution_window = execution_window;
         }
 
         Ok(state)
@@ -548,6 +1362,197 @@ pub fn try_update_config<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+pub fn validate_vote_settings(settings: &VoteSettings) -> StdResult<()> {
+    let max = Uint128(ONE_HUNDRED_PERCENT);
+    if settings.quorum > max
+        || settings.threshold > max
+        || settings.veto.map_or(false, |veto| veto > max)
+    {
+        return Err(StdError::generic_err("Vote settings cannot exceed 100%"));
+    }
+    Ok(())
+}
+
+pub fn try_set_vote_settings<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    target: String,
+    settings: Option<VoteSettings>,
+) -> StdResult<HandleResponse> {
+    // It has to be self
+    if env.contract.address != env.message.sender {
+        return Err(StdError::Unauthorized { backtrace: None });
+    }
+
+    // Target must be able to receive proposals
+    if target != GOVERNANCE_SELF
+        && supported_contract_r(&deps.storage)
+            .may_load(target.as_bytes())?
+            .is_none()
+    {
+        return Err(StdError::not_found("Supported contract"));
+    }
+
+    match settings {
+        Some(settings) => {
+            validate_vote_settings(&settings)?;
+            vote_settings_w(&mut deps.storage).save(target.as_bytes(), &settings)?;
+        }
+        None => vote_settings_w(&mut deps.storage).remove(target.as_bytes()),
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetVoteSettings {
+            status: Success,
+        })?),
+    })
+}
+
+pub fn try_set_emergency_settings<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    settings: Option<EmergencySettings>,
+) -> StdResult<HandleResponse> {
+    // It has to be self
+    if env.contract.address != env.message.sender {
+        return Err(StdError::Unauthorized { backtrace: None });
+    }
+
+    if let Some(settings) = &settings {
+        if settings.council.is_empty() {
+            return Err(StdError::generic_err("Emergency council cannot be empty"));
+        }
+        validate_vote_settings(&settings.vote_settings)?;
+
+        // Shorter votes must be backed by more of the stake
+        let config = config_r(&deps.storage).load()?;
+        if settings.vote_settings.quorum < config.vote_settings.quorum {
+            return Err(StdError::generic_err(
+                "Emergency quorum cannot be below the default quorum",
+            ));
+        }
+    }
+
+    emergency_settings_w(&mut deps.storage).save(&settings)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetEmergencySettings {
+            status: Success,
+        })?),
+    })
+}
+
+pub fn try_set_timelock<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    target: String,
+    delay: Option<u64>,
+) -> StdResult<HandleResponse> {
+    // It has to be self
+    if env.contract.address != env.message.sender {
+        return Err(StdError::Unauthorized { backtrace: None });
+    }
+
+    // Target must be able to receive proposals
+    if target != GOVERNANCE_SELF
+        && supported_contract_r(&deps.storage)
+            .may_load(target.as_bytes())?
+            .is_none()
+    {
+        return Err(StdError::not_found("Supported contract"));
+    }
+
+    match delay {
+        Some(delay) => timelock_w(&mut deps.storage).save(target.as_bytes(), &delay)?,
+        None => timelock_w(&mut deps.storage).remove(target.as_bytes()),
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetTimelock { status: Success })?),
+    })
+}
+
+pub fn try_set_contract_schema<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    target: String,
+    schema: Option<MessageSchema>,
+) -> StdResult<HandleResponse> {
+    // It has to be self
+    if env.contract.address != env.message.sender {
+        return Err(StdError::Unauthorized { backtrace: None });
+    }
+
+    // Target must be able to receive proposals
+    if target != GOVERNANCE_SELF
+        && supported_contract_r(&deps.storage)
+            .may_load(target.as_bytes())?
+            .is_none()
+    {
+        return Err(StdError::not_found("Supported contract"));
+    }
+
+    match schema {
+        Some(schema) => contract_schema_w(&mut deps.storage).save(target.as_bytes(), &schema)?,
+        None => contract_schema_w(&mut deps.storage).remove(target.as_bytes()),
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetContractSchema {
+            status: Success,
+        })?),
+    })
+}
+
+// Proposals to a target with a registered schema must be one of its messages
+pub fn validate_proposal_msg<S: Storage>(storage: &S, target: &str, msg: &Binary) -> StdResult<()> {
+    let schema = match contract_schema_r(storage).may_load(target.as_bytes())? {
+        None => return Ok(()),
+        Some(schema) => schema,
+    };
+
+    let parsed: BTreeMap<String, BTreeMap<String, IgnoredAny>> = from_slice(msg.as_slice())
+        .map_err(|_| StdError::generic_err(format!("Proposal to {} is not a message", target)))?;
+    if parsed.len() != 1 {
+        return Err(StdError::generic_err(
+            "Proposal must contain exactly one message",
+        ));
+    }
+
+    for (name, fields) in parsed.iter() {
+        let variant = schema
+            .variant(name)
+            .ok_or_else(|| StdError::not_found(format!("{} message {}", target, name)))?;
+
+        if let Some(field) = fields.keys().find(|field| !variant.fields.contains(*field)) {
+            return Err(StdError::generic_err(format!(
+                "Unknown field {} in {}",
+                field, name
+            )));
+        }
+        if let Some(field) = variant
+            .required
+            .iter()
+            .find(|field| !fields.contains_key(*field))
+        {
+            return Err(StdError::generic_err(format!(
+                "Missing field {} in {}",
+                field, name
+            )));
+        }
+    }
+
+    Ok(())
+}
+
 pub fn try_disable_staker<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     _env: &Env,
@@ -667,12 +1672,15 @@ pub fn try_add_admin_command<S: Storage, A: Api, Q: Querier>(
     env: &Env,
     name: String,
     proposal: String,
+    params: Vec<AdminCommandParam>,
 ) -> StdResult<HandleResponse> {
     // It has to be self
     if env.contract.address != env.message.sender {
         return Err(StdError::Unauthorized { backtrace: None });
     }
 
+    validate_admin_command(&proposal, &params)?;
+
     // Admin command cannot exist
     if admin_commands_r(&deps.storage)
         .may_load(name.as_bytes())?
@@ -685,8 +1693,8 @@ pub fn try_add_admin_command<S: Storage, A: Api, Q: Querier>(
     admin_commands_w(&mut deps.storage).save(
         name.as_bytes(),
         &AdminCommand {
-            msg: proposal.clone(),
-            total_arguments: proposal.matches(ADMIN_COMMAND_VARIABLE).count() as u16,
+            msg: proposal,
+            params,
         },
     )?;
 
@@ -738,17 +1746,20 @@ pub fn try_update_admin_command<S: Storage, A: Api, Q: Querier>(
     env: &Env,
     name: String,
     proposal: String,
+    params: Vec<AdminCommandParam>,
 ) -> StdResult<HandleResponse> {
     // It has to be self
     if env.contract.address != env.message.sender {
         return Err(StdError::Unauthorized { backtrace: None });
     }
 
+    validate_admin_command(&proposal, &params)?;
+
     // Replace contract
     admin_commands_w(&mut deps.storage).update(name.as_bytes(), |_state| {
         Ok(AdminCommand {
             msg: proposal.clone(),
-            total_arguments: proposal.matches(ADMIN_COMMAND_VARIABLE).count() as u16,
+            params: params.clone(),
         })
     })?;
 
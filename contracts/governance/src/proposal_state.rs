@@ -1,4 +1,4 @@
-use cosmwasm_std::{Storage, Uint128};
+use cosmwasm_std::{HumanAddr, StdResult, Storage, Uint128};
 use cosmwasm_storage::{
     bucket, bucket_read, singleton, singleton_read, Bucket, ReadonlyBucket, ReadonlySingleton,
     Singleton,
@@ -7,6 +7,7 @@ use secret_toolkit::snip20::batch::SendAction;
 use shade_protocol::governance::{
     proposal::{Proposal, ProposalStatus},
     vote::VoteTally,
+    EmergencySettings,
 };
 use shade_protocol::utils::generic_response::ResponseStatus;
 
@@ -14,13 +15,22 @@ use shade_protocol::utils::generic_response::ResponseStatus;
 pub static PROPOSAL_KEY: &[u8] = b"proposals";
 pub static PROPOSAL_VOTE_DEADLINE_KEY: &[u8] = b"proposal_vote_deadline_key";
 pub static PROPOSAL_FUNDING_DEADLINE_KEY: &[u8] = b"proposal_funding_deadline_key";
+pub static PROPOSAL_EXECUTION_TIME_KEY: &[u8] = b"proposal_execution_time_key";
+pub static PROPOSAL_EXPIRATION_TIME_KEY: &[u8] = b"proposal_expiration_time_key";
+pub static PROPOSAL_SNAPSHOT_KEY: &[u8] = b"proposal_snapshot_key";
+pub static PROPOSAL_EMERGENCY_KEY: &[u8] = b"proposal_emergency_key";
 pub static PROPOSAL_STATUS_KEY: &[u8] = b"proposal_status_key";
 pub static PROPOSAL_RUN_KEY: &[u8] = b"proposal_run_key";
+pub static PROPOSAL_RUN_ERROR_KEY: &[u8] = b"proposal_run_error_key";
+pub static PROPOSAL_ACTION_RUN_KEY: &[u8] = b"proposal_action_run_key";
 pub static PROPOSAL_FUNDING_KEY: &[u8] = b"proposal_funding_key";
 pub static PROPOSAL_FUNDING_BATCH_KEY: &[u8] = b"proposal_funding_batch_key";
 pub static PROPOSAL_VOTES_KEY: &str = "proposal_votes";
 pub static TOTAL_PROPOSAL_VOTES_KEY: &[u8] = b"total_proposal_votes";
 pub static TOTAL_PROPOSAL_KEY: &[u8] = b"total_proposals";
+pub static PROPOSAL_INDEX_KEY: &str = "proposal_index";
+pub static PROPOSAL_INDEX_MEMBER_KEY: &str = "proposal_index_member";
+pub static PROPOSAL_INDEX_COUNT_KEY: &[u8] = b"proposal_index_count";
 
 // Total proposal counter
 pub fn total_proposals_w<S: Storage>(storage: &mut S) -> Singleton<S, Uint128> {
@@ -58,6 +68,42 @@ pub fn proposal_voting_deadline_w<S: Storage>(storage: &mut S) -> Bucket<S, u64>
     bucket(PROPOSAL_VOTE_DEADLINE_KEY, storage)
 }
 
+// Proposal timelock end
+pub fn proposal_execution_time_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, u64> {
+    bucket_read(PROPOSAL_EXECUTION_TIME_KEY, storage)
+}
+
+pub fn proposal_execution_time_w<S: Storage>(storage: &mut S) -> Bucket<S, u64> {
+    bucket(PROPOSAL_EXECUTION_TIME_KEY, storage)
+}
+
+// Proposal execution expiration
+pub fn proposal_expiration_time_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, u64> {
+    bucket_read(PROPOSAL_EXPIRATION_TIME_KEY, storage)
+}
+
+pub fn proposal_expiration_time_w<S: Storage>(storage: &mut S) -> Bucket<S, u64> {
+    bucket(PROPOSAL_EXPIRATION_TIME_KEY, storage)
+}
+
+// Proposal voting snapshot height
+pub fn proposal_snapshot_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, u64> {
+    bucket_read(PROPOSAL_SNAPSHOT_KEY, storage)
+}
+
+pub fn proposal_snapshot_w<S: Storage>(storage: &mut S) -> Bucket<S, u64> {
+    bucket(PROPOSAL_SNAPSHOT_KEY, storage)
+}
+
+// Emergency settings a proposal was created with
+pub fn proposal_emergency_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, EmergencySettings> {
+    bucket_read(PROPOSAL_EMERGENCY_KEY, storage)
+}
+
+pub fn proposal_emergency_w<S: Storage>(storage: &mut S) -> Bucket<S, EmergencySettings> {
+    bucket(PROPOSAL_EMERGENCY_KEY, storage)
+}
+
 // Proposal status
 pub fn proposal_status_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, ProposalStatus> {
     bucket_read(PROPOSAL_STATUS_KEY, storage)
@@ -67,6 +113,16 @@ pub fn proposal_status_w<S: Storage>(storage: &mut S) -> Bucket<S, ProposalStatu
     bucket(PROPOSAL_STATUS_KEY, storage)
 }
 
+// Saves the status and adds the proposal to that status' index
+pub fn save_proposal_status<S: Storage>(
+    storage: &mut S,
+    proposal_id: Uint128,
+    status: &ProposalStatus,
+) -> StdResult<()> {
+    proposal_status_w(storage).save(proposal_id.to_string().as_bytes(), status)?;
+    push_proposal_index(storage, &status_index(status), proposal_id)
+}
+
 // Proposal total funding
 pub fn proposal_funding_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, Uint128> {
     bucket_read(PROPOSAL_FUNDING_KEY, storage)
@@ -94,6 +150,24 @@ pub fn proposal_run_status_w<S: Storage>(storage: &mut S) -> Bucket<S, ResponseS
     bucket(PROPOSAL_RUN_KEY, storage)
 }
 
+// Proposal run error - available when a failed execution is reported
+pub fn proposal_run_error_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, String> {
+    bucket_read(PROPOSAL_RUN_ERROR_KEY, storage)
+}
+
+pub fn proposal_run_error_w<S: Storage>(storage: &mut S) -> Bucket<S, String> {
+    bucket(PROPOSAL_RUN_ERROR_KEY, storage)
+}
+
+// Proposal run status of each action
+pub fn proposal_action_status_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, Vec<ResponseStatus>> {
+    bucket_read(PROPOSAL_ACTION_RUN_KEY, storage)
+}
+
+pub fn proposal_action_status_w<S: Storage>(storage: &mut S) -> Bucket<S, Vec<ResponseStatus>> {
+    bucket(PROPOSAL_ACTION_RUN_KEY, storage)
+}
+
 // Individual proposal user votes
 pub fn proposal_votes_r<S: Storage>(
     storage: &S,
@@ -120,3 +194,73 @@ pub fn total_proposal_votes_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, Vote
 pub fn total_proposal_votes_w<S: Storage>(storage: &mut S) -> Bucket<S, VoteTally> {
     bucket(TOTAL_PROPOSAL_VOTES_KEY, storage)
 }
+
+// Secondary indexes, index -> position -> proposal ID in the order proposals were added
+pub fn proposal_index_r<S: Storage>(storage: &S, index: &str) -> ReadonlyBucket<S, Uint128> {
+    bucket_read((index.to_string() + PROPOSAL_INDEX_KEY).as_bytes(), storage)
+}
+
+pub fn proposal_index_w<S: Storage>(storage: &mut S, index: &str) -> Bucket<S, Uint128> {
+    bucket((index.to_string() + PROPOSAL_INDEX_KEY).as_bytes(), storage)
+}
+
+pub fn proposal_index_count_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, u64> {
+    bucket_read(PROPOSAL_INDEX_COUNT_KEY, storage)
+}
+
+pub fn proposal_index_count_w<S: Storage>(storage: &mut S) -> Bucket<S, u64> {
+    bucket(PROPOSAL_INDEX_COUNT_KEY, storage)
+}
+
+// index -> proposal ID, keeps a proposal from being added twice
+pub fn proposal_index_member_r<S: Storage>(storage: &S, index: &str) -> ReadonlyBucket<S, bool> {
+    bucket_read(
+        (index.to_string() + PROPOSAL_INDEX_MEMBER_KEY).as_bytes(),
+        storage,
+    )
+}
+
+pub fn proposal_index_member_w<S: Storage>(storage: &mut S, index: &str) -> Bucket<S, bool> {
+    bucket(
+        (index.to_string() + PROPOSAL_INDEX_MEMBER_KEY).as_bytes(),
+        storage,
+    )
+}
+
+pub fn push_proposal_index<S: Storage>(
+    storage: &mut S,
+    index: &str,
+    proposal_id: Uint128,
+) -> StdResult<()> {
+    let key = proposal_id.to_string();
+    if proposal_index_member_r(storage, index)
+        .may_load(key.as_bytes())?
+        .is_some()
+    {
+        return Ok(());
+    }
+
+    let count = proposal_index_count_r(storage)
+        .may_load(index.as_bytes())?
+        .unwrap_or(0);
+    proposal_index_w(storage, index).save(&count.to_be_bytes(), &proposal_id)?;
+    proposal_index_count_w(storage).save(index.as_bytes(), &(count + 1))?;
+    proposal_index_member_w(storage, index).save(key.as_bytes(), &true)
+}
+
+pub fn proposer_index(proposer: &HumanAddr) -> String {
+    format!("proposer/{}/", proposer)
+}
+
+pub fn target_index(target: &str) -> String {
+    format!("target/{}/", target)
+}
+
+pub fn category_index(category: &str) -> String {
+    format!("category/{}/", category)
+}
+
+// Statuses are never removed from, a proposal can appear in a status it already left
+pub fn status_index(status: &ProposalStatus) -> String {
+    format!("status/{:?}/", status)
+}
@@ -1,16 +1,24 @@
-use cosmwasm_std::{Api, Extern, Querier, StdError, StdResult, Storage, Uint128};
+use cosmwasm_std::{Api, Extern, HumanAddr, Querier, StdError, StdResult, Storage, Uint128};
+use query_authentication::viewing_keys::ViewingKey;
 use shade_protocol::governance::{
-    proposal::{ProposalStatus, QueriedProposal},
+    proposal::{ProposalFilter, ProposalStatus, QueriedProposal},
+    vote::{VoterKey, VoterPermit},
     QueryAnswer,
 };
 
 use crate::{
     proposal_state::{
-        proposal_funding_deadline_r, proposal_funding_r, proposal_r, proposal_run_status_r,
-        proposal_status_r, proposal_voting_deadline_r, total_proposal_votes_r, total_proposals_r,
+        category_index, proposal_action_status_r, proposal_emergency_r, proposal_execution_time_r,
+        proposal_expiration_time_r, proposal_funding_deadline_r, proposal_funding_r,
+        proposal_index_count_r, proposal_index_r, proposal_r, proposal_run_error_r,
+        proposal_run_status_r, proposal_snapshot_r, proposal_status_r, proposal_votes_r,
+        proposal_voting_deadline_r, proposer_index, status_index, target_index,
+        total_proposal_votes_r, total_proposals_r,
     },
     state::{
-        admin_commands_list_r, admin_commands_r, supported_contract_r, supported_contracts_list_r,
+        admin_commands_list_r, admin_commands_r, config_r, contract_schema_r, emergency_settings_r,
+        pending_upgrade_r, self_address_r, supported_contract_r, supported_contracts_list_r,
+        timelock_r, vote_settings_r, voter_permit_key_r, voter_viewing_key_r,
     },
 };
 
@@ -22,18 +30,32 @@ fn build_proposal<S: Storage, A: Api, Q: Querier>(
 
     Ok(QueriedProposal {
         id: proposal.id,
-        target: proposal.target,
-        msg: proposal.msg,
+        actions: proposal.actions,
         description: proposal.description,
+        proposer: proposal.proposer,
+        metadata: proposal.metadata,
+        emergency: proposal_emergency_r(&deps.storage)
+            .may_load(proposal_id.to_string().as_bytes())?
+            .is_some(),
         funding_deadline: proposal_funding_deadline_r(&deps.storage)
             .load(proposal_id.to_string().as_bytes())?,
         voting_deadline: proposal_voting_deadline_r(&deps.storage)
             .may_load(proposal_id.to_string().as_bytes())?,
         total_funding: proposal_funding_r(&deps.storage)
             .load(proposal_id.to_string().as_bytes())?,
+        execution_time: proposal_execution_time_r(&deps.storage)
+            .may_load(proposal_id.to_string().as_bytes())?,
+        expiration_time: proposal_expiration_time_r(&deps.storage)
+            .may_load(proposal_id.to_string().as_bytes())?,
+        snapshot: proposal_snapshot_r(&deps.storage)
+            .may_load(proposal_id.to_string().as_bytes())?,
         status: proposal_status_r(&deps.storage).load(proposal_id.to_string().as_bytes())?,
         run_status: proposal_run_status_r(&deps.storage)
             .may_load(proposal_id.to_string().as_bytes())?,
+        run_error: proposal_run_error_r(&deps.storage)
+            .may_load(proposal_id.to_string().as_bytes())?,
+        action_status: proposal_action_status_r(&deps.storage)
+            .may_load(proposal_id.to_string().as_bytes())?,
     })
 }
 
@@ -71,6 +93,82 @@ pub fn proposals<S: Storage, A: Api, Q: Querier>(
     Ok(QueryAnswer::Proposals { proposals })
 }
 
+// Upper bound on index entries read by a single search
+const MAX_SEARCH_SCAN: u64 = 100;
+const DEFAULT_SEARCH_LIMIT: u8 = 10;
+
+pub fn search_proposals<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    filter: ProposalFilter,
+    cursor: Option<u64>,
+    limit: Option<u8>,
+) -> StdResult<QueryAnswer> {
+    // Walk the first index that is set, the rest of the filter is checked per proposal
+    let index = if let Some(proposer) = &filter.proposer {
+        Some(proposer_index(proposer))
+    } else if let Some(target) = &filter.target {
+        Some(target_index(target))
+    } else if let Some(category) = &filter.category {
+        Some(category_index(category))
+    } else {
+        filter.status.as_ref().map(status_index)
+    };
+
+    let count = match &index {
+        Some(index) => proposal_index_count_r(&deps.storage)
+            .may_load(index.as_bytes())?
+            .unwrap_or(0),
+        None => total_proposals_r(&deps.storage).load()?.u128() as u64,
+    };
+
+    let limit = limit.unwrap_or(DEFAULT_SEARCH_LIMIT) as usize;
+    let mut position = cursor.unwrap_or(0);
+    let end = count.min(position + MAX_SEARCH_SCAN);
+    let mut proposals = vec![];
+
+    while position < end && proposals.len() < limit {
+        let proposal_id = match &index {
+            Some(index) => proposal_index_r(&deps.storage, index).load(&position.to_be_bytes())?,
+            None => Uint128(position as u128 + 1),
+        };
+        position += 1;
+
+        let proposal = build_proposal(deps, proposal_id)?;
+        if matches_filter(&proposal, &filter) {
+            proposals.push(proposal);
+        }
+    }
+
+    Ok(QueryAnswer::SearchProposals {
+        proposals,
+        cursor: if position < count {
+            Some(position)
+        } else {
+            None
+        },
+    })
+}
+
+fn matches_filter(proposal: &QueriedProposal, filter: &ProposalFilter) -> bool {
+    filter
+        .proposer
+        .as_ref()
+        .map_or(true, |proposer| *proposer == proposal.proposer)
+        && filter.target.as_ref().map_or(true, |target| {
+            proposal
+                .actions
+                .iter()
+                .any(|action| action.target == *target)
+        })
+        && filter.category.as_ref().map_or(true, |category| {
+            proposal.metadata.category.as_ref() == Some(category)
+        })
+        && filter
+            .status
+            .as_ref()
+            .map_or(true, |status| *status == proposal.status)
+}
+
 pub fn proposal<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
     proposal_id: Uint128,
@@ -97,6 +195,57 @@ pub fn proposal_votes<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+fn voter_vote<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    proposal_id: Uint128,
+    voter: HumanAddr,
+) -> StdResult<QueryAnswer> {
+    Ok(QueryAnswer::VoterVote {
+        votes: proposal_votes_r(&deps.storage, proposal_id)
+            .may_load(voter.to_string().as_bytes())?,
+    })
+}
+
+pub fn voter_vote_with_key<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    proposal_id: Uint128,
+    voter: HumanAddr,
+    key: String,
+) -> StdResult<QueryAnswer> {
+    let stored_hash = voter_viewing_key_r(&deps.storage)
+        .may_load(voter.to_string().as_bytes())?
+        .ok_or_else(|| StdError::unauthorized())?;
+
+    if !VoterKey(key).compare(&stored_hash) {
+        return Err(StdError::unauthorized());
+    }
+
+    voter_vote(deps, proposal_id, voter)
+}
+
+pub fn voter_vote_with_permit<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    proposal_id: Uint128,
+    permit: VoterPermit,
+) -> StdResult<QueryAnswer> {
+    // Check that permit was made for this contract
+    if permit.params.contract != self_address_r(&deps.storage).load()? {
+        return Err(StdError::generic_err("Permit is for a different contract"));
+    }
+
+    let voter = permit.validate(None)?.as_humanaddr(&deps.api)?;
+
+    // Check that permit is not revoked
+    if voter_permit_key_r(&deps.storage, voter.to_string())
+        .may_load(permit.params.key.as_bytes())?
+        .is_some()
+    {
+        return Err(StdError::generic_err("Permit key has been revoked"));
+    }
+
+    voter_vote(deps, proposal_id, voter)
+}
+
 pub fn supported_contracts<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
 ) -> StdResult<QueryAnswer> {
@@ -130,3 +279,64 @@ pub fn admin_command<S: Storage, A: Api, Q: Querier>(
         command: admin_commands_r(&deps.storage).load(name.as_bytes())?,
     })
 }
+
+pub fn admin_command_params<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    name: String,
+) -> StdResult<QueryAnswer> {
+    Ok(QueryAnswer::AdminCommandParams {
+        params: admin_commands_r(&deps.storage)
+            .load(name.as_bytes())?
+            .params,
+    })
+}
+
+pub fn vote_settings<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    target: String,
+) -> StdResult<QueryAnswer> {
+    let settings = match vote_settings_r(&deps.storage).may_load(target.as_bytes())? {
+        Some(settings) => settings,
+        None => config_r(&deps.storage).load()?.vote_settings,
+    };
+
+    Ok(QueryAnswer::VoteSettings { settings })
+}
+
+pub fn timelock<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    target: String,
+) -> StdResult<QueryAnswer> {
+    let delay = match timelock_r(&deps.storage).may_load(target.as_bytes())? {
+        Some(delay) => delay,
+        None => config_r(&deps.storage).load()?.timelock,
+    };
+
+    Ok(QueryAnswer::Timelock { delay })
+}
+
+pub fn pending_upgrade<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    label: String,
+) -> StdResult<QueryAnswer> {
+    Ok(QueryAnswer::PendingUpgrade {
+        upgrade: pending_upgrade_r(&deps.storage).may_load(label.as_bytes())?,
+    })
+}
+
+pub fn emergency_settings<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<QueryAnswer> {
+    Ok(QueryAnswer::EmergencySettings {
+        settings: emergency_settings_r(&deps.storage).may_load()?.flatten(),
+    })
+}
+
+pub fn contract_schema<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    target: String,
+) -> StdResult<QueryAnswer> {
+    Ok(QueryAnswer::ContractSchema {
+        schema: contract_schema_r(&deps.storage).may_load(target.as_bytes())?,
+    })
+}
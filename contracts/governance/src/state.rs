@@ -1,9 +1,12 @@
-use cosmwasm_std::Storage;
+use cosmwasm_std::{HumanAddr, Storage};
 use cosmwasm_storage::{
     bucket, bucket_read, singleton, singleton_read, Bucket, ReadonlyBucket, ReadonlySingleton,
     Singleton,
 };
-use shade_protocol::governance::{AdminCommand, Config};
+use shade_protocol::governance::{
+    proposal::PendingUpgrade, schema::MessageSchema, AdminCommand, Config, EmergencySettings,
+    VoteSettings,
+};
 use shade_protocol::utils::asset::Contract;
 
 pub static CONFIG_KEY: &[u8] = b"config";
@@ -13,6 +16,20 @@ pub static CONTRACT_LIST_KEY: &[u8] = b"supported_contracts_list";
 // Admin commands
 pub static ADMIN_COMMANDS_KEY: &[u8] = b"admin_commands";
 pub static ADMIN_COMMANDS_LIST_KEY: &[u8] = b"admin_commands_list";
+// Vote settings overrides per target
+pub static VOTE_SETTINGS_KEY: &[u8] = b"vote_settings";
+// Timelock overrides per target
+pub static TIMELOCK_KEY: &[u8] = b"timelock";
+// Message schemas per target
+pub static CONTRACT_SCHEMA_KEY: &[u8] = b"contract_schema";
+// Emergency proposal settings
+pub static EMERGENCY_SETTINGS_KEY: &[u8] = b"emergency_settings";
+// Instantiated upgrades per label
+pub static PENDING_UPGRADE_KEY: &[u8] = b"pending_upgrade";
+// Voter authentication
+pub static SELF_ADDRESS_KEY: &[u8] = b"self_address";
+pub static VOTER_VIEWING_KEY: &[u8] = b"voter_viewing_key";
+pub static VOTER_PERMIT_KEY: &str = "voter_permit_key";
 
 pub fn config_w<S: Storage>(storage: &mut S) -> Singleton<S, Config> {
     singleton(storage, CONFIG_KEY)
@@ -57,3 +74,85 @@ pub fn admin_commands_list_w<S: Storage>(storage: &mut S) -> Singleton<S, Vec<St
 pub fn admin_commands_list_r<S: Storage>(storage: &S) -> ReadonlySingleton<S, Vec<String>> {
     singleton_read(storage, ADMIN_COMMANDS_LIST_KEY)
 }
+
+// Vote settings overrides
+
+pub fn vote_settings_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, VoteSettings> {
+    bucket_read(VOTE_SETTINGS_KEY, storage)
+}
+
+pub fn vote_settings_w<S: Storage>(storage: &mut S) -> Bucket<S, VoteSettings> {
+    bucket(VOTE_SETTINGS_KEY, storage)
+}
+
+// Timelock overrides
+
+pub fn timelock_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, u64> {
+    bucket_read(TIMELOCK_KEY, storage)
+}
+
+pub fn timelock_w<S: Storage>(storage: &mut S) -> Bucket<S, u64> {
+    bucket(TIMELOCK_KEY, storage)
+}
+
+// Emergency proposal settings
+
+pub fn emergency_settings_r<S: Storage>(
+    storage: &S,
+) -> ReadonlySingleton<S, Option<EmergencySettings>> {
+    singleton_read(storage, EMERGENCY_SETTINGS_KEY)
+}
+
+pub fn emergency_settings_w<S: Storage>(
+    storage: &mut S,
+) -> Singleton<S, Option<EmergencySettings>> {
+    singleton(storage, EMERGENCY_SETTINGS_KEY)
+}
+
+// Pending upgrades
+
+pub fn pending_upgrade_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, PendingUpgrade> {
+    bucket_read(PENDING_UPGRADE_KEY, storage)
+}
+
+pub fn pending_upgrade_w<S: Storage>(storage: &mut S) -> Bucket<S, PendingUpgrade> {
+    bucket(PENDING_UPGRADE_KEY, storage)
+}
+
+// Message schemas
+
+pub fn contract_schema_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, MessageSchema> {
+    bucket_read(CONTRACT_SCHEMA_KEY, storage)
+}
+
+pub fn contract_schema_w<S: Storage>(storage: &mut S) -> Bucket<S, MessageSchema> {
+    bucket(CONTRACT_SCHEMA_KEY, storage)
+}
+
+pub fn self_address_w<S: Storage>(storage: &mut S) -> Singleton<S, HumanAddr> {
+    singleton(storage, SELF_ADDRESS_KEY)
+}
+
+pub fn self_address_r<S: Storage>(storage: &S) -> ReadonlySingleton<S, HumanAddr> {
+    singleton_read(storage, SELF_ADDRESS_KEY)
+}
+
+// Voter viewing key hashes
+
+pub fn voter_viewing_key_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, [u8; 32]> {
+    bucket_read(VOTER_VIEWING_KEY, storage)
+}
+
+pub fn voter_viewing_key_w<S: Storage>(storage: &mut S) -> Bucket<S, [u8; 32]> {
+    bucket(VOTER_VIEWING_KEY, storage)
+}
+
+// Revoked voter permit keys
+
+pub fn voter_permit_key_r<S: Storage>(storage: &S, voter: String) -> ReadonlyBucket<S, bool> {
+    bucket_read((VOTER_PERMIT_KEY.to_string() + &voter).as_bytes(), storage)
+}
+
+pub fn voter_permit_key_w<S: Storage>(storage: &mut S, voter: String) -> Bucket<S, bool> {
+    bucket((VOTER_PERMIT_KEY.to_string() + &voter).as_bytes(), storage)
+}
@@ -1,18 +1,54 @@
 #[cfg(test)]
 mod tests {
-    use crate::contract;
+    use crate::{contract, handle::tally_status};
     use cosmwasm_std::{
         coins, from_binary,
-        testing::{mock_dependencies, mock_env},
-        Api, Extern, HumanAddr, Querier, Storage, Uint128,
+        testing::{mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage},
+        to_binary, Api, Binary, CosmosMsg, Extern, HumanAddr, Querier, Storage, Uint128, WasmMsg,
     };
+    use schemars::schema_for;
+    use shade_protocol::governance::schema::MessageSchema;
     use shade_protocol::utils::asset::Contract;
     use shade_protocol::utils::generic_response::ResponseStatus;
     use shade_protocol::{
         governance,
-        governance::proposal::{ProposalStatus, QueriedProposal},
+        governance::proposal::{
+            InstantiateAction, ProposalAction, ProposalFilter, ProposalMetadata, ProposalStatus,
+            QueriedProposal,
+        },
+        governance::vote::VoteTally,
     };
 
+    // Settings shared by the tests, each test overrides the fields it covers
+    fn init_msg() -> governance::InitMsg {
+        governance::InitMsg {
+            admin: None,
+            staker: None,
+            funding_token: Contract {
+                address: HumanAddr::from("funding_token"),
+                code_hash: String::from(""),
+            },
+            funding_amount: Uint128(1000),
+            funding_policy: None,
+            funding_deadline: 180,
+            voting_deadline: 180,
+            vote_settings: governance::VoteSettings {
+                quorum: Uint128(governance::ONE_HUNDRED_PERCENT / 10),
+                threshold: Uint128(governance::ONE_HUNDRED_PERCENT / 2),
+                veto: None,
+            },
+            guardian: None,
+            timelock: None,
+            execution_window: None,
+        }
+    }
+
+    fn init_governance(msg: governance::InitMsg) -> Extern<MockStorage, MockApi, MockQuerier> {
+        let mut deps = mock_dependencies(20, &coins(0, ""));
+        contract::init(&mut deps, mock_env("admin", &coins(0, "")), msg).unwrap();
+        deps
+    }
+
     #[test]
     fn get_proposals_by_status() {
         let mut deps = mock_dependencies(20, &coins(0, ""));
@@ -28,10 +64,17 @@ mod tests {
                 code_hash: String::from(""),
             },
             funding_amount: Uint128(1000000),
+            funding_policy: None,
             funding_deadline: 180,
             voting_deadline: 180,
-            // 5 shade is the minimum
-            quorum: Uint128(5000000),
+            vote_settings: governance::VoteSettings {
+                quorum: Uint128(governance::ONE_HUNDRED_PERCENT / 10),
+                threshold: Uint128(governance::ONE_HUNDRED_PERCENT / 2),
+                veto: None,
+            },
+            guardian: None,
+            timelock: None,
+            execution_window: None,
         };
         let res = contract::init(&mut deps, env, governance_init_msg).unwrap();
         assert_eq!(1, res.messages.len());
@@ -65,11 +108,17 @@ mod tests {
                 target_contract: String::from(governance::GOVERNANCE_SELF),
                 proposal: serde_json::to_string(&governance::HandleMsg::AddAdminCommand {
                     name: "random data here".to_string(),
-                    proposal: "{\"update_config\":{\"unbond_time\": {}, \"admin\": null}}"
-                        .to_string(),
+                    proposal:
+                        "{\"update_config\":{\"unbond_time\": {{unbond_time}}, \"admin\": null}}"
+                            .to_string(),
+                    params: vec![governance::AdminCommandParam {
+                        name: "unbond_time".to_string(),
+                        param_type: governance::AdminCommandParamType::Json,
+                    }],
                 })
                 .unwrap(),
                 description: String::from("Proposal on governance contract"),
+                metadata: None,
             },
         )
         .unwrap();
@@ -131,6 +180,984 @@ mod tests {
         );
     }
 
+    #[test]
+    fn revote_replaces_previous_vote() {
+        let mut deps = init_governance(governance::InitMsg {
+            admin: Some(HumanAddr::from("creator")),
+            staker: Some(Contract {
+                address: HumanAddr::from("staker"),
+                code_hash: String::from(""),
+            }),
+            ..init_msg()
+        });
+
+        contract::handle(
+            &mut deps,
+            mock_env("creator", &coins(0, "")),
+            governance::HandleMsg::CreateProposal {
+                target_contract: String::from(governance::GOVERNANCE_SELF),
+                proposal: "{}".to_string(),
+                description: String::from("Proposal"),
+                metadata: None,
+            },
+        )
+        .unwrap();
+
+        // Fully fund the proposal to start voting
+        contract::handle(
+            &mut deps,
+            mock_env("funding_token", &coins(0, "")),
+            governance::HandleMsg::Receive {
+                sender: HumanAddr::from("creator"),
+                amount: Uint128(1000),
+                msg: Some(to_binary(&Uint128(1)).unwrap()),
+            },
+        )
+        .unwrap();
+
+        let vote = |yes: u128, no: u128| governance::HandleMsg::MakeVote {
+            voter: HumanAddr::from("voter"),
+            proposal_id: Uint128(1),
+            votes: VoteTally {
+                yes: Uint128(yes),
+                no: Uint128(no),
+                abstain: Uint128::zero(),
+                veto: Uint128::zero(),
+            },
+        };
+        contract::handle(&mut deps, mock_env("staker", &coins(0, "")), vote(100, 0)).unwrap();
+        contract::handle(&mut deps, mock_env("staker", &coins(0, "")), vote(0, 40)).unwrap();
+
+        let res = contract::query(
+            &deps,
+            governance::QueryMsg::GetProposalVotes {
+                proposal_id: Uint128(1),
+            },
+        )
+        .unwrap();
+        match from_binary(&res).unwrap() {
+            governance::QueryAnswer::ProposalVotes { status } => {
+                assert_eq!(Uint128::zero(), status.yes);
+                assert_eq!(Uint128(40), status.no);
+            }
+            _ => panic!("Received wrong answer"),
+        }
+
+        // Voter can read their own vote with a viewing key
+        contract::handle(
+            &mut deps,
+            mock_env("voter", &coins(0, "")),
+            governance::HandleMsg::SetViewingKey {
+                key: "password".to_string(),
+            },
+        )
+        .unwrap();
+
+        let res = contract::query(
+            &deps,
+            governance::QueryMsg::GetVoterVote {
+                proposal_id: Uint128(1),
+                voter: HumanAddr::from("voter"),
+                key: "password".to_string(),
+            },
+        )
+        .unwrap();
+        match from_binary(&res).unwrap() {
+            governance::QueryAnswer::VoterVote { votes } => {
+                assert_eq!(Uint128(40), votes.unwrap().no);
+            }
+            _ => panic!("Received wrong answer"),
+        }
+
+        assert!(contract::query(
+            &deps,
+            governance::QueryMsg::GetVoterVote {
+                proposal_id: Uint128(1),
+                voter: HumanAddr::from("voter"),
+                key: "wrong".to_string(),
+            },
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn emergency_proposal() {
+        let mut deps = init_governance(governance::InitMsg {
+            admin: Some(HumanAddr::from("creator")),
+            staker: Some(Contract {
+                address: HumanAddr::from("staker"),
+                code_hash: String::from(""),
+            }),
+            ..init_msg()
+        });
+
+        let emergency = governance::HandleMsg::CreateEmergencyProposal {
+            actions: vec![ProposalAction {
+                target: String::from(governance::GOVERNANCE_SELF),
+                msg: Binary::from("{}".as_bytes()),
+                funds: vec![],
+                instantiate: None,
+            }],
+            description: String::from("Pause the mint"),
+            metadata: None,
+        };
+
+        // Disabled by default
+        assert!(contract::handle(
+            &mut deps,
+            mock_env("council", &coins(0, "")),
+            emergency.clone()
+        )
+        .is_err());
+
+        let settings = |quorum: u128| governance::HandleMsg::SetEmergencySettings {
+            settings: Some(governance::EmergencySettings {
+                council: vec![HumanAddr::from("council")],
+                voting_deadline: 60,
+                vote_settings: governance::VoteSettings {
+                    quorum: Uint128(quorum),
+                    threshold: Uint128(governance::ONE_HUNDRED_PERCENT / 2),
+                    veto: None,
+                },
+                timelock: 0,
+            }),
+        };
+        assert!(contract::handle(
+            &mut deps,
+            mock_env("anyone", &coins(0, "")),
+            settings(governance::ONE_HUNDRED_PERCENT / 2)
+        )
+        .is_err());
+        // Quorum cannot be lowered for emergencies
+        assert!(contract::handle(
+            &mut deps,
+            mock_env("cosmos2contract", &coins(0, "")),
+            settings(governance::ONE_HUNDRED_PERCENT / 20)
+        )
+        .is_err());
+        contract::handle(
+            &mut deps,
+            mock_env("cosmos2contract", &coins(0, "")),
+            settings(governance::ONE_HUNDRED_PERCENT / 2),
+        )
+        .unwrap();
+
+        assert!(contract::handle(
+            &mut deps,
+            mock_env("anyone", &coins(0, "")),
+            emergency.clone()
+        )
+        .is_err());
+        let env = mock_env("council", &coins(0, ""));
+        let now = env.block.time;
+        contract::handle(&mut deps, env, emergency).unwrap();
+
+        // Funding is skipped and voting uses the emergency deadline
+        let res = contract::query(
+            &deps,
+            governance::QueryMsg::GetProposal {
+                proposal_id: Uint128(1),
+            },
+        )
+        .unwrap();
+        match from_binary(&res).unwrap() {
+            governance::QueryAnswer::Proposal { proposal } => {
+                assert!(proposal.emergency);
+                assert_eq!(ProposalStatus::Voting, proposal.status);
+                assert_eq!(Some(now + 60), proposal.voting_deadline);
+                assert!(proposal.snapshot.is_some());
+            }
+            _ => panic!("Received wrong answer"),
+        }
+    }
+
+    #[test]
+    fn multi_action_proposal() {
+        let mut deps = init_governance(init_msg());
+
+        let action = |msg: &str| ProposalAction {
+            target: String::from(governance::GOVERNANCE_SELF),
+            msg: Binary::from(msg.as_bytes()),
+            funds: vec![],
+            instantiate: None,
+        };
+
+        // Targets must exist
+        assert!(contract::handle(
+            &mut deps,
+            mock_env("admin", &coins(0, "")),
+            governance::HandleMsg::CreateMultiProposal {
+                actions: vec![
+                    action("{}"),
+                    ProposalAction {
+                        target: String::from("missing"),
+                        msg: Binary::from("{}".as_bytes()),
+                        funds: vec![],
+                        instantiate: None,
+                    }
+                ],
+                description: String::from("Proposal"),
+                metadata: None,
+            },
+        )
+        .is_err());
+
+        contract::handle(
+            &mut deps,
+            mock_env("admin", &coins(0, "")),
+            governance::HandleMsg::CreateMultiProposal {
+                actions: vec![action("{\"disable_staker\":{}}"), action("{}")],
+                description: String::from("Proposal"),
+                metadata: None,
+            },
+        )
+        .unwrap();
+
+        let res = contract::handle(
+            &mut deps,
+            mock_env("admin", &coins(0, "")),
+            governance::HandleMsg::TriggerProposal {
+                proposal_id: Uint128(1),
+            },
+        )
+        .unwrap();
+        // Both actions followed by the execution confirmation
+        assert_eq!(3, res.messages.len());
+
+        // Nothing is recorded until the run is confirmed
+        let res = contract::query(
+            &deps,
+            governance::QueryMsg::GetProposal {
+                proposal_id: Uint128(1),
+            },
+        )
+        .unwrap();
+        match from_binary(&res).unwrap() {
+            governance::QueryAnswer::Proposal { proposal } => {
+                assert_eq!(None, proposal.run_status);
+                assert_eq!(None, proposal.action_status);
+            }
+            _ => panic!("Received wrong answer"),
+        }
+
+        let confirm = governance::HandleMsg::ConfirmExecution {
+            proposal_id: Uint128(1),
+            error: None,
+        };
+        assert!(
+            contract::handle(&mut deps, mock_env("admin", &coins(0, "")), confirm.clone()).is_err()
+        );
+        contract::handle(
+            &mut deps,
+            mock_env("cosmos2contract", &coins(0, "")),
+            confirm,
+        )
+        .unwrap();
+
+        let res = contract::query(
+            &deps,
+            governance::QueryMsg::GetProposal {
+                proposal_id: Uint128(1),
+            },
+        )
+        .unwrap();
+        match from_binary(&res).unwrap() {
+            governance::QueryAnswer::Proposal { proposal } => {
+                assert_eq!(ProposalStatus::Executed, proposal.status);
+                assert_eq!(Some(ResponseStatus::Success), proposal.run_status);
+                assert_eq!(
+                    Some(vec![ResponseStatus::Success, ResponseStatus::Success]),
+                    proposal.action_status
+                );
+            }
+            _ => panic!("Received wrong answer"),
+        }
+    }
+
+    #[test]
+    fn typed_admin_command() {
+        let mut deps = init_governance(init_msg());
+
+        let param = |name: &str, param_type| governance::AdminCommandParam {
+            name: name.to_string(),
+            param_type,
+        };
+        let add = |proposal: &str, params| governance::HandleMsg::AddAdminCommand {
+            name: "transfer".to_string(),
+            proposal: proposal.to_string(),
+            params,
+        };
+        let self_env = mock_env("cosmos2contract", &coins(0, ""));
+
+        contract::handle(
+            &mut deps,
+            self_env.clone(),
+            governance::HandleMsg::AddSupportedContract {
+                name: "token".to_string(),
+                contract: Contract {
+                    address: HumanAddr::from("token"),
+                    code_hash: String::from(""),
+                },
+            },
+        )
+        .unwrap();
+
+        // Placeholders and params must line up
+        assert!(contract::handle(
+            &mut deps,
+            self_env.clone(),
+            add(
+                "{\"transfer\":{\"amount\":{{amount}}}}",
+                vec![param(
+                    "recipient",
+                    governance::AdminCommandParamType::Address
+                )]
+            )
+        )
+        .is_err());
+        assert!(contract::handle(
+            &mut deps,
+            self_env.clone(),
+            add(
+                "{\"transfer\":{\"amount\":{{amount}}}}",
+                vec![
+                    param("amount", governance::AdminCommandParamType::Uint128),
+                    param("memo", governance::AdminCommandParamType::String)
+                ]
+            )
+        )
+        .is_err());
+
+        let params = vec![
+            param("amount", governance::AdminCommandParamType::Uint128),
+            param("memo", governance::AdminCommandParamType::String),
+        ];
+        contract::handle(
+            &mut deps,
+            self_env,
+            add(
+                "{\"transfer\":{\"amount\":{{amount}},\"memo\":{{memo}}}}",
+                params.clone(),
+            ),
+        )
+        .unwrap();
+
+        let res = contract::query(
+            &deps,
+            governance::QueryMsg::GetAdminCommandParams {
+                name: "transfer".to_string(),
+            },
+        )
+        .unwrap();
+        match from_binary(&res).unwrap() {
+            governance::QueryAnswer::AdminCommandParams { params: queried } => {
+                assert_eq!(params, queried);
+            }
+            _ => panic!("Received wrong answer"),
+        }
+
+        let trigger = |amount: &str, memo: &str| governance::HandleMsg::TriggerAdminCommand {
+            target: "token".to_string(),
+            command: "transfer".to_string(),
+            variables: vec![amount.to_string(), memo.to_string()],
+            description: "Admin triggered command".to_string(),
+        };
+
+        // Arguments are type checked
+        assert!(contract::handle(
+            &mut deps,
+            mock_env("admin", &coins(0, "")),
+            trigger("ten", "memo")
+        )
+        .is_err());
+
+        // Arguments are escaped instead of rewriting the message
+        let res = contract::handle(
+            &mut deps,
+            mock_env("admin", &coins(0, "")),
+            trigger("10", "\",\"amount\":\"99{{amount}}"),
+        )
+        .unwrap();
+        match &res.messages[0] {
+            CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => {
+                assert_eq!(
+                    "{\"transfer\":{\"amount\":\"10\",\"memo\":\"\\\",\\\"amount\\\":\\\"99{{amount}}\"}}",
+                    String::from_utf8(msg.0.clone()).unwrap()
+                );
+            }
+            _ => panic!("Expected a wasm execute"),
+        }
+    }
+
+    #[test]
+    fn proposal_schema_validation() {
+        let mut deps = init_governance(init_msg());
+
+        let schema = MessageSchema::from_root(&schema_for!(governance::HandleMsg));
+        assert!(schema.variant("disable_staker").is_some());
+
+        contract::handle(
+            &mut deps,
+            mock_env("cosmos2contract", &coins(0, "")),
+            governance::HandleMsg::SetContractSchema {
+                target: String::from(governance::GOVERNANCE_SELF),
+                schema: Some(schema),
+            },
+        )
+        .unwrap();
+
+        let propose = |proposal: &str| governance::HandleMsg::CreateProposal {
+            target_contract: String::from(governance::GOVERNANCE_SELF),
+            proposal: proposal.to_string(),
+            description: String::from("Proposal"),
+            metadata: None,
+        };
+
+        for proposal in [
+            "{\"disable_staker\":",
+            "{\"disable_stakers\":{}}",
+            "{\"disable_staker\":{\"admin\":null}}",
+            "{\"set_timelock\":{\"delay\":10}}",
+            "{\"disable_staker\":{},\"set_timelock\":{\"target\":\"SELF\"}}",
+        ]
+        .iter()
+        {
+            assert!(contract::handle(
+                &mut deps,
+                mock_env("admin", &coins(0, "")),
+                propose(proposal)
+            )
+            .is_err());
+        }
+
+        contract::handle(
+            &mut deps,
+            mock_env("admin", &coins(0, "")),
+            propose("{\"set_timelock\":{\"target\":\"SELF\",\"delay\":null}}"),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn search_proposals() {
+        let mut deps = init_governance(init_msg());
+
+        for (proposer, category) in [
+            ("alice", Some("treasury")),
+            ("admin", None),
+            ("alice", None),
+        ]
+        .iter()
+        {
+            contract::handle(
+                &mut deps,
+                mock_env(*proposer, &coins(0, "")),
+                governance::HandleMsg::CreateProposal {
+                    target_contract: String::from(governance::GOVERNANCE_SELF),
+                    proposal: "{}".to_string(),
+                    description: String::from("Proposal"),
+                    metadata: Some(ProposalMetadata {
+                        title: String::from("Title"),
+                        link: Some(String::from("https://forum")),
+                        document_hash: None,
+                        category: category.map(String::from),
+                    }),
+                },
+            )
+            .unwrap();
+        }
+        contract::handle(
+            &mut deps,
+            mock_env("admin", &coins(0, "")),
+            governance::HandleMsg::TriggerProposal {
+                proposal_id: Uint128(2),
+            },
+        )
+        .unwrap();
+
+        let search =
+            |filter: ProposalFilter, cursor: Option<u64>, limit: Option<u8>| match from_binary(
+                &contract::query(
+                    &deps,
+                    governance::QueryMsg::SearchProposals {
+                        filter,
+                        cursor,
+                        limit,
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap()
+            {
+                governance::QueryAnswer::SearchProposals { proposals, cursor } => (
+                    proposals.iter().map(|p| p.id.u128()).collect::<Vec<_>>(),
+                    cursor,
+                ),
+                _ => panic!("Received wrong answer"),
+            };
+
+        let by_alice = ProposalFilter {
+            proposer: Some(HumanAddr::from("alice")),
+            ..Default::default()
+        };
+        assert_eq!((vec![1, 3], None), search(by_alice.clone(), None, None));
+        assert_eq!(
+            (vec![1], None),
+            search(
+                ProposalFilter {
+                    category: Some(String::from("treasury")),
+                    ..by_alice
+                },
+                None,
+                None
+            )
+        );
+
+        // Proposals that left a status are skipped
+        let funding = ProposalFilter {
+            status: Some(ProposalStatus::Funding),
+            ..Default::default()
+        };
+        assert_eq!((vec![1], Some(1)), search(funding.clone(), None, Some(1)));
+        assert_eq!((vec![3], None), search(funding, Some(1), Some(1)));
+        assert_eq!(
+            (vec![1, 2, 3], None),
+            search(ProposalFilter::default(), None, None)
+        );
+    }
+
+    #[test]
+    fn upgrade_proposal() {
+        let mut deps = init_governance(init_msg());
+
+        contract::handle(
+            &mut deps,
+            mock_env("cosmos2contract", &coins(0, "")),
+            governance::HandleMsg::AddSupportedContract {
+                name: "token".to_string(),
+                contract: Contract {
+                    address: HumanAddr::from("token"),
+                    code_hash: String::from("old"),
+                },
+            },
+        )
+        .unwrap();
+
+        let upgrade = |target: &str, msg: &str| governance::HandleMsg::CreateMultiProposal {
+            actions: vec![ProposalAction {
+                target: target.to_string(),
+                msg: Binary::from(msg.as_bytes()),
+                funds: vec![],
+                instantiate: Some(InstantiateAction {
+                    code_id: 2,
+                    code_hash: String::from("new"),
+                    label: String::from("token-v2"),
+                }),
+            }],
+            description: String::from("Upgrade token"),
+            metadata: None,
+        };
+
+        let hook = |governance: &str, label: &str| {
+            format!(
+                r#"{{"upgrade":{{"governance":{{"address":"{}","code_hash":""}},"label":"{}"}}}}"#,
+                governance, label
+            )
+        };
+
+        // Governance itself cannot be replaced
+        assert!(contract::handle(
+            &mut deps,
+            mock_env("admin", &coins(0, "")),
+            upgrade(
+                governance::GOVERNANCE_SELF,
+                &hook("cosmos2contract", "token-v2")
+            )
+        )
+        .is_err());
+
+        // The new contract must be set to register with this governance under its label
+        for msg in vec![
+            "{}".to_string(),
+            hook("other_governance", "token-v2"),
+            hook("cosmos2contract", "token-v3"),
+        ] {
+            assert!(contract::handle(
+                &mut deps,
+                mock_env("admin", &coins(0, "")),
+                upgrade("token", &msg)
+            )
+            .is_err());
+        }
+
+        contract::handle(
+            &mut deps,
+            mock_env("admin", &coins(0, "")),
+            upgrade("token", &hook("cosmos2contract", "token-v2")),
+        )
+        .unwrap();
+        let res = contract::handle(
+            &mut deps,
+            mock_env("admin", &coins(0, "")),
+            governance::HandleMsg::TriggerProposal {
+                proposal_id: Uint128(1),
+            },
+        )
+        .unwrap();
+        match &res.messages[0] {
+            CosmosMsg::Wasm(WasmMsg::Instantiate { code_id, label, .. }) => {
+                assert_eq!(2, *code_id);
+                assert_eq!("token-v2", label);
+            }
+            _ => panic!("Expected a wasm instantiate"),
+        }
+
+        // The run only completes once the new contract registers from its init
+        let confirm = governance::HandleMsg::ConfirmExecution {
+            proposal_id: Uint128(1),
+            error: None,
+        };
+        assert!(contract::handle(
+            &mut deps,
+            mock_env("cosmos2contract", &coins(0, "")),
+            confirm.clone()
+        )
+        .is_err());
+
+        let register = |code_hash: &str| governance::HandleMsg::RegisterUpgrade {
+            label: String::from("token-v2"),
+            code_hash: code_hash.to_string(),
+        };
+        assert!(contract::handle(
+            &mut deps,
+            mock_env("token_v2", &coins(0, "")),
+            register("old")
+        )
+        .is_err());
+        contract::handle(
+            &mut deps,
+            mock_env("token_v2", &coins(0, "")),
+            register("new"),
+        )
+        .unwrap();
+        assert!(contract::handle(
+            &mut deps,
+            mock_env("anyone", &coins(0, "")),
+            register("new")
+        )
+        .is_err());
+        contract::handle(
+            &mut deps,
+            mock_env("cosmos2contract", &coins(0, "")),
+            confirm,
+        )
+        .unwrap();
+
+        let res = contract::query(
+            &deps,
+            governance::QueryMsg::GetSupportedContract {
+                name: "token".to_string(),
+            },
+        )
+        .unwrap();
+        match from_binary(&res).unwrap() {
+            governance::QueryAnswer::SupportedContract { contract } => {
+                assert_eq!(HumanAddr::from("token_v2"), contract.address);
+                assert_eq!("new", contract.code_hash);
+            }
+            _ => panic!("Received wrong answer"),
+        }
+    }
+
+    #[test]
+    fn proposal_timelock() {
+        let mut deps = init_governance(governance::InitMsg {
+            guardian: Some(HumanAddr::from("guardian")),
+            timelock: Some(100),
+            execution_window: Some(50),
+            ..init_msg()
+        });
+
+        let at = |sender: &str, offset: u64| {
+            let mut env = mock_env(sender, &coins(0, ""));
+            env.block.time += offset;
+            env
+        };
+        let trigger = |proposal_id: u128| governance::HandleMsg::TriggerProposal {
+            proposal_id: Uint128(proposal_id),
+        };
+        let status = |deps: &Extern<_, _, _>, proposal_id: u128| {
+            let res = contract::query(
+                deps,
+                governance::QueryMsg::GetProposal {
+                    proposal_id: Uint128(proposal_id),
+                },
+            )
+            .unwrap();
+            match from_binary(&res).unwrap() {
+                governance::QueryAnswer::Proposal { proposal } => proposal.status,
+                _ => panic!("Received wrong answer"),
+            }
+        };
+
+        for _ in 0..3 {
+            contract::handle(
+                &mut deps,
+                at("admin", 0),
+                governance::HandleMsg::CreateProposal {
+                    target_contract: String::from(governance::GOVERNANCE_SELF),
+                    proposal: "{}".to_string(),
+                    description: String::from("Proposal"),
+                    metadata: None,
+                },
+            )
+            .unwrap();
+        }
+
+        // Passing queues the proposal
+        for id in 1..=3 {
+            let res = contract::handle(&mut deps, at("admin", 0), trigger(id)).unwrap();
+            assert!(res.messages.is_empty());
+            assert_eq!(ProposalStatus::Queued, status(&deps, id));
+        }
+
+        let run = |deps: &Extern<_, _, _>, proposal_id: u128| {
+            let res = contract::query(
+                deps,
+                governance::QueryMsg::GetProposal {
+                    proposal_id: Uint128(proposal_id),
+                },
+            )
+            .unwrap();
+            match from_binary(&res).unwrap() {
+                governance::QueryAnswer::Proposal { proposal } => {
+                    (proposal.run_status, proposal.run_error)
+                }
+                _ => panic!("Received wrong answer"),
+            }
+        };
+
+        // Reverted runs can only be reported by the admin once the timelock ends
+        let report = |proposal_id: u128| governance::HandleMsg::ConfirmExecution {
+            proposal_id: Uint128(proposal_id),
+            error: Some("target is paused".to_string()),
+        };
+        assert!(contract::handle(&mut deps, at("admin", 50), report(1)).is_err());
+        assert!(contract::handle(&mut deps, at("anyone", 100), report(1)).is_err());
+        contract::handle(&mut deps, at("admin", 100), report(1)).unwrap();
+        assert_eq!(
+            (
+                Some(ResponseStatus::Failure),
+                Some("target is paused".to_string())
+            ),
+            run(&deps, 1)
+        );
+        assert_eq!(ProposalStatus::Queued, status(&deps, 1));
+
+        // Timelock must end before execution
+        assert!(contract::handle(&mut deps, at("anyone", 50), trigger(1)).is_err());
+        let res = contract::handle(&mut deps, at("anyone", 100), trigger(1)).unwrap();
+        assert_eq!(2, res.messages.len());
+        assert_eq!(ProposalStatus::Passed, status(&deps, 1));
+
+        // A confirmed run replaces the report
+        contract::handle(
+            &mut deps,
+            at("cosmos2contract", 100),
+            governance::HandleMsg::ConfirmExecution {
+                proposal_id: Uint128(1),
+                error: None,
+            },
+        )
+        .unwrap();
+        assert_eq!((Some(ResponseStatus::Success), None), run(&deps, 1));
+        assert_eq!(ProposalStatus::Executed, status(&deps, 1));
+        assert!(contract::handle(&mut deps, at("admin", 100), report(1)).is_err());
+
+        // Only the guardian or admin can cancel
+        let cancel = governance::HandleMsg::CancelProposal {
+            proposal_id: Uint128(2),
+        };
+        assert!(contract::handle(&mut deps, at("anyone", 0), cancel.clone()).is_err());
+        contract::handle(&mut deps, at("guardian", 0), cancel).unwrap();
+        assert_eq!(ProposalStatus::Cancelled, status(&deps, 2));
+        assert!(contract::handle(&mut deps, at("anyone", 100), trigger(2)).is_err());
+
+        // Execution window has passed
+        let res = contract::handle(&mut deps, at("anyone", 151), trigger(3)).unwrap();
+        assert!(res.messages.is_empty());
+        assert_eq!(ProposalStatus::Expired, status(&deps, 3));
+    }
+
+    #[test]
+    fn funding_refunds() {
+        let mut deps = init_governance(governance::InitMsg {
+            funding_policy: Some(governance::FundingPolicy {
+                treasury: Some(Contract {
+                    address: HumanAddr::from("treasury"),
+                    code_hash: String::from(""),
+                }),
+                ..Default::default()
+            }),
+            ..init_msg()
+        });
+
+        let at = |sender: &str, offset: u64| {
+            let mut env = mock_env(sender, &coins(0, ""));
+            env.block.time += offset;
+            env
+        };
+        let fund = |funder: &str, amount: u128, proposal_id: u128| governance::HandleMsg::Receive {
+            sender: HumanAddr::from(funder),
+            amount: Uint128(amount),
+            msg: Some(to_binary(&Uint128(proposal_id)).unwrap()),
+        };
+        let claim = governance::HandleMsg::ClaimFunding {
+            proposal_id: Uint128(1),
+        };
+
+        for _ in 0..2 {
+            contract::handle(
+                &mut deps,
+                at("admin", 0),
+                governance::HandleMsg::CreateProposal {
+                    target_contract: String::from(governance::GOVERNANCE_SELF),
+                    proposal: "{}".to_string(),
+                    description: String::from("Proposal"),
+                    metadata: None,
+                },
+            )
+            .unwrap();
+        }
+
+        // Deposits can only be claimed once funding failed
+        contract::handle(&mut deps, at("funding_token", 0), fund("alice", 400, 1)).unwrap();
+        assert!(contract::handle(&mut deps, at("alice", 0), claim.clone()).is_err());
+
+        let res = contract::handle(&mut deps, at("alice", 180), claim.clone()).unwrap();
+        assert_eq!(1, res.messages.len());
+        match from_binary(&res.data.unwrap()).unwrap() {
+            governance::HandleAnswer::ClaimFunding { amount, .. } => {
+                assert_eq!(Uint128(400), amount);
+            }
+            _ => panic!("Received wrong answer"),
+        }
+        assert!(contract::handle(&mut deps, at("alice", 180), claim).is_err());
+
+        // Passed proposals refund their funders
+        contract::handle(&mut deps, at("funding_token", 0), fund("bob", 300, 2)).unwrap();
+        let res = contract::handle(
+            &mut deps,
+            at("admin", 0),
+            governance::HandleMsg::TriggerProposal {
+                proposal_id: Uint128(2),
+            },
+        )
+        .unwrap();
+        assert_eq!(3, res.messages.len());
+    }
+
+    #[test]
+    fn slash_needs_treasury_asset() {
+        let mut deps = init_governance(governance::InitMsg {
+            funding_policy: Some(governance::FundingPolicy {
+                treasury: Some(Contract {
+                    address: HumanAddr::from("treasury"),
+                    code_hash: String::from(""),
+                }),
+                slash_on: vec![ProposalStatus::Passed],
+            }),
+            ..init_msg()
+        });
+
+        contract::handle(
+            &mut deps,
+            mock_env("admin", &coins(0, "")),
+            governance::HandleMsg::CreateProposal {
+                target_contract: String::from(governance::GOVERNANCE_SELF),
+                proposal: "{}".to_string(),
+                description: String::from("Proposal"),
+                metadata: None,
+            },
+        )
+        .unwrap();
+        contract::handle(
+            &mut deps,
+            mock_env("funding_token", &coins(0, "")),
+            governance::HandleMsg::Receive {
+                sender: HumanAddr::from("bob"),
+                amount: Uint128(1000),
+                msg: Some(to_binary(&Uint128(1)).unwrap()),
+            },
+        )
+        .unwrap();
+
+        // Treasury doesn't answer for the funding token so the deposit is refunded
+        let res = contract::handle(
+            &mut deps,
+            mock_env("admin", &coins(0, "")),
+            governance::HandleMsg::TriggerProposal {
+                proposal_id: Uint128(1),
+            },
+        )
+        .unwrap();
+        assert!(res.messages.iter().any(|msg| match msg {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr, msg, ..
+            }) => {
+                *contract_addr == HumanAddr::from("funding_token")
+                    && String::from_utf8_lossy(msg.as_slice()).contains("batch_send")
+            }
+            _ => false,
+        }));
+    }
+
+    #[test]
+    fn vote_tally_status() {
+        let percent = |p: u128| Uint128(governance::ONE_HUNDRED_PERCENT / 100 * p);
+        let settings = governance::VoteSettings {
+            quorum: percent(40),
+            threshold: percent(66),
+            veto: Some(percent(33)),
+        };
+        let tally = |yes: u128, no: u128, abstain: u128, veto: u128| VoteTally {
+            yes: Uint128(yes),
+            no: Uint128(no),
+            abstain: Uint128(abstain),
+            veto: Uint128(veto),
+        };
+        let total_staked = Uint128(1000);
+
+        // Below quorum
+        assert_eq!(
+            ProposalStatus::Expired,
+            tally_status(&tally(300, 0, 0, 0), total_staked, &settings)
+        );
+        // Abstain counts towards quorum but not the threshold
+        assert_eq!(
+            ProposalStatus::Passed,
+            tally_status(&tally(70, 30, 300, 0), total_staked, &settings)
+        );
+        assert_eq!(
+            ProposalStatus::Rejected,
+            tally_status(&tally(300, 200, 0, 0), total_staked, &settings)
+        );
+        assert_eq!(
+            ProposalStatus::Vetoed,
+            tally_status(&tally(600, 0, 0, 400), total_staked, &settings)
+        );
+
+        // Without vetoes the veto votes only count against the proposal
+        let settings = governance::VoteSettings {
+            veto: None,
+            ..settings
+        };
+        assert_eq!(
+            ProposalStatus::Rejected,
+            tally_status(&tally(600, 0, 0, 400), total_staked, &settings)
+        );
+    }
+
     ///
     /// Assert via assertFn on the result of governance::QueryMsg::GetProposals contract call.
     ///
@@ -57,10 +57,15 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
 
     asset_list_w(&mut deps.storage).save(&vec![])?;
 
+    let mut messages = vec![];
+    if let Some(hook) = msg.upgrade {
+        messages.push(hook.register_msg(&env)?);
+    }
+
     debug_print!("Contract was initialized by {}", env.message.sender);
 
     Ok(InitResponse {
-        messages: vec![],
+        messages,
         log: vec![],
     })
 }
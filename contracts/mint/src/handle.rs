@@ -14,6 +14,7 @@ use shade_protocol::{
     mint::{Config, HandleAnswer, Limit, MintMsgHook, SupportedAsset},
     oracle::QueryMsg::Price,
     snip20::{token_config_query, Snip20Asset, TokenConfig},
+    treasury::TreasuryReceiveMsg,
 };
 use std::{cmp::Ordering, convert::TryFrom};
 
@@ -76,7 +77,9 @@ pub fn try_burn<S: Storage, A: Api, Q: Querier>(
         messages.push(send_msg(
             config.treasury.clone(),
             fee_amount,
-            None,
+            Some(to_binary(&TreasuryReceiveMsg::FeeIncome {
+                source: "mint".to_string(),
+            })?),
             None,
             None,
             1,
@@ -113,7 +116,9 @@ pub fn try_burn<S: Storage, A: Api, Q: Querier>(
         messages.push(send_msg(
             config.treasury,
             capture_amount,
-            None,
+            Some(to_binary(&TreasuryReceiveMsg::FeeIncome {
+                source: "mint".to_string(),
+            })?),
             None,
             None,
             1,
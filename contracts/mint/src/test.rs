@@ -62,6 +62,7 @@ pub mod tests {
             treasury,
             secondary_burn: None,
             limit: None,
+            upgrade: None,
         };
         let env = mock_env(admin, &coins(1000, "earth"));
         let _res = init(&mut deps, env, msg).unwrap();
@@ -81,6 +82,7 @@ pub mod tests {
             treasury: Option::from(create_contract("", "")),
             // 1%
             capture: Option::from(Uint128(100)),
+            upgrade: None,
         };
         let env = mock_env("creator", &coins(1000, "earth"));
 
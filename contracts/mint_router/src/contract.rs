@@ -31,6 +31,9 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
     //current_assets_w(&mut deps.storage).save(&vec![])?;
 
     let mut messages = vec![];
+    if let Some(hook) = msg.upgrade {
+        messages.push(hook.register_msg(&env)?);
+    }
 
     if config.path.len() > 0 {
         //messages.append(&mut handle::update_entry_assets(deps, env, config.path[0].clone())?);
@@ -68,6 +68,7 @@ pub mod tests {
             epoch_frequency: None,
             epoch_mint_limit: None,
             */
+            upgrade: None,
         };
         let env = mock_env(admin, &coins(1000, "earth"));
         let _res = init(&mut deps, env, msg).unwrap();
@@ -87,6 +88,7 @@ pub mod tests {
             treasury: Option::from(create_contract("", "")),
             // 1%
             capture: Option::from(Uint128(100)),
+            upgrade: None,
         };
         let env = mock_env("creator", &coins(1000, "earth"));
 
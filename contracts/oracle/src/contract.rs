@@ -21,9 +21,17 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
 
     config_w(&mut deps.storage).save(&state)?;
 
+    let mut messages = vec![];
+    if let Some(hook) = msg.upgrade {
+        messages.push(hook.register_msg(&env)?);
+    }
+
     debug_print!("Contract was initialized by {}", env.message.sender);
 
-    Ok(InitResponse::default())
+    Ok(InitResponse {
+        messages,
+        log: vec![],
+    })
 }
 
 pub fn handle<S: Storage, A: Api, Q: Querier>(
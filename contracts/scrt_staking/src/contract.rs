@@ -1,6 +1,6 @@
 use cosmwasm_std::{
     debug_print, to_binary, Api, Binary, Env, Extern, HandleResponse, InitResponse, Querier,
-    StdResult, Storage,
+    StdResult, Storage, Uint128,
 };
 
 use shade_protocol::scrt_staking::{Config, HandleMsg, InitMsg, QueryMsg};
@@ -9,7 +9,9 @@ use secret_toolkit::snip20::{register_receive_msg, set_viewing_key_msg};
 
 use crate::{
     handle, query,
-    state::{config_w, self_address_w, viewing_key_r, viewing_key_w},
+    state::{
+        config_w, self_address_w, unbonding_w, viewing_key_r, viewing_key_w, withdrawn_rewards_w,
+    },
 };
 
 pub fn init<S: Storage, A: Api, Q: Querier>(
@@ -31,26 +33,33 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
 
     self_address_w(&mut deps.storage).save(&env.contract.address)?;
     viewing_key_w(&mut deps.storage).save(&msg.viewing_key)?;
+    unbonding_w(&mut deps.storage).save(&Uint128::zero())?;
+    withdrawn_rewards_w(&mut deps.storage).save(&Uint128::zero())?;
 
     debug_print!("Contract was initialized by {}", env.message.sender);
 
+    let mut messages = vec![
+        set_viewing_key_msg(
+            viewing_key_r(&deps.storage).load()?,
+            None,
+            1,
+            config.sscrt.code_hash.clone(),
+            config.sscrt.address.clone(),
+        )?,
+        register_receive_msg(
+            env.contract_code_hash.clone(),
+            None,
+            256,
+            config.sscrt.code_hash,
+            config.sscrt.address,
+        )?,
+    ];
+    if let Some(hook) = msg.upgrade {
+        messages.push(hook.register_msg(&env)?);
+    }
+
     Ok(InitResponse {
-        messages: vec![
-            set_viewing_key_msg(
-                viewing_key_r(&deps.storage).load()?,
-                None,
-                1,
-                config.sscrt.code_hash.clone(),
-                config.sscrt.address.clone(),
-            )?,
-            register_receive_msg(
-                env.contract_code_hash,
-                None,
-                256,
-                config.sscrt.code_hash,
-                config.sscrt.address,
-            )?,
-        ],
+        messages,
         log: vec![],
     })
 }
@@ -71,6 +80,7 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
         HandleMsg::UpdateConfig { admin } => handle::try_update_config(deps, env, admin),
         // Begin unbonding of a certain amount of scrt
         HandleMsg::Unbond { validator } => handle::unbond(deps, env, validator),
+        HandleMsg::UnbondAll {} => handle::unbond_all(deps, env),
         // Collect a completed unbonding/rewards
         HandleMsg::Claim { validator } => handle::claim(deps, env, validator),
     }
@@ -84,6 +94,8 @@ pub fn query<S: Storage, A: Api, Q: Querier>(
         QueryMsg::GetConfig {} => to_binary(&query::config(deps)?),
         // All delegations
         QueryMsg::Delegations {} => to_binary(&query::delegations(deps)?),
+        QueryMsg::Balance {} => to_binary(&query::balance(deps)?),
+        QueryMsg::Unbonding {} => to_binary(&query::unbonding(deps)?),
         //QueryMsg::Delegation { validator } => to_binary(&query::delegation(deps, validator)?),
         QueryMsg::Rewards {} => to_binary(&query::rewards(deps)?),
     }
@@ -4,17 +4,18 @@ use cosmwasm_std::{
     Validator,
 };
 
-use secret_toolkit::snip20::{deposit_msg, redeem_msg, send_msg};
+use secret_toolkit::{snip20::redeem_msg, utils::HandleCallback};
 
+use shade_protocol::scrt_staking::{HandleAnswer, ValidatorBounds};
+use shade_protocol::treasury::{self, TreasuryReceiveMsg};
 use shade_protocol::utils::generic_response::ResponseStatus;
-use shade_protocol::{
-    scrt_staking::{HandleAnswer, ValidatorBounds},
-    treasury::Flag,
-};
 
 use crate::{
     query,
-    state::{config_r, config_w, self_address_r},
+    state::{
+        config_r, config_w, self_address_r, unbonding_r, unbonding_w, withdrawn_rewards_r,
+        withdrawn_rewards_w,
+    },
 };
 
 pub fn receive<S: Storage, A: Api, Q: Querier>(
@@ -37,6 +38,7 @@ pub fn receive<S: Storage, A: Api, Q: Querier>(
     }
 
     let validator = choose_validator(&deps, env.block.time)?;
+    add_withdrawn_rewards(deps, Some(&validator.address))?;
 
     Ok(HandleResponse {
         messages: vec![
@@ -105,7 +107,7 @@ pub fn unbond<S: Storage, A: Api, Q: Querier>(
 
     let config = config_r(&deps.storage).load()?;
 
-    if env.message.sender != config.admin && env.message.sender != config.treasury {
+    if env.message.sender != config.admin && env.message.sender != config.treasury.address {
         return Err(StdError::Unauthorized { backtrace: None });
     }
 
@@ -114,6 +116,10 @@ pub fn unbond<S: Storage, A: Api, Q: Querier>(
         .query_all_delegations(self_address_r(&deps.storage).load()?)?
     {
         if delegation.validator == validator {
+            add_withdrawn_rewards(deps, Some(&validator))?;
+            unbonding_w(&mut deps.storage)
+                .update(|unbonding| Ok(unbonding + delegation.amount.amount))?;
+
             return Ok(HandleResponse {
                 messages: vec![CosmosMsg::Staking(StakingMsg::Undelegate {
                     validator,
@@ -153,9 +159,61 @@ pub fn unbond<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+pub fn unbond_all<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> StdResult<HandleResponse> {
+    let config = config_r(&deps.storage).load()?;
+
+    if env.message.sender != config.admin && env.message.sender != config.treasury.address {
+        return Err(StdError::Unauthorized { backtrace: None });
+    }
+
+    let mut messages = vec![];
+    let mut amount = Uint128::zero();
+    add_withdrawn_rewards(deps, None)?;
+
+    for delegation in deps
+        .querier
+        .query_all_delegations(self_address_r(&deps.storage).load()?)?
+    {
+        amount += delegation.amount.amount;
+        messages.push(CosmosMsg::Staking(StakingMsg::Undelegate {
+            validator: delegation.validator,
+            amount: delegation.amount,
+        }));
+    }
+
+    unbonding_w(&mut deps.storage).update(|unbonding| Ok(unbonding + amount))?;
+
+    Ok(HandleResponse {
+        messages,
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::UnbondAll {
+            status: ResponseStatus::Success,
+            amount,
+        })?),
+    })
+}
+
+// Delegating and undelegating withdraw the validator's pending rewards into the balance
+fn add_withdrawn_rewards<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    validator: Option<&HumanAddr>,
+) -> StdResult<()> {
+    let rewards = query::validator_rewards(deps, validator)?;
+    if !rewards.is_zero() {
+        let withdrawn = withdrawn_rewards_r(&deps.storage)
+            .may_load()?
+            .unwrap_or_default();
+        withdrawn_rewards_w(&mut deps.storage).save(&(withdrawn + rewards))?;
+    }
+    Ok(())
+}
+
 /*
- * Claims rewards and collects completed unbondings
- * from a given validator and returns them directly to treasury
+ * Claims rewards from a given validator along with the rewards
+ * already withdrawn and completed unbondings, then returns them to treasury
  *
  * TODO: convert to sSCRT first or rely on treasury to do so
  */
@@ -166,12 +224,10 @@ pub fn claim<S: Storage, A: Api, Q: Querier>(
 ) -> StdResult<HandleResponse> {
     let config = config_r(&deps.storage).load()?;
 
-    //TODO: query scrt balance and deposit into sscrt
-
     let mut messages = vec![];
     let address = self_address_r(&deps.storage).load()?;
 
-    // Get total scrt balance, to get recently claimed rewards + lingering unbonded scrt
+    // Lingering scrt is from withdrawn rewards and completed unbondings
     let scrt_balance: BalanceResponse = deps.querier.query(
         &BankQuery::Balance {
             address: address.clone(),
@@ -179,41 +235,52 @@ pub fn claim<S: Storage, A: Api, Q: Querier>(
         }
         .into(),
     )?;
+    let balance = scrt_balance.amount.amount;
 
-    let amount = query::rewards(&deps)? + scrt_balance.amount.amount;
+    // Withdrawn rewards are tracked, the rest of the balance is repaid up to what is unbonding
+    let withdrawn = withdrawn_rewards_r(&deps.storage)
+        .may_load()?
+        .unwrap_or_default()
+        .min(balance);
+    let unbonding = unbonding_r(&deps.storage).load()?;
+    let unbonded = (balance - withdrawn)?.min(unbonding);
+    let rewards = query::validator_rewards(deps, Some(&validator))? + (balance - unbonded)?;
+
+    unbonding_w(&mut deps.storage).save(&(unbonding - unbonded)?)?;
+    withdrawn_rewards_w(&mut deps.storage).save(&Uint128::zero())?;
 
     messages.push(CosmosMsg::Staking(StakingMsg::Withdraw {
         validator,
         recipient: Some(address.clone()),
     }));
 
-    messages.push(deposit_msg(
-        amount,
-        None,
-        256,
-        config.sscrt.code_hash.clone(),
-        config.sscrt.address.clone(),
-    )?);
-
-    /* NOTE: This will likely trigger the receive callback which
-     *       would result in re-delegating a portion of the funds.
-     *       This case will need to be tested and mitigated by either
-     *       - accounting for it when rebalancing
-     *       - add a "unallocated" flag with funds to force treasury not to
-     *         allocate them, to then be allocated at rebalancing
-     */
-    messages.push(send_msg(
-        config.treasury,
-        amount,
-        Some(to_binary(&Flag {
-            flag: "unallocated".to_string(),
-        })?),
-        None,
-        None,
-        1,
-        config.sscrt.code_hash.clone(),
-        config.sscrt.address.clone(),
-    )?);
+    // Sent through treasury so rewards are accounted as income and unbondings as repayments
+    if !rewards.is_zero() {
+        messages.push(
+            treasury::HandleMsg::ReceiveNative {
+                msg: Some(TreasuryReceiveMsg::FeeIncome {
+                    source: address.to_string(),
+                }),
+            }
+            .to_cosmos_msg(
+                config.treasury.code_hash.clone(),
+                config.treasury.address.clone(),
+                Some(rewards),
+            )?,
+        );
+    }
+    if !unbonded.is_zero() {
+        messages.push(
+            treasury::HandleMsg::ReceiveNative {
+                msg: Some(TreasuryReceiveMsg::Repayment { address }),
+            }
+            .to_cosmos_msg(
+                config.treasury.code_hash,
+                config.treasury.address,
+                Some(unbonded),
+            )?,
+        );
+    }
 
     Ok(HandleResponse {
         messages,
@@ -5,7 +5,7 @@ use cosmwasm_std::{
 
 use shade_protocol::scrt_staking::QueryAnswer;
 
-use crate::state::{config_r, self_address_r};
+use crate::state::{config_r, self_address_r, unbonding_r};
 
 pub fn config<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>) -> StdResult<QueryAnswer> {
     Ok(QueryAnswer::Config {
@@ -20,6 +20,22 @@ pub fn delegations<S: Storage, A: Api, Q: Querier>(
         .query_all_delegations(self_address_r(&deps.storage).load()?)
 }
 
+pub fn balance<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>) -> StdResult<QueryAnswer> {
+    let mut amount = Uint128::zero();
+
+    for delegation in delegations(deps)? {
+        amount += delegation.amount.amount;
+    }
+
+    Ok(QueryAnswer::Balance { amount })
+}
+
+pub fn unbonding<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>) -> StdResult<QueryAnswer> {
+    Ok(QueryAnswer::Unbonding {
+        amount: unbonding_r(&deps.storage).load()?,
+    })
+}
+
 // TODO: change to 'claimable'
 pub fn rewards<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>) -> StdResult<Uint128> {
     let scrt_balance: BalanceResponse = deps.querier.query(
@@ -61,6 +77,39 @@ pub fn rewards<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>) -> StdRes
     })
 }
 
+// Pending rewards of one validator, or of every delegation when None
+pub fn validator_rewards<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    validator: Option<&HumanAddr>,
+) -> StdResult<Uint128> {
+    let query_rewards: RewardsResponse = deps
+        .querier
+        .query(
+            &DistQuery::Rewards {
+                delegator: self_address_r(&deps.storage).load()?,
+            }
+            .into(),
+        )
+        .unwrap_or_else(|_| RewardsResponse {
+            rewards: vec![],
+            total: vec![],
+        });
+
+    let mut amount = Uint128::zero();
+    for rewards in query_rewards.rewards {
+        if validator.map_or(false, |v| *v != rewards.validator_address) {
+            continue;
+        }
+        for coin in rewards.reward {
+            if coin.denom == "uscrt" {
+                amount += coin.amount;
+            }
+        }
+    }
+
+    Ok(amount)
+}
+
 // This won't work until cosmwasm 0.16ish
 /*
 pub fn delegation<S: Storage, A: Api, Q: Querier>(
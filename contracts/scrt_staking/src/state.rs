@@ -1,10 +1,12 @@
-use cosmwasm_std::{HumanAddr, Storage};
+use cosmwasm_std::{HumanAddr, Storage, Uint128};
 use cosmwasm_storage::{singleton, singleton_read, ReadonlySingleton, Singleton};
 use shade_protocol::scrt_staking;
 
 pub static CONFIG_KEY: &[u8] = b"config";
 pub static SELF_ADDRESS: &[u8] = b"self_address";
 pub static VIEWING_KEY: &[u8] = b"viewing_key";
+pub static UNBONDING: &[u8] = b"unbonding";
+pub static WITHDRAWN_REWARDS: &[u8] = b"withdrawn_rewards";
 
 //pub static DELEGATIONS: &[u8] = b"delegations";
 
@@ -32,6 +34,23 @@ pub fn viewing_key_r<S: Storage>(storage: &S) -> ReadonlySingleton<S, String> {
     singleton_read(storage, VIEWING_KEY)
 }
 
+pub fn unbonding_w<S: Storage>(storage: &mut S) -> Singleton<S, Uint128> {
+    singleton(storage, UNBONDING)
+}
+
+pub fn unbonding_r<S: Storage>(storage: &S) -> ReadonlySingleton<S, Uint128> {
+    singleton_read(storage, UNBONDING)
+}
+
+// Rewards withdrawn into the balance by delegating or undelegating, not yet sent to treasury
+pub fn withdrawn_rewards_w<S: Storage>(storage: &mut S) -> Singleton<S, Uint128> {
+    singleton(storage, WITHDRAWN_REWARDS)
+}
+
+pub fn withdrawn_rewards_r<S: Storage>(storage: &S) -> ReadonlySingleton<S, Uint128> {
+    singleton_read(storage, WITHDRAWN_REWARDS)
+}
+
 /*
 pub fn delegations_r<S: Storage>(storage: &S) -> ReadonlySingleton<S, Vec<Delegation>> {
     singleton_read(storage, DELEGATIONS)
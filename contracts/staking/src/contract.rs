@@ -1,10 +1,11 @@
 use crate::{
     handle::{
-        try_claim_rewards, try_claim_unbond, try_set_viewing_key, try_stake, try_unbond,
+        try_claim_rewards, try_claim_unbond, try_compound, try_delegate, try_register_reward_token,
+        try_set_auto_compound, try_set_emissions, try_set_viewing_key, try_stake, try_unbond,
         try_update_config, try_vote,
     },
     query,
-    state::{config_w, stake_state_w, unbonding_w},
+    state::{config_w, emissions_w, reward_pool_w, reward_tokens_w, stake_state_w, unbonding_w},
 };
 use binary_heap_plus::BinaryHeap;
 use cosmwasm_std::{
@@ -12,7 +13,10 @@ use cosmwasm_std::{
     Uint128,
 };
 use secret_toolkit::snip20::register_receive_msg;
-use shade_protocol::staking::{stake::Stake, Config, HandleMsg, InitMsg, QueryMsg};
+use shade_protocol::staking::{
+    stake::{RewardPool, Stake},
+    Config, HandleMsg, InitMsg, QueryMsg,
+};
 use shade_protocol::utils::asset::Contract;
 
 pub fn init<S: Storage, A: Api, Q: Querier>(
@@ -30,31 +34,50 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
         },
         unbond_time: msg.unbond_time,
         staked_token: msg.staked_token,
+        treasury: msg.treasury,
     };
 
     config_w(&mut deps.storage).save(&state)?;
 
     // Register staked_token
     let cosmos_msg = register_receive_msg(
-        env.contract_code_hash,
+        env.contract_code_hash.clone(),
         None,
         256,
         state.staked_token.code_hash.clone(),
-        state.staked_token.address,
+        state.staked_token.address.clone(),
     )?;
 
     // Initialize binary heap
     let unbonding_heap = BinaryHeap::new_min();
     unbonding_w(&mut deps.storage).save(&unbonding_heap)?;
 
+    // Staked token rewards are not emitted until a schedule is set
+    let staked_token = state.staked_token.address.as_str().as_bytes();
+    reward_tokens_w(&mut deps.storage).save(&vec![state.staked_token.clone()])?;
+    emissions_w(&mut deps.storage).save(staked_token, &vec![])?;
+    reward_pool_w(&mut deps.storage).save(
+        staked_token,
+        &RewardPool {
+            reward_per_token: Uint128::zero(),
+            last_update: env.block.time,
+            available: Uint128::zero(),
+        },
+    )?;
+
     // Initialize stake state
     stake_state_w(&mut deps.storage).save(&Stake {
         total_shares: Uint128::zero(),
         total_tokens: Uint128::zero(),
     })?;
 
+    let mut messages = vec![cosmos_msg];
+    if let Some(hook) = msg.upgrade {
+        messages.push(hook.register_msg(&env)?);
+    }
+
     Ok(InitResponse {
-        messages: vec![cosmos_msg],
+        messages,
         log: vec![],
     })
 }
@@ -65,8 +88,14 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
     msg: HandleMsg,
 ) -> StdResult<HandleResponse> {
     match msg {
-        HandleMsg::UpdateConfig { admin, unbond_time } => {
-            try_update_config(deps, &env, admin, unbond_time)
+        HandleMsg::UpdateConfig {
+            admin,
+            unbond_time,
+            treasury,
+        } => try_update_config(deps, &env, admin, unbond_time, treasury),
+        HandleMsg::RegisterRewardToken { token } => try_register_reward_token(deps, &env, token),
+        HandleMsg::SetEmissions { token, schedule } => {
+            try_set_emissions(deps, &env, token, schedule)
         }
         HandleMsg::Receive {
             sender,
@@ -76,8 +105,11 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
         HandleMsg::Unbond { amount } => try_unbond(deps, &env, amount),
         HandleMsg::Vote { proposal_id, votes } => try_vote(deps, &env, proposal_id, votes),
         HandleMsg::ClaimUnbond {} => try_claim_unbond(deps, &env),
-        HandleMsg::ClaimRewards {} => try_claim_rewards(deps, &env),
+        HandleMsg::ClaimRewards { tokens } => try_claim_rewards(deps, &env, tokens),
         HandleMsg::SetViewingKey { key } => try_set_viewing_key(deps, &env, key),
+        HandleMsg::Compound {} => try_compound(deps, &env),
+        HandleMsg::SetAutoCompound { enabled } => try_set_auto_compound(deps, &env, enabled),
+        HandleMsg::Delegate { delegate } => try_delegate(deps, &env, delegate),
     }
 }
 
@@ -88,11 +120,24 @@ pub fn query<S: Storage, A: Api, Q: Querier>(
     match msg {
         QueryMsg::Config {} => to_binary(&query::config(deps)?),
         QueryMsg::TotalStaked {} => to_binary(&query::total_staked(deps)?),
+        QueryMsg::TotalStakedAt { height } => to_binary(&query::total_staked_at(deps, height)?),
         QueryMsg::TotalUnbonding { start, end } => {
             to_binary(&query::total_unbonding(deps, start, end)?)
         }
         QueryMsg::UserStake { address, key, time } => {
             to_binary(&query::user_stake(deps, address, key, time)?)
         }
+        QueryMsg::Delegation { address, key } => to_binary(&query::delegation(deps, address, key)?),
+        QueryMsg::VotingPower {
+            address,
+            key,
+            height,
+        } => to_binary(&query::voting_power(deps, address, key, height)?),
+        QueryMsg::RewardTokens {} => to_binary(&query::reward_tokens(deps)?),
+        QueryMsg::Emissions { token } => to_binary(&query::emissions(deps, token)?),
+        QueryMsg::Apr { time } => to_binary(&query::apr(deps, time)?),
+        QueryMsg::ProjectedRewards { amount, start, end } => {
+            to_binary(&query::projected_rewards(deps, amount, start, end)?)
+        }
     }
 }
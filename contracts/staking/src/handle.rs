@@ -1,20 +1,28 @@
 use crate::state::{
-    config_r, config_w, stake_state_r, stake_state_w, staker_r, staker_w, unbonding_w,
-    user_unbonding_w, viewking_key_w,
+    auto_compound_r, auto_compound_w, config_r, config_w, delegate_at, delegate_r, delegate_w,
+    delegated_owner, emissions_r, emissions_w, latest_stake, proposal_overrides_w,
+    proposal_votes_r, proposal_votes_w, push_checkpoint, push_delegation, reward_pool_r,
+    reward_pool_w, reward_tokens_r, reward_tokens_w, stake_at, stake_state_r, stake_state_w,
+    staker_r, staker_w, unbonding_w, user_reward_r, user_reward_w, user_unbonding_w,
+    viewking_key_w, voting_power_at, TOTAL_CHECKPOINTS,
 };
 use binary_heap_plus::BinaryHeap;
 use cosmwasm_std::{
     to_binary, Api, Env, Extern, HandleResponse, HumanAddr, Querier, StdError, StdResult, Storage,
     Uint128,
 };
-use secret_toolkit::{snip20::send_msg, utils::HandleCallback};
+use secret_toolkit::{
+    snip20::{register_receive_msg, send_msg},
+    utils::{HandleCallback, Query},
+};
 use shade_protocol::utils::asset::Contract;
 use shade_protocol::utils::generic_response::ResponseStatus::Success;
 use shade_protocol::{
+    governance,
     governance::vote::{UserVote, Vote, VoteTally},
     staking::{
-        stake::{Stake, Unbonding, UserStake},
-        HandleAnswer,
+        stake::{EmissionPeriod, RewardPool, Stake, Unbonding, UserReward, UserStake},
+        Config, HandleAnswer, REWARD_PRECISION,
     },
 };
 
@@ -38,11 +46,106 @@ pub(crate) fn calculate_rewards(user: &UserStake, state: &Stake) -> Uint128 {
     (calculate_tokens(user.shares, state) - user.tokens_staked).unwrap()
 }
 
+// Rewards scheduled between two times, regardless of funding
+pub(crate) fn scheduled_emission(schedule: &[EmissionPeriod], from: u64, to: u64) -> Uint128 {
+    let mut total = Uint128::zero();
+    for period in schedule {
+        let start = period.start.max(from);
+        let end = period.end.min(to);
+        if start < end {
+            total += period.rate.multiply_ratio(end - start, 1u64);
+        }
+    }
+    total
+}
+
+// Emits the funded rewards up to now into the accumulator
+fn accrue_rewards(
+    pool: &mut RewardPool,
+    schedule: Option<&[EmissionPeriod]>,
+    total_staked: Uint128,
+    now: u64,
+) -> StdResult<()> {
+    // Nothing is emitted while nobody is staked
+    if !total_staked.is_zero() {
+        let emitted = match schedule {
+            Some(schedule) => scheduled_emission(schedule, pool.last_update, now),
+            // Tokens without a schedule are distributed as they arrive
+            None => pool.available,
+        }
+        .min(pool.available);
+        pool.reward_per_token += emitted.multiply_ratio(REWARD_PRECISION, total_staked);
+        pool.available = (pool.available - emitted)?;
+    }
+    pool.last_update = pool.last_update.max(now);
+
+    Ok(())
+}
+
+// Loads the token's reward pool brought up to now
+pub(crate) fn accrued_pool<S: Storage>(
+    storage: &S,
+    token: &HumanAddr,
+    now: u64,
+) -> StdResult<RewardPool> {
+    let mut pool = reward_pool_r(storage).load(token.as_str().as_bytes())?;
+    accrue_rewards(
+        &mut pool,
+        emissions_r(storage)
+            .may_load(token.as_str().as_bytes())?
+            .as_deref(),
+        latest_stake(storage, TOTAL_CHECKPOINTS)?,
+        now,
+    )?;
+    Ok(pool)
+}
+
+pub(crate) fn earned_rewards(
+    user: &UserReward,
+    staked: Uint128,
+    reward_per_token: Uint128,
+) -> StdResult<Uint128> {
+    Ok(user.pending
+        + staked.multiply_ratio(
+            (reward_per_token - user.reward_per_token_paid)?,
+            REWARD_PRECISION,
+        ))
+}
+
+// Brings the accumulators and the user's earned rewards up to date, must run before the stake changes
+fn update_rewards<S: Storage>(
+    storage: &mut S,
+    user: &HumanAddr,
+    now: u64,
+) -> StdResult<Vec<(Contract, UserReward)>> {
+    let staked = latest_stake(storage, user.as_str())?;
+    let mut rewards = vec![];
+
+    for token in reward_tokens_r(storage).load()? {
+        let pool = accrued_pool(storage, &token.address, now)?;
+        reward_pool_w(storage).save(token.address.as_str().as_bytes(), &pool)?;
+
+        let reward = user_reward_r(storage, token.address.as_str())
+            .may_load(user.as_str().as_bytes())?
+            .unwrap_or_default();
+        let reward = UserReward {
+            pending: earned_rewards(&reward, staked, pool.reward_per_token)?,
+            reward_per_token_paid: pool.reward_per_token,
+        };
+        user_reward_w(storage, token.address.as_str()).save(user.as_str().as_bytes(), &reward)?;
+
+        rewards.push((token, reward));
+    }
+
+    Ok(rewards)
+}
+
 pub fn try_update_config<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: &Env,
     admin: Option<Contract>,
     unbond_time: Option<u64>,
+    treasury: Option<HumanAddr>,
 ) -> StdResult<HandleResponse> {
     let config = config_r(&deps.storage).load()?;
     // Check if admin
@@ -57,6 +160,9 @@ pub fn try_update_config<S: Storage, A: Api, Q: Querier>(
         if let Some(unbond_time) = unbond_time {
             config.unbond_time = unbond_time;
         }
+        if treasury.is_some() {
+            config.treasury = treasury;
+        }
         Ok(config)
     })?;
 
@@ -69,6 +175,122 @@ pub fn try_update_config<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+pub fn try_register_reward_token<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    token: Contract,
+) -> StdResult<HandleResponse> {
+    let config = config_r(&deps.storage).load()?;
+    // Check if admin
+    if env.message.sender != config.admin.address {
+        return Err(StdError::Unauthorized { backtrace: None });
+    }
+
+    let mut tokens = reward_tokens_r(&deps.storage).load()?;
+    if tokens.iter().any(|t| t.address == token.address) {
+        return Err(StdError::generic_err("Reward token is already registered"));
+    }
+    tokens.push(token.clone());
+    reward_tokens_w(&mut deps.storage).save(&tokens)?;
+
+    // Stakers only earn from the rewards received after registration
+    reward_pool_w(&mut deps.storage).save(
+        token.address.as_str().as_bytes(),
+        &RewardPool {
+            reward_per_token: Uint128::zero(),
+            last_update: env.block.time,
+            available: Uint128::zero(),
+        },
+    )?;
+
+    Ok(HandleResponse {
+        messages: vec![register_receive_msg(
+            env.contract_code_hash.clone(),
+            None,
+            256,
+            token.code_hash,
+            token.address,
+        )?],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::RegisterRewardToken {
+            status: Success,
+        })?),
+    })
+}
+
+pub fn try_set_emissions<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    token: HumanAddr,
+    schedule: Option<Vec<EmissionPeriod>>,
+) -> StdResult<HandleResponse> {
+    let config = config_r(&deps.storage).load()?;
+    // Check if admin
+    if env.message.sender != config.admin.address {
+        return Err(StdError::Unauthorized { backtrace: None });
+    }
+
+    if let Some(schedule) = &schedule {
+        if schedule.iter().any(|period| period.start >= period.end) {
+            return Err(StdError::generic_err(
+                "Emission periods must end after they start",
+            ));
+        }
+    }
+
+    if reward_pool_r(&deps.storage)
+        .may_load(token.as_str().as_bytes())?
+        .is_none()
+    {
+        return Err(StdError::not_found("Reward token"));
+    }
+
+    // Rewards up to now are emitted with the previous schedule
+    let pool = accrued_pool(&deps.storage, &token, env.block.time)?;
+    reward_pool_w(&mut deps.storage).save(token.as_str().as_bytes(), &pool)?;
+
+    match schedule {
+        Some(mut schedule) => {
+            schedule.retain(|period| period.end > env.block.time);
+            emissions_w(&mut deps.storage).save(token.as_str().as_bytes(), &schedule)?;
+        }
+        None => emissions_w(&mut deps.storage).remove(token.as_str().as_bytes()),
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetEmissions { status: Success })?),
+    })
+}
+
+fn try_fund_rewards<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    token: &HumanAddr,
+    amount: Uint128,
+) -> StdResult<HandleResponse> {
+    // Emissions missed while unfunded are not paid out later
+    let mut pool = accrued_pool(&deps.storage, token, env.block.time)?;
+    pool.available += amount;
+    // Unscheduled tokens are distributed right away
+    accrue_rewards(
+        &mut pool,
+        emissions_r(&deps.storage)
+            .may_load(token.as_str().as_bytes())?
+            .as_deref(),
+        latest_stake(&deps.storage, TOTAL_CHECKPOINTS)?,
+        env.block.time,
+    )?;
+    reward_pool_w(&mut deps.storage).save(token.as_str().as_bytes(), &pool)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::FundRewards { status: Success })?),
+    })
+}
+
 pub fn try_stake<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: &Env,
@@ -77,11 +299,26 @@ pub fn try_stake<S: Storage, A: Api, Q: Querier>(
     amount: Uint128,
 ) -> StdResult<HandleResponse> {
     let config = config_r(&deps.storage).load()?;
-    // Check if staking token
+    // Any other registered reward token is distributed to stakers
     if env.message.sender != config.staked_token.address {
-        return Err(StdError::Unauthorized { backtrace: None });
+        if !reward_tokens_r(&deps.storage)
+            .load()?
+            .iter()
+            .any(|token| token.address == env.message.sender)
+        {
+            return Err(StdError::Unauthorized { backtrace: None });
+        }
+        return try_fund_rewards(deps, env, &env.message.sender, amount);
     }
 
+    // Treasury allocations fund the reward emissions instead of staking
+    if config.treasury.as_ref() == Some(&sender) {
+        return try_fund_rewards(deps, env, &config.staked_token.address, amount);
+    }
+
+    update_rewards(&mut deps.storage, &sender, env.block.time)?;
+    auto_compound(&mut deps.storage, &sender, env.block.height)?;
+
     let mut state = stake_state_r(&deps.storage).load()?;
 
     // Either create a new account or add stake
@@ -110,6 +347,24 @@ pub fn try_stake<S: Storage, A: Api, Q: Querier>(
     // Update total stake
     stake_state_w(&mut deps.storage).save(&state)?;
 
+    // Record voting stake
+    push_checkpoint(
+        &mut deps.storage,
+        sender.as_str(),
+        env.block.height,
+        staker_r(&deps.storage)
+            .load(sender.as_str().as_bytes())?
+            .tokens_staked,
+    )?;
+    let total = latest_stake(&deps.storage, TOTAL_CHECKPOINTS)? + amount;
+    push_checkpoint(
+        &mut deps.storage,
+        TOTAL_CHECKPOINTS,
+        env.block.height,
+        total,
+    )?;
+    move_delegated_stake(&mut deps.storage, &sender, env.block.height, amount, true)?;
+
     Ok(HandleResponse {
         messages: vec![],
         log: vec![],
@@ -124,6 +379,9 @@ pub fn try_unbond<S: Storage, A: Api, Q: Querier>(
 ) -> StdResult<HandleResponse> {
     let sender = env.message.sender.clone();
 
+    update_rewards(&mut deps.storage, &sender, env.block.time)?;
+    auto_compound(&mut deps.storage, &sender, env.block.height)?;
+
     let mut state = stake_state_r(&deps.storage).load()?;
 
     // Check if user has >= amount
@@ -186,6 +444,24 @@ pub fn try_unbond<S: Storage, A: Api, Q: Querier>(
 
     stake_state_w(&mut deps.storage).save(&state)?;
 
+    // Record voting stake
+    push_checkpoint(
+        &mut deps.storage,
+        sender.as_str(),
+        env.block.height,
+        staker_r(&deps.storage)
+            .load(sender.as_str().as_bytes())?
+            .tokens_staked,
+    )?;
+    let total = (latest_stake(&deps.storage, TOTAL_CHECKPOINTS)? - amount)?;
+    push_checkpoint(
+        &mut deps.storage,
+        TOTAL_CHECKPOINTS,
+        env.block.height,
+        total,
+    )?;
+    move_delegated_stake(&mut deps.storage, &sender, env.block.height, amount, false)?;
+
     Ok(HandleResponse {
         messages: vec![],
         log: vec![],
@@ -197,18 +473,70 @@ pub fn stake_weight(stake: Uint128, weight: u8) -> Uint128 {
     stake.multiply_ratio(weight, 100u128)
 }
 
-pub fn try_vote<S: Storage, A: Api, Q: Querier>(
+// Keeps the stake delegated to the staker's delegate in sync
+fn move_delegated_stake<S: Storage>(
+    storage: &mut S,
+    staker: &HumanAddr,
+    height: u64,
+    amount: Uint128,
+    add: bool,
+) -> StdResult<()> {
+    let delegate = match delegate_r(storage).may_load(staker.as_str().as_bytes())? {
+        None => return Ok(()),
+        Some(delegate) => delegated_owner(delegate.as_str()),
+    };
+
+    let delegated = latest_stake(storage, &delegate)?;
+    let delegated = match add {
+        true => delegated + amount,
+        false => (delegated - amount)?,
+    };
+    push_checkpoint(storage, &delegate, height, delegated)
+}
+
+pub fn try_delegate<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: &Env,
-    proposal_id: Uint128,
-    votes: Vec<UserVote>,
+    delegate: Option<HumanAddr>,
 ) -> StdResult<HandleResponse> {
-    let user_state = staker_r(&deps.storage).load(env.message.sender.to_string().as_bytes())?;
+    let sender = env.message.sender.clone();
+    // Delegating to yourself is the same as not delegating
+    let delegate = delegate.filter(|delegate| *delegate != sender);
+    update_rewards(&mut deps.storage, &sender, env.block.time)?;
+    auto_compound(&mut deps.storage, &sender, env.block.height)?;
+    let staked = latest_stake(&deps.storage, sender.as_str())?;
+
+    // Move the stake from the previous delegate to the new one
+    move_delegated_stake(&mut deps.storage, &sender, env.block.height, staked, false)?;
+    match &delegate {
+        Some(delegate) => {
+            delegate_w(&mut deps.storage).save(sender.as_str().as_bytes(), delegate)?
+        }
+        None => delegate_w(&mut deps.storage).remove(sender.as_str().as_bytes()),
+    }
+    move_delegated_stake(&mut deps.storage, &sender, env.block.height, staked, true)?;
+
+    push_delegation(
+        &mut deps.storage,
+        sender.as_str(),
+        env.block.height,
+        delegate,
+    )?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::Delegate { status: Success })?),
+    })
+}
+
+fn tally_votes(staked: Uint128, votes: &[UserVote]) -> StdResult<VoteTally> {
     // check that percentage is <= 100 and calculate distribution
     let mut total_votes = VoteTally {
         yes: Uint128(0),
         no: Uint128(0),
         abstain: Uint128(0),
+        veto: Uint128(0),
     };
 
     let mut count = 0;
@@ -216,13 +544,16 @@ pub fn try_vote<S: Storage, A: Api, Q: Querier>(
     for vote in votes {
         match vote.vote {
             Vote::Yes => {
-                total_votes.yes += stake_weight(user_state.tokens_staked, vote.weight);
+                total_votes.yes += stake_weight(staked, vote.weight);
             }
             Vote::No => {
-                total_votes.no += stake_weight(user_state.tokens_staked, vote.weight);
+                total_votes.no += stake_weight(staked, vote.weight);
             }
             Vote::Abstain => {
-                total_votes.abstain += stake_weight(user_state.tokens_staked, vote.weight);
+                total_votes.abstain += stake_weight(staked, vote.weight);
+            }
+            Vote::Veto => {
+                total_votes.veto += stake_weight(staked, vote.weight);
             }
         };
         count += vote.weight;
@@ -235,14 +566,88 @@ pub fn try_vote<S: Storage, A: Api, Q: Querier>(
         });
     }
 
-    // Admin is governance, send to governance
+    Ok(total_votes)
+}
+
+pub fn try_vote<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    proposal_id: Uint128,
+    votes: Vec<UserVote>,
+) -> StdResult<HandleResponse> {
+    // Admin is governance
     let config = config_r(&deps.storage).load()?;
-    let messages = vec![shade_protocol::governance::HandleMsg::MakeVote {
-        voter: env.message.sender.clone(),
+
+    // Voting power is fixed to the stake held when voting started
+    let snapshot = match (governance::QueryMsg::GetProposal { proposal_id }.query(
+        &deps.querier,
+        config.admin.code_hash.clone(),
+        config.admin.address.clone(),
+    )?) {
+        governance::QueryAnswer::Proposal { proposal } => proposal.snapshot,
+        _ => None,
+    }
+    .ok_or_else(|| StdError::generic_err("Proposal is not in voting"))?;
+
+    let voter = env.message.sender.clone();
+    update_rewards(&mut deps.storage, &voter, env.block.time)?;
+    auto_compound(&mut deps.storage, &voter, env.block.height)?;
+
+    let first_vote = proposal_votes_r(&deps.storage, proposal_id)
+        .may_load(voter.as_str().as_bytes())?
+        .is_none();
+
+    // Voting directly takes the voter's stake back from their delegate for this proposal
+    let mut recount = None;
+    if first_vote {
+        if let Some(delegate) = delegate_at(&deps.storage, voter.as_str(), snapshot)? {
+            let staked = stake_at(&deps.storage, voter.as_str(), snapshot)?;
+            proposal_overrides_w(&mut deps.storage, proposal_id)
+                .update(delegate.as_str().as_bytes(), |overridden| {
+                    Ok(overridden.unwrap_or_default() + staked)
+                })?;
+            recount = Some(delegate);
+        }
+    }
+
+    let power = voting_power_at(&deps.storage, proposal_id, voter.as_str(), snapshot)?;
+    if power.is_zero() {
+        return Err(StdError::generic_err("No stake at proposal snapshot"));
+    }
+
+    // Send to governance
+    let mut messages = vec![shade_protocol::governance::HandleMsg::MakeVote {
+        voter: voter.clone(),
         proposal_id,
-        votes: total_votes,
+        votes: tally_votes(power, &votes)?,
+    }
+    .to_cosmos_msg(
+        config.admin.code_hash.clone(),
+        config.admin.address.clone(),
+        None,
+    )?];
+    proposal_votes_w(&mut deps.storage, proposal_id).save(voter.as_str().as_bytes(), &votes)?;
+
+    // Delegates that already voted lose the overridden stake
+    if let Some(delegate) = recount {
+        if let Some(delegate_votes) =
+            proposal_votes_r(&deps.storage, proposal_id).may_load(delegate.as_str().as_bytes())?
+        {
+            let power = voting_power_at(&deps.storage, proposal_id, delegate.as_str(), snapshot)?;
+            messages.push(
+                shade_protocol::governance::HandleMsg::MakeVote {
+                    voter: delegate,
+                    proposal_id,
+                    votes: tally_votes(power, &delegate_votes)?,
+                }
+                .to_cosmos_msg(
+                    config.admin.code_hash,
+                    config.admin.address,
+                    None,
+                )?,
+            );
+        }
     }
-    .to_cosmos_msg(config.admin.code_hash, config.admin.address, None)?];
 
     Ok(HandleResponse {
         messages,
@@ -256,6 +661,8 @@ pub fn try_claim_unbond<S: Storage, A: Api, Q: Querier>(
     env: &Env,
 ) -> StdResult<HandleResponse> {
     let config = config_r(&deps.storage).load()?;
+    update_rewards(&mut deps.storage, &env.message.sender, env.block.time)?;
+    auto_compound(&mut deps.storage, &env.message.sender, env.block.height)?;
 
     let mut total = Uint128::zero();
 
@@ -297,48 +704,205 @@ pub fn try_claim_unbond<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+// Pays the rewards accrued through share growth
+fn claim_share_rewards<S: Storage>(storage: &mut S, user: &HumanAddr) -> StdResult<Uint128> {
+    let mut state = stake_state_r(storage).load()?;
+    let mut rewards = Uint128::zero();
+
+    staker_w(storage).update(user.to_string().as_bytes(), |user_state| {
+        let mut user = user_state.ok_or_else(|| StdError::NotFound {
+            kind: "user".to_string(),
+            backtrace: None,
+        })?;
+
+        rewards = calculate_rewards(&user, &state);
+        let shares = calculate_shares(rewards, &state);
+        user.shares = (user.shares - shares)?;
+        state.total_shares = (state.total_shares - shares)?;
+        state.total_tokens = (state.total_tokens - rewards)?;
+
+        Ok(user)
+    })?;
+    stake_state_w(storage).save(&state)?;
+
+    Ok(rewards)
+}
+
 pub fn try_claim_rewards<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: &Env,
+    tokens: Option<Vec<HumanAddr>>,
 ) -> StdResult<HandleResponse> {
     let config = config_r(&deps.storage).load()?;
+    let sender = env.message.sender.clone();
 
-    let mut state = stake_state_r(&deps.storage).load()?;
+    let earned = update_rewards(&mut deps.storage, &sender, env.block.time)?;
+    if let Some(tokens) = &tokens {
+        if tokens
+            .iter()
+            .any(|token| !earned.iter().any(|(reward, _)| reward.address == *token))
+        {
+            return Err(StdError::not_found("Reward token"));
+        }
+    }
+
+    let auto_compound = is_auto_compounding(&deps.storage, &sender)?;
     let mut messages = vec![];
+    for (token, reward) in earned {
+        if let Some(tokens) = &tokens {
+            if !tokens.contains(&token.address) {
+                continue;
+            }
+        }
 
-    staker_w(&mut deps.storage).update(
-        env.message.sender.to_string().as_bytes(),
-        |user_state| {
-            let mut user = user_state.ok_or_else(|| StdError::NotFound {
-                kind: "user".to_string(),
-                backtrace: None,
-            })?;
-
-            let rewards = calculate_rewards(&user, &state);
-            let shares = calculate_shares(rewards, &state);
-            user.shares = (user.shares - shares)?;
-            state.total_shares = (state.total_shares - shares)?;
-            state.total_tokens = (state.total_tokens - rewards)?;
+        if auto_compound && token.address == config.staked_token.address {
+            compound_rewards(&mut deps.storage, &config, &sender, env.block.height)?;
+            continue;
+        }
+
+        user_reward_w(&mut deps.storage, token.address.as_str()).save(
+            sender.as_str().as_bytes(),
+            &UserReward {
+                pending: Uint128::zero(),
+                ..reward
+            },
+        )?;
+
+        let mut amount = reward.pending;
+        // Share rewards are paid along with the staked token emissions
+        if token.address == config.staked_token.address {
+            amount += claim_share_rewards(&mut deps.storage, &sender)?;
+        }
 
+        if !amount.is_zero() {
             messages.push(send_msg(
-                env.message.sender.clone(),
-                rewards,
+                sender.clone(),
+                amount,
                 None,
                 None,
                 None,
                 1,
-                config.staked_token.code_hash.clone(),
-                config.staked_token.address.clone(),
+                token.code_hash,
+                token.address,
             )?);
+        }
+    }
+
+    Ok(HandleResponse {
+        messages,
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::ClaimRewards { status: Success })?),
+    })
+}
+
+fn is_auto_compounding<S: Storage>(storage: &S, user: &HumanAddr) -> StdResult<bool> {
+    Ok(auto_compound_r(storage)
+        .may_load(user.as_str().as_bytes())?
+        .unwrap_or(false))
+}
+
+// Compounds for users that opted in whenever their position is touched, rewards must be updated first
+fn auto_compound<S: Storage>(storage: &mut S, user: &HumanAddr, height: u64) -> StdResult<()> {
+    if is_auto_compounding(storage, user)? {
+        let config = config_r(storage).load()?;
+        compound_rewards(storage, &config, user, height)?;
+    }
+    Ok(())
+}
 
-            Ok(user)
+// Moves the pending staked token rewards into the user's stake, rewards must be updated first
+fn compound_rewards<S: Storage>(
+    storage: &mut S,
+    config: &Config,
+    user: &HumanAddr,
+    height: u64,
+) -> StdResult<Uint128> {
+    let mut user_stake = match staker_r(storage).may_load(user.as_str().as_bytes())? {
+        None => return Ok(Uint128::zero()),
+        Some(user_stake) => user_stake,
+    };
+    let mut state = stake_state_r(storage).load()?;
+    let token = config.staked_token.address.as_str();
+
+    let reward = user_reward_r(storage, token)
+        .may_load(user.as_str().as_bytes())?
+        .unwrap_or_default();
+    user_reward_w(storage, token).save(
+        user.as_str().as_bytes(),
+        &UserReward {
+            pending: Uint128::zero(),
+            ..reward
         },
     )?;
 
+    // Share rewards are already held as shares, they only become principal
+    let share_rewards = calculate_rewards(&user_stake, &state);
+    // Emitted rewards buy new shares
+    let shares = calculate_shares(reward.pending, &state);
+    user_stake.shares += shares;
+    state.total_shares += shares;
+    state.total_tokens += reward.pending;
+
+    let compounded = share_rewards + reward.pending;
+    if compounded.is_zero() {
+        return Ok(compounded);
+    }
+    user_stake.tokens_staked += compounded;
+
+    staker_w(storage).save(user.as_str().as_bytes(), &user_stake)?;
+    stake_state_w(storage).save(&state)?;
+
+    // Record voting stake
+    push_checkpoint(storage, user.as_str(), height, user_stake.tokens_staked)?;
+    let total = latest_stake(storage, TOTAL_CHECKPOINTS)? + compounded;
+    push_checkpoint(storage, TOTAL_CHECKPOINTS, height, total)?;
+    move_delegated_stake(storage, user, height, compounded, true)?;
+
+    Ok(compounded)
+}
+
+pub fn try_compound<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+) -> StdResult<HandleResponse> {
+    let config = config_r(&deps.storage).load()?;
+    let sender = env.message.sender.clone();
+
+    if staker_r(&deps.storage)
+        .may_load(sender.as_str().as_bytes())?
+        .is_none()
+    {
+        return Err(StdError::not_found("user"));
+    }
+
+    update_rewards(&mut deps.storage, &sender, env.block.time)?;
+    compound_rewards(&mut deps.storage, &config, &sender, env.block.height)?;
+
     Ok(HandleResponse {
-        messages,
+        messages: vec![],
         log: vec![],
-        data: Some(to_binary(&HandleAnswer::ClaimRewards { status: Success })?),
+        data: Some(to_binary(&HandleAnswer::Compound { status: Success })?),
+    })
+}
+
+pub fn try_set_auto_compound<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    enabled: bool,
+) -> StdResult<HandleResponse> {
+    let sender = env.message.sender.clone();
+
+    match enabled {
+        true => auto_compound_w(&mut deps.storage).save(sender.as_str().as_bytes(), &true)?,
+        false => auto_compound_w(&mut deps.storage).remove(sender.as_str().as_bytes()),
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetAutoCompound {
+            status: Success,
+        })?),
     })
 }
 
@@ -1,9 +1,18 @@
 use crate::{
-    handle::calculate_rewards,
-    state::{config_r, stake_state_r, staker_r, unbonding_r, user_unbonding_r, viewking_key_r},
+    handle::{accrued_pool, calculate_rewards, earned_rewards, scheduled_emission},
+    state::{
+        auto_compound_r, config_r, delegate_at, delegate_r, delegated_owner, emissions_r,
+        latest_stake, reward_pool_r, reward_tokens_r, stake_at, stake_state_r, staker_r,
+        unbonding_r, user_reward_r, user_unbonding_r, viewking_key_r, TOTAL_CHECKPOINTS,
+    },
 };
 use cosmwasm_std::{Api, Extern, HumanAddr, Querier, StdError, StdResult, Storage, Uint128};
-use shade_protocol::staking::QueryAnswer;
+use shade_protocol::staking::{
+    stake::{EmissionPeriod, TokenReward},
+    QueryAnswer, REWARD_PRECISION,
+};
+
+const SECONDS_PER_YEAR: u64 = 31_536_000;
 
 pub fn config<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>) -> StdResult<QueryAnswer> {
     Ok(QueryAnswer::Config {
@@ -19,6 +28,15 @@ pub fn total_staked<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+pub fn total_staked_at<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    height: u64,
+) -> StdResult<QueryAnswer> {
+    Ok(QueryAnswer::TotalStakedAt {
+        total: stake_at(&deps.storage, TOTAL_CHECKPOINTS, height)?,
+    })
+}
+
 pub fn total_unbonding<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
     start_limit: Option<u64>,
@@ -70,10 +88,158 @@ pub fn user_stake<S: Storage, A: Api, Q: Querier>(
         }
     }
 
+    // Emitted rewards are projected to the given time
+    let staked_token = config_r(&deps.storage).load()?.staked_token.address;
+    let mut pending_rewards = calculate_rewards(&user_state, &state);
+    let mut token_rewards = vec![];
+    for token in reward_tokens_r(&deps.storage).load()? {
+        let pool = accrued_pool(&deps.storage, &token.address, time)?;
+        let pending = earned_rewards(
+            &user_reward_r(&deps.storage, token.address.as_str())
+                .may_load(address.to_string().as_bytes())?
+                .unwrap_or_default(),
+            user_state.tokens_staked,
+            pool.reward_per_token,
+        )?;
+
+        if token.address == staked_token {
+            pending_rewards += pending;
+        }
+        token_rewards.push(TokenReward {
+            token: token.address,
+            pending,
+        });
+    }
+
     Ok(QueryAnswer::UserStake {
         staked: user_state.tokens_staked,
-        pending_rewards: calculate_rewards(&user_state, &state),
+        pending_rewards,
         unbonding,
         unbonded,
+        token_rewards,
+        auto_compound: auto_compound_r(&deps.storage)
+            .may_load(address.to_string().as_bytes())?
+            .unwrap_or(false),
+    })
+}
+
+pub fn delegation<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: HumanAddr,
+    key: String,
+) -> StdResult<QueryAnswer> {
+    if viewking_key_r(&deps.storage).load(address.to_string().as_bytes())? != key {
+        return Err(StdError::Unauthorized { backtrace: None });
+    }
+
+    Ok(QueryAnswer::Delegation {
+        delegate: delegate_r(&deps.storage).may_load(address.to_string().as_bytes())?,
+        delegated: latest_stake(&deps.storage, &delegated_owner(address.as_str()))?,
+    })
+}
+
+pub fn voting_power<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: HumanAddr,
+    key: String,
+    height: Option<u64>,
+) -> StdResult<QueryAnswer> {
+    if viewking_key_r(&deps.storage).load(address.to_string().as_bytes())? != key {
+        return Err(StdError::Unauthorized { backtrace: None });
+    }
+
+    let delegated_owner = delegated_owner(address.as_str());
+    let (staked, delegated, delegate) = match height {
+        Some(height) => (
+            stake_at(&deps.storage, address.as_str(), height)?,
+            stake_at(&deps.storage, &delegated_owner, height)?,
+            delegate_at(&deps.storage, address.as_str(), height)?,
+        ),
+        None => (
+            latest_stake(&deps.storage, address.as_str())?,
+            latest_stake(&deps.storage, &delegated_owner)?,
+            delegate_r(&deps.storage).may_load(address.to_string().as_bytes())?,
+        ),
+    };
+
+    // Same as voting_power_at before any delegator votes itself
+    Ok(QueryAnswer::VotingPower {
+        staked,
+        delegated,
+        delegate,
+        power: staked + delegated,
+    })
+}
+
+pub fn reward_tokens<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<QueryAnswer> {
+    Ok(QueryAnswer::RewardTokens {
+        tokens: reward_tokens_r(&deps.storage).load()?,
+    })
+}
+
+pub fn emissions<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    token: HumanAddr,
+) -> StdResult<QueryAnswer> {
+    Ok(QueryAnswer::Emissions {
+        schedule: emissions_r(&deps.storage).may_load(token.as_str().as_bytes())?,
+        pool: reward_pool_r(&deps.storage)
+            .may_load(token.as_str().as_bytes())?
+            .ok_or_else(|| StdError::not_found("Reward token"))?,
+    })
+}
+
+// Emission schedule of the staked token
+fn staked_token_emissions<S: Storage>(storage: &S) -> StdResult<Vec<EmissionPeriod>> {
+    let token = config_r(storage).load()?.staked_token.address;
+    Ok(emissions_r(storage)
+        .may_load(token.as_str().as_bytes())?
+        .unwrap_or_default())
+}
+
+pub fn apr<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    time: u64,
+) -> StdResult<QueryAnswer> {
+    let total_staked = latest_stake(&deps.storage, TOTAL_CHECKPOINTS)?;
+    if total_staked.is_zero() {
+        return Ok(QueryAnswer::Apr {
+            apr: Uint128::zero(),
+        });
+    }
+
+    // Current rate kept for a year
+    let rate = staked_token_emissions(&deps.storage)?
+        .iter()
+        .filter(|period| period.start <= time && time < period.end)
+        .fold(Uint128::zero(), |rate, period| rate + period.rate);
+
+    Ok(QueryAnswer::Apr {
+        apr: rate
+            .multiply_ratio(SECONDS_PER_YEAR, 1u64)
+            .multiply_ratio(REWARD_PRECISION, total_staked),
+    })
+}
+
+pub fn projected_rewards<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    amount: Uint128,
+    start: u64,
+    end: u64,
+) -> StdResult<QueryAnswer> {
+    // The amount is added to the current stake
+    let total_staked = latest_stake(&deps.storage, TOTAL_CHECKPOINTS)? + amount;
+    if total_staked.is_zero() {
+        return Ok(QueryAnswer::ProjectedRewards {
+            rewards: Uint128::zero(),
+        });
+    }
+
+    let emission = scheduled_emission(&staked_token_emissions(&deps.storage)?, start, end);
+
+    Ok(QueryAnswer::ProjectedRewards {
+        rewards: emission.multiply_ratio(amount, total_staked),
     })
 }
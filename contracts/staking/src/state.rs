@@ -1,13 +1,20 @@
-use cosmwasm_std::Storage;
+use cosmwasm_std::{HumanAddr, StdResult, Storage, Uint128};
 use cosmwasm_storage::{
     bucket, bucket_read, singleton, singleton_read, Bucket, ReadonlyBucket, ReadonlySingleton,
     Singleton,
 };
 
 use binary_heap_plus::{BinaryHeap, MinComparator};
-use shade_protocol::staking::{
-    stake::{Stake, Unbonding, UserStake},
-    Config,
+use shade_protocol::utils::asset::Contract;
+use shade_protocol::{
+    governance::vote::UserVote,
+    staking::{
+        stake::{
+            Checkpoint, DelegationCheckpoint, EmissionPeriod, RewardPool, Stake, Unbonding,
+            UserReward, UserStake,
+        },
+        Config,
+    },
 };
 
 pub static CONFIG_KEY: &[u8] = b"config";
@@ -16,6 +23,22 @@ pub static STAKER_KEY: &[u8] = b"staker";
 pub static UNBONDING_KEY: &[u8] = b"unbonding";
 pub static USER_UNBONDING_KEY: &[u8] = b"user_unbonding";
 pub static VIEWKING_KEY: &[u8] = b"viewing_key";
+pub static CHECKPOINTS_KEY: &str = "checkpoints";
+pub static CHECKPOINT_COUNT_KEY: &[u8] = b"checkpoint_count";
+// Checkpoint owner used for the total stake, cannot collide with an address
+pub static TOTAL_CHECKPOINTS: &str = "total";
+// Checkpoint owner prefix for stake delegated to an address
+pub static DELEGATED_CHECKPOINTS: &str = "delegated/";
+pub static DELEGATE_KEY: &[u8] = b"delegate";
+pub static DELEGATIONS_KEY: &str = "delegations";
+pub static DELEGATION_COUNT_KEY: &[u8] = b"delegation_count";
+pub static PROPOSAL_VOTES_KEY: &str = "proposal_votes";
+pub static PROPOSAL_OVERRIDES_KEY: &str = "proposal_overrides";
+pub static REWARD_TOKENS_KEY: &[u8] = b"reward_tokens";
+pub static EMISSIONS_KEY: &[u8] = b"emissions";
+pub static REWARD_POOL_KEY: &[u8] = b"reward_pool";
+pub static USER_REWARD_KEY: &str = "user_reward";
+pub static AUTO_COMPOUND_KEY: &[u8] = b"auto_compound";
 
 pub fn config_w<S: Storage>(storage: &mut S) -> Singleton<S, Config> {
     singleton(storage, CONFIG_KEY)
@@ -41,6 +64,51 @@ pub fn staker_w<S: Storage>(storage: &mut S) -> Bucket<S, UserStake> {
     bucket(STAKER_KEY, storage)
 }
 
+// Tokens distributed to stakers, the staked token is always first
+pub fn reward_tokens_w<S: Storage>(storage: &mut S) -> Singleton<S, Vec<Contract>> {
+    singleton(storage, REWARD_TOKENS_KEY)
+}
+
+pub fn reward_tokens_r<S: Storage>(storage: &S) -> ReadonlySingleton<S, Vec<Contract>> {
+    singleton_read(storage, REWARD_TOKENS_KEY)
+}
+
+// token -> emission schedule, rewards are distributed as they arrive without one
+pub fn emissions_w<S: Storage>(storage: &mut S) -> Bucket<S, Vec<EmissionPeriod>> {
+    bucket(EMISSIONS_KEY, storage)
+}
+
+pub fn emissions_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, Vec<EmissionPeriod>> {
+    bucket_read(EMISSIONS_KEY, storage)
+}
+
+// token -> reward pool
+pub fn reward_pool_w<S: Storage>(storage: &mut S) -> Bucket<S, RewardPool> {
+    bucket(REWARD_POOL_KEY, storage)
+}
+
+pub fn reward_pool_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, RewardPool> {
+    bucket_read(REWARD_POOL_KEY, storage)
+}
+
+// token -> user -> reward
+pub fn user_reward_r<S: Storage>(storage: &S, token: &str) -> ReadonlyBucket<S, UserReward> {
+    bucket_read((token.to_string() + USER_REWARD_KEY).as_bytes(), storage)
+}
+
+pub fn user_reward_w<S: Storage>(storage: &mut S, token: &str) -> Bucket<S, UserReward> {
+    bucket((token.to_string() + USER_REWARD_KEY).as_bytes(), storage)
+}
+
+// Users that compound their staked token rewards
+pub fn auto_compound_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, bool> {
+    bucket_read(AUTO_COMPOUND_KEY, storage)
+}
+
+pub fn auto_compound_w<S: Storage>(storage: &mut S) -> Bucket<S, bool> {
+    bucket(AUTO_COMPOUND_KEY, storage)
+}
+
 // Ideally these queues will be removed
 pub fn unbonding_w<S: Storage>(
     storage: &mut S,
@@ -73,3 +141,221 @@ pub fn viewking_key_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, String> {
 pub fn viewking_key_w<S: Storage>(storage: &mut S) -> Bucket<S, String> {
     bucket(VIEWKING_KEY, storage)
 }
+
+// owner -> index -> checkpoint, ordered by height
+pub fn checkpoints_r<S: Storage>(storage: &S, owner: &str) -> ReadonlyBucket<S, Checkpoint> {
+    bucket_read((owner.to_string() + CHECKPOINTS_KEY).as_bytes(), storage)
+}
+
+pub fn checkpoints_w<S: Storage>(storage: &mut S, owner: &str) -> Bucket<S, Checkpoint> {
+    bucket((owner.to_string() + CHECKPOINTS_KEY).as_bytes(), storage)
+}
+
+pub fn checkpoint_count_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, u64> {
+    bucket_read(CHECKPOINT_COUNT_KEY, storage)
+}
+
+pub fn checkpoint_count_w<S: Storage>(storage: &mut S) -> Bucket<S, u64> {
+    bucket(CHECKPOINT_COUNT_KEY, storage)
+}
+
+pub fn latest_stake<S: Storage>(storage: &S, owner: &str) -> StdResult<Uint128> {
+    let count = checkpoint_count_r(storage)
+        .may_load(owner.as_bytes())?
+        .unwrap_or(0);
+    if count == 0 {
+        return Ok(Uint128::zero());
+    }
+    Ok(checkpoints_r(storage, owner)
+        .load(&(count - 1).to_be_bytes())?
+        .amount)
+}
+
+pub fn push_checkpoint<S: Storage>(
+    storage: &mut S,
+    owner: &str,
+    height: u64,
+    amount: Uint128,
+) -> StdResult<()> {
+    let count = checkpoint_count_r(storage)
+        .may_load(owner.as_bytes())?
+        .unwrap_or(0);
+    let checkpoint = Checkpoint { height, amount };
+
+    // Only keep the last change of a block
+    if count > 0 {
+        let last = (count - 1).to_be_bytes();
+        if checkpoints_r(storage, owner).load(&last)?.height == height {
+            return checkpoints_w(storage, owner).save(&last, &checkpoint);
+        }
+    }
+
+    checkpoints_w(storage, owner).save(&count.to_be_bytes(), &checkpoint)?;
+    checkpoint_count_w(storage).save(owner.as_bytes(), &(count + 1))
+}
+
+// Index of the last checkpoint before height
+fn checkpoint_before<F>(count: u64, height: u64, height_of: F) -> StdResult<Option<u64>>
+where
+    F: Fn(u64) -> StdResult<u64>,
+{
+    // Find the first checkpoint at or after height
+    let (mut low, mut high) = (0u64, count);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if height_of(mid)? < height {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+
+    Ok(low.checked_sub(1))
+}
+
+// Stake held before the given block, so changes made in the snapshot block don't count
+pub fn stake_at<S: Storage>(storage: &S, owner: &str, height: u64) -> StdResult<Uint128> {
+    let count = checkpoint_count_r(storage)
+        .may_load(owner.as_bytes())?
+        .unwrap_or(0);
+    let checkpoints = checkpoints_r(storage, owner);
+
+    match checkpoint_before(count, height, |i| {
+        Ok(checkpoints.load(&i.to_be_bytes())?.height)
+    })? {
+        None => Ok(Uint128::zero()),
+        Some(i) => Ok(checkpoints.load(&i.to_be_bytes())?.amount),
+    }
+}
+
+pub fn delegated_owner(delegate: &str) -> String {
+    DELEGATED_CHECKPOINTS.to_string() + delegate
+}
+
+// Current delegate of a staker
+pub fn delegate_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, HumanAddr> {
+    bucket_read(DELEGATE_KEY, storage)
+}
+
+pub fn delegate_w<S: Storage>(storage: &mut S) -> Bucket<S, HumanAddr> {
+    bucket(DELEGATE_KEY, storage)
+}
+
+// owner -> index -> delegation, ordered by height
+pub fn delegations_r<S: Storage>(
+    storage: &S,
+    owner: &str,
+) -> ReadonlyBucket<S, DelegationCheckpoint> {
+    bucket_read((owner.to_string() + DELEGATIONS_KEY).as_bytes(), storage)
+}
+
+pub fn delegations_w<S: Storage>(storage: &mut S, owner: &str) -> Bucket<S, DelegationCheckpoint> {
+    bucket((owner.to_string() + DELEGATIONS_KEY).as_bytes(), storage)
+}
+
+pub fn delegation_count_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, u64> {
+    bucket_read(DELEGATION_COUNT_KEY, storage)
+}
+
+pub fn delegation_count_w<S: Storage>(storage: &mut S) -> Bucket<S, u64> {
+    bucket(DELEGATION_COUNT_KEY, storage)
+}
+
+pub fn push_delegation<S: Storage>(
+    storage: &mut S,
+    owner: &str,
+    height: u64,
+    delegate: Option<HumanAddr>,
+) -> StdResult<()> {
+    let count = delegation_count_r(storage)
+        .may_load(owner.as_bytes())?
+        .unwrap_or(0);
+    let checkpoint = DelegationCheckpoint { height, delegate };
+
+    // Only keep the last change of a block
+    if count > 0 {
+        let last = (count - 1).to_be_bytes();
+        if delegations_r(storage, owner).load(&last)?.height == height {
+            return delegations_w(storage, owner).save(&last, &checkpoint);
+        }
+    }
+
+    delegations_w(storage, owner).save(&count.to_be_bytes(), &checkpoint)?;
+    delegation_count_w(storage).save(owner.as_bytes(), &(count + 1))
+}
+
+// Delegate chosen before the given block
+pub fn delegate_at<S: Storage>(
+    storage: &S,
+    owner: &str,
+    height: u64,
+) -> StdResult<Option<HumanAddr>> {
+    let count = delegation_count_r(storage)
+        .may_load(owner.as_bytes())?
+        .unwrap_or(0);
+    let delegations = delegations_r(storage, owner);
+
+    match checkpoint_before(count, height, |i| {
+        Ok(delegations.load(&i.to_be_bytes())?.height)
+    })? {
+        None => Ok(None),
+        Some(i) => Ok(delegations.load(&i.to_be_bytes())?.delegate),
+    }
+}
+
+// proposal -> voter -> vote, kept to recount a delegate when a delegator overrides it
+pub fn proposal_votes_r<S: Storage>(
+    storage: &S,
+    proposal_id: Uint128,
+) -> ReadonlyBucket<S, Vec<UserVote>> {
+    bucket_read(
+        (proposal_id.to_string() + PROPOSAL_VOTES_KEY).as_bytes(),
+        storage,
+    )
+}
+
+pub fn proposal_votes_w<S: Storage>(
+    storage: &mut S,
+    proposal_id: Uint128,
+) -> Bucket<S, Vec<UserVote>> {
+    bucket(
+        (proposal_id.to_string() + PROPOSAL_VOTES_KEY).as_bytes(),
+        storage,
+    )
+}
+
+// proposal -> delegate -> stake taken back by delegators that voted themselves
+pub fn proposal_overrides_r<S: Storage>(
+    storage: &S,
+    proposal_id: Uint128,
+) -> ReadonlyBucket<S, Uint128> {
+    bucket_read(
+        (proposal_id.to_string() + PROPOSAL_OVERRIDES_KEY).as_bytes(),
+        storage,
+    )
+}
+
+pub fn proposal_overrides_w<S: Storage>(
+    storage: &mut S,
+    proposal_id: Uint128,
+) -> Bucket<S, Uint128> {
+    bucket(
+        (proposal_id.to_string() + PROPOSAL_OVERRIDES_KEY).as_bytes(),
+        storage,
+    )
+}
+
+// Own stake plus stake delegated to the voter, minus delegators that voted themselves
+pub fn voting_power_at<S: Storage>(
+    storage: &S,
+    proposal_id: Uint128,
+    voter: &str,
+    height: u64,
+) -> StdResult<Uint128> {
+    let overridden = proposal_overrides_r(storage, proposal_id)
+        .may_load(voter.as_bytes())?
+        .unwrap_or_default();
+
+    stake_at(storage, voter, height)? + stake_at(storage, &delegated_owner(voter), height)?
+        - overridden
+}
@@ -1,9 +1,21 @@
 #[cfg(test)]
 pub mod tests {
+    use crate::contract;
     use crate::handle::{calculate_shares, calculate_tokens, stake_weight};
+    use crate::state::{
+        proposal_overrides_w, push_checkpoint, stake_at, voting_power_at, TOTAL_CHECKPOINTS,
+    };
     use binary_heap_plus::{BinaryHeap, MinComparator};
-    use cosmwasm_std::Uint128;
-    use shade_protocol::staking::stake::{Stake, Unbonding, UserStake};
+    use cosmwasm_std::{
+        coins, from_binary,
+        testing::{mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage},
+        CosmosMsg, Extern, HandleResponse, HumanAddr, StdResult, Uint128, WasmMsg,
+    };
+    use shade_protocol::staking;
+    use shade_protocol::staking::stake::{
+        EmissionPeriod, Stake, TokenReward, Unbonding, UserStake,
+    };
+    use shade_protocol::utils::asset::Contract;
 
     #[test]
     fn test_weight_calculation() {
@@ -40,6 +52,418 @@ pub mod tests {
         assert_eq!(2, unbonding_heap.pop().unwrap().unbond_time);
     }
 
+    #[test]
+    fn stake_checkpoints() {
+        let mut storage = MockStorage::new();
+
+        push_checkpoint(&mut storage, "user", 10, Uint128(100)).unwrap();
+        push_checkpoint(&mut storage, "user", 20, Uint128(50)).unwrap();
+        // Same block overwrites
+        push_checkpoint(&mut storage, "user", 20, Uint128(300)).unwrap();
+        push_checkpoint(&mut storage, "user", 30, Uint128(0)).unwrap();
+
+        assert_eq!(Uint128::zero(), stake_at(&storage, "user", 10).unwrap());
+        assert_eq!(Uint128(100), stake_at(&storage, "user", 11).unwrap());
+        assert_eq!(Uint128(100), stake_at(&storage, "user", 20).unwrap());
+        assert_eq!(Uint128(300), stake_at(&storage, "user", 25).unwrap());
+        assert_eq!(Uint128::zero(), stake_at(&storage, "user", 31).unwrap());
+        assert_eq!(Uint128::zero(), stake_at(&storage, "other", 31).unwrap());
+    }
+
+    fn init_staking(treasury: Option<&str>) -> Extern<MockStorage, MockApi, MockQuerier> {
+        let mut deps = mock_dependencies(20, &coins(0, ""));
+        contract::init(
+            &mut deps,
+            mock_env("governance", &coins(0, "")),
+            staking::InitMsg {
+                admin: None,
+                unbond_time: 180,
+                staked_token: Contract {
+                    address: HumanAddr::from("token"),
+                    code_hash: String::from(""),
+                },
+                treasury: treasury.map(HumanAddr::from),
+                upgrade: None,
+            },
+        )
+        .unwrap();
+        deps
+    }
+
+    fn receive(
+        deps: &mut Extern<MockStorage, MockApi, MockQuerier>,
+        token: &str,
+        sender: &str,
+        amount: u128,
+    ) -> StdResult<HandleResponse> {
+        contract::handle(
+            deps,
+            mock_env(token, &coins(0, "")),
+            staking::HandleMsg::Receive {
+                sender: HumanAddr::from(sender),
+                from: HumanAddr::from(sender),
+                amount: Uint128(amount),
+            },
+        )
+    }
+
+    fn set_viewing_key(deps: &mut Extern<MockStorage, MockApi, MockQuerier>, user: &str) {
+        contract::handle(
+            deps,
+            mock_env(user, &coins(0, "")),
+            staking::HandleMsg::SetViewingKey {
+                key: user.to_string(),
+            },
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn vote_delegation() {
+        let mut deps = init_staking(None);
+
+        for (user, amount) in [("alice", 100), ("bob", 50)].iter() {
+            receive(&mut deps, "token", user, *amount).unwrap();
+            set_viewing_key(&mut deps, user);
+        }
+
+        let mut env = mock_env("alice", &coins(0, ""));
+        env.block.height += 10;
+        contract::handle(
+            &mut deps,
+            env,
+            staking::HandleMsg::Delegate {
+                delegate: Some(HumanAddr::from("bob")),
+            },
+        )
+        .unwrap();
+
+        let power = |deps: &Extern<_, _, _>, user: &str, height: Option<u64>| match from_binary(
+            &contract::query(
+                deps,
+                staking::QueryMsg::VotingPower {
+                    address: HumanAddr::from(user),
+                    key: user.to_string(),
+                    height,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+        {
+            staking::QueryAnswer::VotingPower { power, .. } => power,
+            _ => panic!("Received wrong answer"),
+        };
+
+        let height = mock_env("", &coins(0, "")).block.height;
+        assert_eq!(Uint128(150), power(&deps, "bob", None));
+        // Alice's stake still counts if she votes herself
+        assert_eq!(Uint128(100), power(&deps, "alice", None));
+        // Delegation only counts after the block it was made in
+        assert_eq!(Uint128(50), power(&deps, "bob", Some(height + 10)));
+        assert_eq!(Uint128(150), power(&deps, "bob", Some(height + 11)));
+
+        // A delegator voting directly takes its stake back from the delegate
+        let proposal = Uint128(1);
+        assert_eq!(
+            Uint128(150),
+            voting_power_at(&deps.storage, proposal, "bob", height + 11).unwrap()
+        );
+        proposal_overrides_w(&mut deps.storage, proposal)
+            .save(b"bob", &Uint128(100))
+            .unwrap();
+        assert_eq!(
+            Uint128(50),
+            voting_power_at(&deps.storage, proposal, "bob", height + 11).unwrap()
+        );
+        assert_eq!(
+            Uint128(100),
+            voting_power_at(&deps.storage, proposal, "alice", height + 11).unwrap()
+        );
+    }
+
+    #[test]
+    fn reward_emissions() {
+        let mut deps = init_staking(Some("treasury"));
+        let now = mock_env("", &coins(0, "")).block.time;
+
+        let schedule = vec![EmissionPeriod {
+            start: now,
+            end: now + 100,
+            rate: Uint128(10),
+        }];
+        assert!(contract::handle(
+            &mut deps,
+            mock_env("alice", &coins(0, "")),
+            staking::HandleMsg::SetEmissions {
+                token: HumanAddr::from("token"),
+                schedule: Some(schedule.clone()),
+            },
+        )
+        .is_err());
+        contract::handle(
+            &mut deps,
+            mock_env("governance", &coins(0, "")),
+            staking::HandleMsg::SetEmissions {
+                token: HumanAddr::from("token"),
+                schedule: Some(schedule),
+            },
+        )
+        .unwrap();
+
+        // Treasury funds half of the schedule
+        for (user, amount) in [("alice", 100), ("bob", 300), ("treasury", 500)].iter() {
+            receive(&mut deps, "token", user, *amount).unwrap();
+        }
+        set_viewing_key(&mut deps, "alice");
+
+        let pending = |deps: &Extern<_, _, _>, time: u64| match from_binary(
+            &contract::query(
+                deps,
+                staking::QueryMsg::UserStake {
+                    address: HumanAddr::from("alice"),
+                    key: "alice".to_string(),
+                    time,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+        {
+            staking::QueryAnswer::UserStake {
+                staked,
+                pending_rewards,
+                ..
+            } => {
+                assert_eq!(Uint128(100), staked);
+                pending_rewards
+            }
+            _ => panic!("Received wrong answer"),
+        };
+
+        assert_eq!(Uint128(125), pending(&deps, now + 50));
+        // Emissions stop once the funding runs out
+        assert_eq!(Uint128(125), pending(&deps, now + 100));
+
+        match from_binary(&contract::query(&deps, staking::QueryMsg::Apr { time: now }).unwrap())
+            .unwrap()
+        {
+            staking::QueryAnswer::Apr { apr } => assert_eq!(
+                Uint128(10 * 31_536_000 * staking::REWARD_PRECISION / 400),
+                apr
+            ),
+            _ => panic!("Received wrong answer"),
+        }
+        match from_binary(
+            &contract::query(
+                &deps,
+                staking::QueryMsg::ProjectedRewards {
+                    amount: Uint128(100),
+                    start: now,
+                    end: now + 100,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+        {
+            staking::QueryAnswer::ProjectedRewards { rewards } => {
+                assert_eq!(Uint128(200), rewards)
+            }
+            _ => panic!("Received wrong answer"),
+        }
+
+        let mut env = mock_env("alice", &coins(0, ""));
+        env.block.time = now + 50;
+        contract::handle(
+            &mut deps,
+            env,
+            staking::HandleMsg::ClaimRewards { tokens: None },
+        )
+        .unwrap();
+        assert_eq!(Uint128::zero(), pending(&deps, now + 50));
+    }
+
+    #[test]
+    fn multi_asset_rewards() {
+        let mut deps = init_staking(None);
+
+        let register = staking::HandleMsg::RegisterRewardToken {
+            token: Contract {
+                address: HumanAddr::from("silk"),
+                code_hash: String::from(""),
+            },
+        };
+        assert!(contract::handle(
+            &mut deps,
+            mock_env("alice", &coins(0, "")),
+            register.clone()
+        )
+        .is_err());
+        contract::handle(&mut deps, mock_env("governance", &coins(0, "")), register).unwrap();
+
+        for (token, sender, amount) in [
+            ("token", "alice", 100),
+            ("token", "bob", 300),
+            // Fee income is distributed as it arrives
+            ("silk", "mint", 400),
+        ]
+        .iter()
+        {
+            receive(&mut deps, token, sender, *amount).unwrap();
+        }
+        assert!(receive(&mut deps, "unknown", "mint", 100).is_err());
+
+        set_viewing_key(&mut deps, "alice");
+        let now = mock_env("", &coins(0, "")).block.time;
+        match from_binary(
+            &contract::query(
+                &deps,
+                staking::QueryMsg::UserStake {
+                    address: HumanAddr::from("alice"),
+                    key: "alice".to_string(),
+                    time: now,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+        {
+            staking::QueryAnswer::UserStake {
+                pending_rewards,
+                token_rewards,
+                ..
+            } => {
+                assert_eq!(Uint128::zero(), pending_rewards);
+                assert_eq!(
+                    vec![
+                        TokenReward {
+                            token: HumanAddr::from("token"),
+                            pending: Uint128::zero(),
+                        },
+                        TokenReward {
+                            token: HumanAddr::from("silk"),
+                            pending: Uint128(100),
+                        },
+                    ],
+                    token_rewards
+                );
+            }
+            _ => panic!("Received wrong answer"),
+        }
+
+        assert!(contract::handle(
+            &mut deps,
+            mock_env("alice", &coins(0, "")),
+            staking::HandleMsg::ClaimRewards {
+                tokens: Some(vec![HumanAddr::from("unknown")]),
+            },
+        )
+        .is_err());
+        let res = contract::handle(
+            &mut deps,
+            mock_env("alice", &coins(0, "")),
+            staking::HandleMsg::ClaimRewards {
+                tokens: Some(vec![HumanAddr::from("silk")]),
+            },
+        )
+        .unwrap();
+        assert_eq!(1, res.messages.len());
+        match &res.messages[0] {
+            CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, .. }) => {
+                assert_eq!(HumanAddr::from("silk"), *contract_addr)
+            }
+            _ => panic!("Expected a token transfer"),
+        }
+    }
+
+    #[test]
+    fn compound_rewards() {
+        let mut deps = init_staking(Some("treasury"));
+
+        // Treasury funding is distributed right away
+        contract::handle(
+            &mut deps,
+            mock_env("governance", &coins(0, "")),
+            staking::HandleMsg::SetEmissions {
+                token: HumanAddr::from("token"),
+                schedule: None,
+            },
+        )
+        .unwrap();
+        for (user, amount) in [("alice", 100), ("bob", 300), ("treasury", 400)].iter() {
+            receive(&mut deps, "token", user, *amount).unwrap();
+            set_viewing_key(&mut deps, user);
+        }
+
+        let now = mock_env("", &coins(0, "")).block.time;
+        let user_stake = |deps: &Extern<_, _, _>, user: &str| match from_binary(
+            &contract::query(
+                deps,
+                staking::QueryMsg::UserStake {
+                    address: HumanAddr::from(user),
+                    key: user.to_string(),
+                    time: now,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+        {
+            staking::QueryAnswer::UserStake {
+                staked,
+                pending_rewards,
+                ..
+            } => (staked, pending_rewards),
+            _ => panic!("Received wrong answer"),
+        };
+        assert_eq!((Uint128(100), Uint128(100)), user_stake(&deps, "alice"));
+
+        let res = contract::handle(
+            &mut deps,
+            mock_env("alice", &coins(0, "")),
+            staking::HandleMsg::Compound {},
+        )
+        .unwrap();
+        assert!(res.messages.is_empty());
+        assert_eq!((Uint128(200), Uint128::zero()), user_stake(&deps, "alice"));
+
+        // Auto compounding stakes the rewards instead of sending them
+        contract::handle(
+            &mut deps,
+            mock_env("bob", &coins(0, "")),
+            staking::HandleMsg::SetAutoCompound { enabled: true },
+        )
+        .unwrap();
+        let res = contract::handle(
+            &mut deps,
+            mock_env("bob", &coins(0, "")),
+            staking::HandleMsg::ClaimRewards { tokens: None },
+        )
+        .unwrap();
+        assert!(res.messages.is_empty());
+        assert_eq!((Uint128(600), Uint128::zero()), user_stake(&deps, "bob"));
+
+        let height = mock_env("", &coins(0, "")).block.height;
+        assert_eq!(
+            Uint128(800),
+            stake_at(&deps.storage, TOTAL_CHECKPOINTS, height + 1).unwrap()
+        );
+
+        // Any other action on the position compounds as well
+        receive(&mut deps, "token", "treasury", 800).unwrap();
+        contract::handle(
+            &mut deps,
+            mock_env("bob", &coins(0, "")),
+            staking::HandleMsg::Delegate {
+                delegate: Some(HumanAddr::from("alice")),
+            },
+        )
+        .unwrap();
+        assert_eq!((Uint128(1200), Uint128::zero()), user_stake(&deps, "bob"));
+        assert_eq!((Uint128(200), Uint128(200)), user_stake(&deps, "alice"));
+    }
+
     fn init_user() -> UserStake {
         UserStake {
             shares: Uint128::zero(),
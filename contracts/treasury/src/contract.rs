@@ -1,6 +1,6 @@
 use cosmwasm_std::{
     debug_print, to_binary, Api, Binary, Env, Extern, HandleResponse, InitResponse, Querier,
-    StdResult, Storage,
+    StdResult, Storage, Uint128,
 };
 
 use shade_protocol::treasury::{Config, HandleMsg, InitMsg, QueryMsg};
@@ -8,8 +8,8 @@ use shade_protocol::treasury::{Config, HandleMsg, InitMsg, QueryMsg};
 use crate::{
     handle, query,
     state::{
-        allocations_w, asset_list_w, config_w, last_allowance_refresh_w, self_address_w,
-        viewing_key_w,
+        asset_count_w, config_w, last_allowance_refresh_w, native_denoms_w, native_wrappers_w,
+        self_address_w, total_outflow_proposals_w, viewing_key_w,
     },
 };
 use chrono::prelude::*;
@@ -21,12 +21,18 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
 ) -> StdResult<InitResponse> {
     config_w(&mut deps.storage).save(&Config {
         admin: msg.admin.unwrap_or(env.message.sender.clone()),
-        sscrt: msg.sscrt,
+        sscrt: msg.sscrt.clone(),
+        oracle: msg.oracle,
     })?;
 
+    // sSCRT wraps uscrt out of the box
+    native_denoms_w(&mut deps.storage).save(&vec!["uscrt".to_string()])?;
+    native_wrappers_w(&mut deps.storage).save(b"uscrt", &msg.sscrt)?;
+
     viewing_key_w(&mut deps.storage).save(&msg.viewing_key)?;
     self_address_w(&mut deps.storage).save(&env.contract.address)?;
-    asset_list_w(&mut deps.storage).save(&Vec::new())?;
+    asset_count_w(&mut deps.storage).save(&0)?;
+    total_outflow_proposals_w(&mut deps.storage).save(&Uint128::zero())?;
 
     //init last refresh with epoch 0 so first refresh always goes
     let timestamp = 0;
@@ -35,10 +41,15 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
 
     last_allowance_refresh_w(&mut deps.storage).save(&datetime.to_rfc3339())?;
 
+    let mut messages = vec![];
+    if let Some(hook) = msg.upgrade {
+        messages.push(hook.register_msg(&env)?);
+    }
+
     debug_print!("Contract was initialized by {}", env.message.sender);
 
     Ok(InitResponse {
-        messages: vec![],
+        messages,
         log: vec![],
     })
 }
@@ -63,6 +74,12 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
         HandleMsg::RegisterAllocation { asset, allocation } => {
             handle::register_allocation(deps, &env, asset, allocation)
         }
+        HandleMsg::UpdateAllocation { asset, allocation } => {
+            handle::update_allocation(deps, &env, asset, allocation)
+        }
+        HandleMsg::RemoveAllocation { asset, address } => {
+            handle::remove_allocation(deps, &env, asset, address)
+        }
         HandleMsg::RefreshAllowance {} => handle::refresh_allowance(deps, &env),
         HandleMsg::OneTimeAllowance {
             asset,
@@ -70,6 +87,31 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             amount,
             expiration,
         } => handle::one_time_allowance(deps, &env, asset, spender, amount, expiration),
+        HandleMsg::Transfer {
+            asset,
+            recipient,
+            amount,
+        } => handle::transfer(deps, &env, asset, recipient, amount),
+        HandleMsg::SetMultisig {
+            signers,
+            threshold,
+            expiry,
+        } => handle::set_multisig(deps, &env, signers, threshold, expiry),
+        HandleMsg::ProposeOutflow { action } => handle::propose_outflow(deps, &env, action),
+        HandleMsg::ApproveOutflow { proposal_id } => {
+            handle::approve_outflow(deps, &env, proposal_id)
+        }
+        HandleMsg::ExecuteOutflow { proposal_id } => {
+            handle::execute_outflow(deps, &env, proposal_id)
+        }
+        HandleMsg::RegisterWrapper { denom, contract } => {
+            handle::register_wrapper(deps, &env, denom, contract)
+        }
+        HandleMsg::Wrap { denom, amount } => handle::wrap(deps, &env, denom, amount),
+        HandleMsg::Unwrap { denom, amount } => handle::unwrap(deps, &env, denom, amount),
+        HandleMsg::ReceiveNative { msg } => handle::receive_native(deps, &env, msg),
+        HandleMsg::Unwind { asset, allocation } => handle::unwind(deps, &env, asset, allocation),
+        HandleMsg::MigrateStorage {} => handle::migrate_storage(deps, &env),
         /*
           HandleMsg::Rebalance {
           } => handle::rebalance(deps, &env),
@@ -83,12 +125,51 @@ pub fn query<S: Storage, A: Api, Q: Querier>(
 ) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query::config(deps)?),
-        QueryMsg::Assets {} => to_binary(&query::assets(deps)?),
-        QueryMsg::Allocations { asset } => to_binary(&query::allocations(deps, asset)?),
-        QueryMsg::Balance { asset } => to_binary(&query::balance(&deps, &asset)?),
-        QueryMsg::Allowances { asset, spender } => {
-            to_binary(&query::allowances(&deps, &asset, &spender)?)
+        QueryMsg::Assets { start_after, limit } => {
+            to_binary(&query::assets(deps, start_after, limit)?)
         }
+        QueryMsg::Allocations {
+            asset,
+            start_after,
+            limit,
+        } => to_binary(&query::allocations(deps, asset, start_after, limit)?),
+        QueryMsg::Balance { asset } => to_binary(&query::balance(&deps, &asset)?),
+        QueryMsg::BalanceSheet {} => to_binary(&query::balance_sheet(&deps)?),
+        QueryMsg::Allowances {
+            asset,
+            spender,
+            start_after,
+            limit,
+        } => to_binary(&query::allowances(
+            &deps,
+            &asset,
+            spender,
+            start_after,
+            limit,
+        )?),
         QueryMsg::LastAllowanceRefresh {} => to_binary(&query::last_allowance_refresh(&deps)?),
+        QueryMsg::Multisig {} => to_binary(&query::multisig(&deps)?),
+        QueryMsg::OutflowProposal { proposal_id } => {
+            to_binary(&query::outflow_proposal(&deps, proposal_id)?)
+        }
+        QueryMsg::NativeWrappers {} => to_binary(&query::native_wrappers(&deps)?),
+        QueryMsg::Income { asset } => to_binary(&query::income(&deps, asset)?),
+        QueryMsg::Unwinds { asset } => to_binary(&query::unwinds(&deps, asset)?),
+        QueryMsg::Ledger {
+            asset,
+            category,
+            start,
+            end,
+            start_after,
+            limit,
+        } => to_binary(&query::ledger(
+            &deps,
+            asset,
+            category,
+            start,
+            end,
+            start_after,
+            limit,
+        )?),
     }
 }
@@ -1,28 +1,35 @@
 use cosmwasm_std;
 use cosmwasm_std::{
-    from_binary, to_binary, Api, Binary, CosmosMsg, Env, Extern, HandleResponse, HumanAddr,
-    Querier, StdError, StdResult, Storage, Uint128,
+    from_binary, to_binary, Api, Binary, Coin, CosmosMsg, Env, Extern, HandleResponse, HumanAddr,
+    Querier, StdError, StdResult, Storage, Uint128, WasmMsg,
 };
 use secret_toolkit;
 use secret_toolkit::snip20::{
-    allowance_query, decrease_allowance_msg, increase_allowance_msg, register_receive_msg,
-    send_msg, set_viewing_key_msg,
+    allowance_query, decrease_allowance_msg, increase_allowance_msg, redeem_msg,
+    register_receive_msg, send_msg, set_viewing_key_msg,
 };
-use secret_toolkit::utils::Query;
+use secret_toolkit::utils::{HandleCallback, Query};
 
 use shade_protocol::{
-    snip20,
-    snip20::fetch_snip20,
-    treasury::{Allocation, Config, Flag, HandleAnswer, QueryAnswer},
+    scrt_staking, snip20,
+    snip20::{fetch_snip20, Snip20Asset},
+    treasury::{
+        Allocation, AllowanceStatus, Config, Cycle, HandleAnswer, LedgerCategory, LedgerEntry,
+        Multisig, OutflowAction, OutflowProposal, TreasuryReceiveMsg, UnwindStatus,
+    },
     utils::{asset::Contract, generic_response::ResponseStatus},
 };
 
-use crate::{
-    query,
-    state::{
-        allocations_r, allocations_w, asset_list_r, asset_list_w, assets_r, assets_w, config_r,
-        config_w, last_allowance_refresh_r, last_allowance_refresh_w, viewing_key_r,
-    },
+use crate::query::unwind_outstanding;
+use crate::state::{
+    allocation_list, allocation_position, allocations_r, allocations_w, allowance_status_r,
+    allowance_status_w, asset_count_r, asset_count_w, asset_list, asset_list_r, asset_list_w,
+    assets_r, assets_w, config_r, config_w, deployed_w, income_sources_w, income_w,
+    last_allowance_refresh_w, ledger_count_r, ledger_count_w, ledger_index_count_r,
+    ledger_index_count_w, ledger_index_w, ledger_w, multisig_r, multisig_w, native_denoms_w,
+    native_wrappers_r, native_wrappers_w, outflow_proposals_r, outflow_proposals_w,
+    push_allocation, push_asset, remove_allocation_at, repayment_sources_w, repayments_w,
+    set_allocation, total_outflow_proposals_w, unwinding_r, unwinding_w, unwinds_w, viewing_key_r,
 };
 use chrono::prelude::*;
 
@@ -30,97 +37,120 @@ pub fn receive<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
     _sender: HumanAddr,
-    _from: HumanAddr,
+    from: HumanAddr,
     amount: Uint128,
     msg: Option<Binary>,
 ) -> StdResult<HandleResponse> {
     let asset = assets_r(&deps.storage).load(env.message.sender.to_string().as_bytes())?;
     //debug_print!("Treasured {} u{}", amount, asset.token_info.symbol);
-    // skip the rest if the send the "unallocated" flag
-    if let Some(f) = msg {
-        let flag: Flag = from_binary(&f)?;
-        if flag.flag == "unallocated" {
-            return Ok(HandleResponse {
-                messages: vec![],
-                log: vec![],
-                data: Some(to_binary(&HandleAnswer::Receive {
-                    status: ResponseStatus::Success,
-                })?),
-            });
-        }
+
+    let receive_msg = match msg {
+        Some(m) => Some(from_binary::<TreasuryReceiveMsg>(&m).map_err(|_| {
+            StdError::generic_err("Invalid receive msg, expected a TreasuryReceiveMsg")
+        })?),
+        None => None,
     };
 
-    let mut messages = vec![];
+    // Unwound allocations can still repay but no longer receive funds
+    let alloc_list = allocation_list(&deps.storage, &asset.contract.address)?;
+    let active = active_allocations(&deps.storage, &asset.contract.address)?;
 
-    allocations_w(&mut deps.storage).update(
-        asset.contract.address.to_string().as_bytes(),
-        |allocs| {
-            let mut alloc_list = allocs.unwrap_or(vec![]);
-
-            for alloc in &mut alloc_list {
-                match alloc {
-                    Allocation::Reserves { allocation: _ } => {}
-                    Allocation::Allowance {
-                        address: _,
-                        amount: _,
-                    } => {}
-
-                    Allocation::Rewards {
-                        allocation,
-                        contract,
-                    } => {
-                        messages.push(send_msg(
-                            contract.address.clone(),
-                            amount.multiply_ratio(*allocation, 10u128.pow(18)),
-                            None,
-                            None,
-                            None,
-                            1,
-                            asset.contract.code_hash.clone(),
-                            asset.contract.address.clone(),
-                        )?);
-                    }
-                    Allocation::Staking {
-                        allocation,
-                        contract,
-                    } => {
-                        //debug_print!("Staking {}/{} u{} to {}", allocation, amount, asset.token_info.symbol, contract.address);
-
-                        messages.push(send_msg(
-                            contract.address.clone(),
-                            amount.multiply_ratio(*allocation, 10u128.pow(18)),
-                            None,
-                            None,
-                            None,
-                            1,
-                            asset.contract.code_hash.clone(),
-                            asset.contract.address.clone(),
-                        )?);
-                    }
+    let mut messages = vec![];
+    let asset_address = asset.contract.address.clone();
 
-                    Allocation::Application {
-                        contract: _,
-                        allocation: _,
-                        token: _,
-                    } => {
-                        //debug_print!("Applications Unsupported {}/{} u{} to {}", allocation, amount, asset.token_info.symbol, contract.address);
-                        //TODO: implement
-                    }
-                    Allocation::Pool {
-                        contract: _,
-                        allocation: _,
-                        secondary_asset: _,
-                        token: _,
-                    } => {
-                        //debug_print!("Pools Unsupported {}/{} u{} to {}", allocation, amount, asset.token_info.symbol, contract.address);
-                        //TODO: implement
-                    }
-                };
+    match receive_msg {
+        // held until rebalancing
+        Some(TreasuryReceiveMsg::Unallocated {}) => {
+            record_entry(
+                &mut deps.storage,
+                &env,
+                &asset_address,
+                LedgerCategory::Deposit,
+                from,
+                amount,
+                None,
+            )?;
+        }
+        Some(TreasuryReceiveMsg::AllocateTo { address }) => {
+            let alloc = match alloc_list
+                .iter()
+                .find(|alloc| allocation_address(alloc) == address)
+            {
+                Some(alloc) => alloc,
+                None => {
+                    return Err(StdError::not_found("Allocation"));
+                }
+            };
+            if is_unwinding(&deps.storage, &asset_address, alloc)? {
+                return Err(StdError::generic_err("Allocation is unwinding"));
             }
 
-            Ok(alloc_list)
-        },
-    )?;
+            record_entry(
+                &mut deps.storage,
+                &env,
+                &asset_address,
+                LedgerCategory::Deposit,
+                from,
+                amount,
+                None,
+            )?;
+            messages.append(&mut allocate(
+                &mut deps.storage,
+                &env,
+                &asset,
+                &[alloc.clone()],
+                amount,
+                false,
+            )?);
+        }
+        Some(TreasuryReceiveMsg::Repayment { address }) => {
+            record_repayment(
+                &mut deps.storage,
+                &env,
+                &asset_address,
+                &alloc_list,
+                address,
+                amount,
+            )?;
+        }
+        Some(TreasuryReceiveMsg::FeeIncome { source }) => {
+            record_income(
+                &mut deps.storage,
+                &env,
+                &asset_address,
+                from,
+                source,
+                amount,
+            )?;
+            messages.append(&mut allocate(
+                &mut deps.storage,
+                &env,
+                &asset,
+                &active,
+                amount,
+                true,
+            )?);
+        }
+        None => {
+            record_entry(
+                &mut deps.storage,
+                &env,
+                &asset_address,
+                LedgerCategory::Deposit,
+                from,
+                amount,
+                None,
+            )?;
+            messages.append(&mut allocate(
+                &mut deps.storage,
+                &env,
+                &asset,
+                &active,
+                amount,
+                true,
+            )?);
+        }
+    }
 
     Ok(HandleResponse {
         messages,
@@ -131,6 +161,289 @@ pub fn receive<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+// Sends out each allocation's portion, or all of amount when not split
+fn allocate<S: Storage>(
+    storage: &mut S,
+    env: &Env,
+    asset: &Snip20Asset,
+    alloc_list: &[Allocation],
+    amount: Uint128,
+    split: bool,
+) -> StdResult<Vec<CosmosMsg>> {
+    let mut messages = vec![];
+
+    for alloc in alloc_list {
+        let alloc_amount = match split {
+            true => amount.multiply_ratio(allocation_portion(alloc), 10u128.pow(18)),
+            false => amount,
+        };
+
+        if let Some(msg) = allocation_msg(asset, alloc, alloc_amount)? {
+            if let Some(address) = allocation_address(alloc) {
+                record_entry(
+                    storage,
+                    env,
+                    &asset.contract.address,
+                    LedgerCategory::Allocation,
+                    address,
+                    alloc_amount,
+                    None,
+                )?;
+            }
+
+            // Staking positions are queried from the staking contract instead
+            match alloc {
+                Allocation::Rewards { contract, .. }
+                | Allocation::Application { contract, .. }
+                | Allocation::Pool { contract, .. } => {
+                    deployed_w(storage, &asset.contract.address)
+                        .update(contract.address.to_string().as_bytes(), |total| {
+                            Ok(total.unwrap_or_default() + alloc_amount)
+                        })?;
+                }
+                _ => {}
+            }
+            messages.push(msg);
+        }
+    }
+
+    Ok(messages)
+}
+
+// Sends amount to an allocation, None when it stays in treasury
+fn allocation_msg(
+    asset: &Snip20Asset,
+    alloc: &Allocation,
+    amount: Uint128,
+) -> StdResult<Option<CosmosMsg>> {
+    match alloc {
+        Allocation::Reserves { .. } => Ok(None),
+        Allocation::Allowance { .. } => Ok(None),
+
+        Allocation::Rewards { contract, .. } | Allocation::Staking { contract, .. } => {
+            //debug_print!("Staking {} u{} to {}", amount, asset.token_info.symbol, contract.address);
+            Ok(Some(send_msg(
+                contract.address.clone(),
+                amount,
+                None,
+                None,
+                None,
+                1,
+                asset.contract.code_hash.clone(),
+                asset.contract.address.clone(),
+            )?))
+        }
+
+        Allocation::Application { .. } => {
+            //debug_print!("Applications Unsupported {} u{} to {}", amount, asset.token_info.symbol, contract.address);
+            //TODO: implement
+            Ok(None)
+        }
+        Allocation::Pool { .. } => {
+            //debug_print!("Pools Unsupported {} u{} to {}", amount, asset.token_info.symbol, contract.address);
+            //TODO: implement
+            Ok(None)
+        }
+    }
+}
+
+// Accounts amount to source, split by source in the Income query
+fn record_income<S: Storage>(
+    storage: &mut S,
+    env: &Env,
+    asset: &HumanAddr,
+    from: HumanAddr,
+    source: String,
+    amount: Uint128,
+) -> StdResult<()> {
+    if source.is_empty() {
+        return Err(StdError::generic_err("Fee income requires a source"));
+    }
+
+    income_sources_w(storage).update(asset.to_string().as_bytes(), |sources| {
+        let mut sources = sources.unwrap_or_default();
+        if !sources.contains(&source) {
+            sources.push(source.clone());
+        }
+        Ok(sources)
+    })?;
+
+    income_w(storage, asset).update(source.as_bytes(), |total| {
+        Ok(total.unwrap_or_default() + amount)
+    })?;
+
+    record_entry(
+        storage,
+        env,
+        asset,
+        LedgerCategory::FeeIncome,
+        from,
+        amount,
+        Some(source),
+    )
+}
+
+// Funds returned by one of the asset's allocations
+fn record_repayment<S: Storage>(
+    storage: &mut S,
+    env: &Env,
+    asset: &HumanAddr,
+    alloc_list: &[Allocation],
+    address: HumanAddr,
+    amount: Uint128,
+) -> StdResult<()> {
+    if !alloc_list
+        .iter()
+        .any(|alloc| allocation_address(alloc) == Some(address.clone()))
+    {
+        return Err(StdError::not_found("Allocation"));
+    }
+
+    repayment_sources_w(storage).update(asset.to_string().as_bytes(), |sources| {
+        let mut sources = sources.unwrap_or_default();
+        if !sources.contains(&address) {
+            sources.push(address.clone());
+        }
+        Ok(sources)
+    })?;
+
+    repayments_w(storage, asset).update(address.to_string().as_bytes(), |total| {
+        Ok(total.unwrap_or_default() + amount)
+    })?;
+
+    // Repaid funds are no longer deployed
+    deployed_w(storage, asset).update(address.to_string().as_bytes(), |total| {
+        Ok((total.unwrap_or_default() - amount).unwrap_or_else(|_| Uint128::zero()))
+    })?;
+
+    record_entry(
+        storage,
+        env,
+        asset,
+        LedgerCategory::Repayment,
+        address,
+        amount,
+        None,
+    )
+}
+
+/* Native coins are accounted to the asset wrapping them,
+ * they stay unwrapped until Wrap so nothing is allocated
+ */
+pub fn receive_native<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    msg: Option<TreasuryReceiveMsg>,
+) -> StdResult<HandleResponse> {
+    if env.message.sent_funds.is_empty() {
+        return Err(StdError::generic_err("No funds were sent"));
+    }
+
+    for coin in &env.message.sent_funds {
+        let asset = match native_wrappers_r(&deps.storage).may_load(coin.denom.as_bytes())? {
+            Some(wrapper) => wrapper.address,
+            None => {
+                return Err(StdError::generic_err(format!(
+                    "No wrapper for {}",
+                    coin.denom
+                )));
+            }
+        };
+
+        if assets_r(&deps.storage)
+            .may_load(asset.to_string().as_bytes())?
+            .is_none()
+        {
+            return Err(StdError::generic_err("Unregistered asset"));
+        }
+
+        match msg.clone() {
+            Some(TreasuryReceiveMsg::Repayment { address }) => {
+                let alloc_list = allocation_list(&deps.storage, &asset)?;
+                record_repayment(
+                    &mut deps.storage,
+                    env,
+                    &asset,
+                    &alloc_list,
+                    address,
+                    coin.amount,
+                )?;
+            }
+            Some(TreasuryReceiveMsg::FeeIncome { source }) => {
+                record_income(
+                    &mut deps.storage,
+                    env,
+                    &asset,
+                    env.message.sender.clone(),
+                    source,
+                    coin.amount,
+                )?;
+            }
+            Some(TreasuryReceiveMsg::AllocateTo { .. }) => {
+                return Err(StdError::generic_err(
+                    "Native funds must be wrapped before allocating",
+                ));
+            }
+            Some(TreasuryReceiveMsg::Unallocated {}) | None => {
+                record_entry(
+                    &mut deps.storage,
+                    env,
+                    &asset,
+                    LedgerCategory::Deposit,
+                    env.message.sender.clone(),
+                    coin.amount,
+                    None,
+                )?;
+            }
+        }
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::ReceiveNative {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+/* Appends to the asset's ledger and the category index
+ * Entries are never modified or removed
+ */
+pub(crate) fn record_entry<S: Storage>(
+    storage: &mut S,
+    env: &Env,
+    asset: &HumanAddr,
+    category: LedgerCategory,
+    counterparty: HumanAddr,
+    amount: Uint128,
+    memo: Option<String>,
+) -> StdResult<()> {
+    let id = ledger_count_r(storage)
+        .may_load(asset.to_string().as_bytes())?
+        .unwrap_or(0);
+    ledger_count_w(storage).save(asset.to_string().as_bytes(), &(id + 1))?;
+
+    let position = ledger_index_count_r(storage, asset)
+        .may_load(format!("{:?}", category).as_bytes())?
+        .unwrap_or(0);
+    ledger_index_count_w(storage, asset)
+        .save(format!("{:?}", category).as_bytes(), &(position + 1))?;
+    ledger_index_w(storage, asset, &category).save(&position.to_be_bytes(), &id)?;
+
+    ledger_w(storage, asset).save(
+        &id.to_be_bytes(),
+        &LedgerEntry {
+            id,
+            category,
+            counterparty,
+            amount,
+            time: env.block.time,
+            memo,
+        },
+    )
+}
+
 pub fn try_update_config<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
@@ -160,28 +473,12 @@ pub fn refresh_allowance<S: Storage, A: Api, Q: Querier>(
     let naive = NaiveDateTime::from_timestamp(env.block.time as i64, 0);
     let now: DateTime<Utc> = DateTime::from_utc(naive, Utc);
 
-    // Parse previous refresh datetime
-    match DateTime::parse_from_rfc3339(&last_allowance_refresh_r(&mut deps.storage).load()?) {
-        Ok(parsed) => {
-            // Parse into UTC
-            let last_refresh: DateTime<Utc> = parsed.with_timezone(&Utc);
-
-            // Fail if we have already refreshed this month
-            if now.year() <= last_refresh.year() && now.month() <= last_refresh.month() {
-                return Err(StdError::generic_err(format!(
-                    "Last refresh too recent: {}",
-                    last_refresh.to_rfc3339()
-                )));
-            }
-        }
-
-        Err(e) => return Err(StdError::generic_err("Failed to parse previous datetime")),
-    };
+    let messages = do_allowance_refresh(deps, env)?;
 
     last_allowance_refresh_w(&mut deps.storage).save(&now.to_rfc3339())?;
 
     Ok(HandleResponse {
-        messages: do_allowance_refresh(&deps, &env)?,
+        messages,
         log: vec![],
         data: Some(to_binary(&HandleAnswer::RefreshAllowance {
             status: ResponseStatus::Success,
@@ -189,57 +486,182 @@ pub fn refresh_allowance<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+/* Earliest time an allowance can be refreshed after its last refresh
+ * None when it can be refreshed at any time
+ */
+pub(crate) fn next_refresh(cycle: &Cycle, last_refresh: u64) -> Option<u64> {
+    match cycle {
+        Cycle::Daily => Some(last_refresh + 86_400),
+        Cycle::Weekly => Some(last_refresh + 604_800),
+        Cycle::Monthly => {
+            let last = NaiveDateTime::from_timestamp(last_refresh as i64, 0);
+            let (year, month) = match last.month() {
+                12 => (last.year() + 1, 1),
+                month => (last.year(), month + 1),
+            };
+            Some(
+                NaiveDate::from_ymd(year, month, 1)
+                    .and_hms(0, 0, 0)
+                    .timestamp() as u64,
+            )
+        }
+        Cycle::Epoch { seconds } => Some(last_refresh + seconds),
+        Cycle::Vesting { .. } => None,
+    }
+}
+
+/* Allowance to give after a refresh at time now, given the total spent so far
+ */
+pub(crate) fn allowance_target(
+    amount: Uint128,
+    cycle: &Cycle,
+    cap: Option<Uint128>,
+    spent: Uint128,
+    now: u64,
+) -> Uint128 {
+    let target = match cycle {
+        Cycle::Vesting { start, end } => {
+            let unlocked = if now <= *start {
+                Uint128::zero()
+            } else if now >= *end {
+                amount
+            } else {
+                amount.multiply_ratio(now - start, end - start)
+            };
+            (unlocked - spent).unwrap_or_else(|_| Uint128::zero())
+        }
+        _ => amount,
+    };
+
+    match cap {
+        Some(cap) => target.min((cap - spent).unwrap_or_else(|_| Uint128::zero())),
+        None => target,
+    }
+}
+
+/* Brings status up to date with the spender's current allowance,
+ * one time allowances are drawn first and whatever is unspent is gone once expired
+ * Returns the amount spent since the last update
+ */
+pub(crate) fn settle_allowance(
+    status: &mut AllowanceStatus,
+    cur_allowance: Uint128,
+    expired: bool,
+) -> Uint128 {
+    let drawn =
+        (status.granted + status.one_time - cur_allowance).unwrap_or_else(|_| Uint128::zero());
+
+    let one_time_drawn = drawn.min(status.one_time);
+    let cycle_drawn = (drawn - one_time_drawn).unwrap_or_else(|_| Uint128::zero());
+
+    status.one_time = (status.one_time - one_time_drawn).unwrap_or_else(|_| Uint128::zero());
+    status.granted = (status.granted - cycle_drawn).unwrap_or_else(|_| Uint128::zero());
+    status.spent += cycle_drawn;
+
+    if expired {
+        status.granted = Uint128::zero();
+        status.one_time = Uint128::zero();
+    }
+
+    drawn
+}
+
+pub(crate) fn is_expired(expiration: Option<u64>, now: u64) -> bool {
+    expiration.map_or(false, |expiration| now >= expiration)
+}
+
 /* Not exposed as a tx
  */
 pub fn do_allowance_refresh<S: Storage, A: Api, Q: Querier>(
-    deps: &Extern<S, A, Q>,
+    deps: &mut Extern<S, A, Q>,
     env: &Env,
 ) -> StdResult<Vec<CosmosMsg>> {
     let mut messages = vec![];
 
     let key = viewing_key_r(&deps.storage).load()?;
 
-    for asset in asset_list_r(&deps.storage).load()? {
-        for alloc in allocations_r(&deps.storage).load(&asset.to_string().as_bytes())? {
-            match alloc {
-                Allocation::Allowance { address, amount } => {
-                    let full_asset = assets_r(&deps.storage).load(asset.to_string().as_bytes())?;
-                    // Determine current allowance
-                    let cur_allowance = allowance_query(
-                        &deps.querier,
-                        env.contract.address.clone(),
+    for asset in asset_list(&deps.storage)? {
+        let full_asset = assets_r(&deps.storage).load(asset.to_string().as_bytes())?;
+
+        for alloc in active_allocations(&deps.storage, &asset)? {
+            if let Allocation::Allowance {
+                address,
+                amount,
+                cycle,
+                cap,
+            } = alloc
+            {
+                let mut status = allowance_status_r(&deps.storage, &asset)
+                    .may_load(address.to_string().as_bytes())?
+                    .unwrap_or_default();
+
+                // Skip allowances that aren't due yet
+                if let Some(next) = next_refresh(&cycle, status.last_refresh.unwrap_or(0)) {
+                    if env.block.time < next {
+                        continue;
+                    }
+                }
+
+                let cur_allowance = allowance_query(
+                    &deps.querier,
+                    env.contract.address.clone(),
+                    address.clone(),
+                    key.clone(),
+                    1,
+                    full_asset.contract.code_hash.clone(),
+                    full_asset.contract.address.clone(),
+                )?;
+                let expired = is_expired(cur_allowance.expiration, env.block.time);
+
+                let newly_spent = settle_allowance(&mut status, cur_allowance.allowance, expired);
+                if !newly_spent.is_zero() {
+                    record_entry(
+                        &mut deps.storage,
+                        env,
+                        &asset,
+                        LedgerCategory::AllowanceSpend,
                         address.clone(),
-                        key.clone(),
+                        newly_spent,
+                        None,
+                    )?;
+                }
+
+                // The token drops an expired allowance on the next increase
+                let available = match expired {
+                    true => Uint128::zero(),
+                    false => cur_allowance.allowance,
+                };
+
+                // Outstanding one time allowances are kept on top of the cycle's
+                let target = allowance_target(amount, &cycle, cap, status.spent, env.block.time);
+                let desired = target + status.one_time;
+
+                if desired > available {
+                    messages.push(increase_allowance_msg(
+                        address.clone(),
+                        (desired - available)?,
+                        None,
+                        None,
                         1,
                         full_asset.contract.code_hash.clone(),
                         full_asset.contract.address.clone(),
-                    )?;
-
-                    if amount > cur_allowance.allowance {
-                        // Increase to monthly allowance amount
-                        messages.push(increase_allowance_msg(
-                            address.clone(),
-                            (amount - cur_allowance.allowance)?,
-                            None,
-                            None,
-                            1,
-                            full_asset.contract.code_hash.clone(),
-                            full_asset.contract.address.clone(),
-                        )?);
-                    } else if amount < cur_allowance.allowance {
-                        // Decrease to monthly allowance
-                        messages.push(decrease_allowance_msg(
-                            address.clone(),
-                            (cur_allowance.allowance - amount)?,
-                            None,
-                            None,
-                            1,
-                            full_asset.contract.code_hash.clone(),
-                            full_asset.contract.address.clone(),
-                        )?);
-                    }
+                    )?);
+                } else if desired < available {
+                    messages.push(decrease_allowance_msg(
+                        address.clone(),
+                        (available - desired)?,
+                        None,
+                        None,
+                        1,
+                        full_asset.contract.code_hash.clone(),
+                        full_asset.contract.address.clone(),
+                    )?);
                 }
-                _ => {}
+
+                status.granted = target;
+                status.last_refresh = Some(env.block.time);
+                allowance_status_w(&mut deps.storage, &asset)
+                    .save(address.to_string().as_bytes(), &status)?;
             }
         }
     }
@@ -255,310 +677,979 @@ pub fn one_time_allowance<S: Storage, A: Api, Q: Querier>(
     amount: Uint128,
     expiration: Option<u64>,
 ) -> StdResult<HandleResponse> {
-    let cur_config = config_r(&deps.storage).load()?;
-
-    if env.message.sender != cur_config.admin {
-        return Err(StdError::unauthorized());
-    }
-
-    let mut messages = vec![];
-
-    if let Some(full_asset) = assets_r(&deps.storage).may_load(&asset.to_string().as_bytes())? {
-        messages.push(increase_allowance_msg(
-            spender,
-            amount,
-            expiration,
-            None,
-            1,
-            full_asset.contract.code_hash.clone(),
-            full_asset.contract.address.clone(),
-        )?);
-
-        return Ok(HandleResponse {
-            messages,
-            log: vec![],
-            data: Some(to_binary(&HandleAnswer::OneTimeAllowance {
-                status: ResponseStatus::Success,
-            })?),
-        });
-    }
+    check_outflow_auth(&deps.storage, env)?;
 
-    Err(StdError::generic_err(format!("Unknown Asset: {}", asset)))
+    Ok(HandleResponse {
+        messages: do_one_time_allowance(deps, env, asset, spender, amount, expiration)?,
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::OneTimeAllowance {
+            status: ResponseStatus::Success,
+        })?),
+    })
 }
 
-pub fn try_register_asset<S: Storage, A: Api, Q: Querier>(
+fn do_one_time_allowance<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: &Env,
-    contract: &Contract,
-    reserves: Option<Uint128>,
-) -> StdResult<HandleResponse> {
-    let config = config_r(&deps.storage).load()?;
-    if env.message.sender != config.admin {
-        return Err(StdError::unauthorized());
+    asset: HumanAddr,
+    spender: HumanAddr,
+    amount: Uint128,
+    expiration: Option<u64>,
+) -> StdResult<Vec<CosmosMsg>> {
+    if let Some(expiration) = expiration {
+        if expiration <= env.block.time {
+            return Err(StdError::generic_err("Expiration has already passed"));
+        }
     }
 
-    let mut messages = vec![];
-
-    asset_list_w(&mut deps.storage).update(|mut list| {
-        list.push(contract.address.clone());
-        Ok(list)
-    })?;
-    assets_w(&mut deps.storage).save(
-        contract.address.to_string().as_bytes(),
-        &snip20::fetch_snip20(&contract, &deps.querier)?,
-    )?;
-
-    let allocs = match reserves {
-        Some(r) => {
-            vec![Allocation::Reserves { allocation: r }]
-        }
+    let full_asset = match assets_r(&deps.storage).may_load(&asset.to_string().as_bytes())? {
+        Some(a) => a,
         None => {
-            vec![]
+            return Err(StdError::generic_err(format!("Unknown Asset: {}", asset)));
         }
     };
 
-    allocations_w(&mut deps.storage).save(contract.address.to_string().as_bytes(), &allocs)?;
+    let cur_allowance = allowance_query(
+        &deps.querier,
+        env.contract.address.clone(),
+        spender.clone(),
+        viewing_key_r(&deps.storage).load()?,
+        1,
+        full_asset.contract.code_hash.clone(),
+        full_asset.contract.address.clone(),
+    )?;
 
-    // Register contract in asset
-    messages.push(register_receive_msg(
-        env.contract_code_hash.clone(),
-        None,
-        256,
-        contract.code_hash.clone(),
-        contract.address.clone(),
-    )?);
+    let mut status = allowance_status_r(&deps.storage, &asset)
+        .may_load(spender.to_string().as_bytes())?
+        .unwrap_or_default();
 
-    // Set viewing key
-    messages.push(set_viewing_key_msg(
-        viewing_key_r(&deps.storage).load()?,
+    let newly_spent = settle_allowance(
+        &mut status,
+        cur_allowance.allowance,
+        is_expired(cur_allowance.expiration, env.block.time),
+    );
+    if !newly_spent.is_zero() {
+        record_entry(
+            &mut deps.storage,
+            env,
+            &asset,
+            LedgerCategory::AllowanceSpend,
+            spender.clone(),
+            newly_spent,
+            None,
+        )?;
+    }
+
+    // Kept apart from the cycle so refreshes leave it in place
+    status.one_time += amount;
+    allowance_status_w(&mut deps.storage, &asset).save(spender.to_string().as_bytes(), &status)?;
+
+    Ok(vec![increase_allowance_msg(
+        spender,
+        amount,
+        expiration,
         None,
         1,
-        contract.code_hash.clone(),
-        contract.address.clone(),
-    )?);
+        full_asset.contract.code_hash.clone(),
+        full_asset.contract.address,
+    )?])
+}
+
+pub fn transfer<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    asset: HumanAddr,
+    recipient: HumanAddr,
+    amount: Uint128,
+) -> StdResult<HandleResponse> {
+    check_outflow_auth(&deps.storage, env)?;
 
     Ok(HandleResponse {
-        messages,
+        messages: do_transfer(deps, env, asset, recipient, amount)?,
         log: vec![],
-        data: Some(to_binary(&HandleAnswer::RegisterAsset {
+        data: Some(to_binary(&HandleAnswer::Transfer {
             status: ResponseStatus::Success,
         })?),
     })
 }
 
-pub fn register_allocation<S: Storage, A: Api, Q: Querier>(
+fn do_transfer<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: &Env,
     asset: HumanAddr,
-    alloc: Allocation,
-) -> StdResult<HandleResponse> {
-    let config = config_r(&deps.storage).load()?;
-
-    /* ADMIN ONLY */
-    if env.message.sender != config.admin {
-        return Err(StdError::unauthorized());
-    }
-
-    let full_asset = match assets_r(&deps.storage).may_load(asset.to_string().as_bytes())? {
+    recipient: HumanAddr,
+    amount: Uint128,
+) -> StdResult<Vec<CosmosMsg>> {
+    let full_asset = match assets_r(&deps.storage).may_load(&asset.to_string().as_bytes())? {
         Some(a) => a,
         None => {
-            return Err(StdError::generic_err("Unregistered asset"));
+            return Err(StdError::generic_err(format!("Unknown Asset: {}", asset)));
         }
     };
 
-    let liquid_balance: Uint128 = match query::balance(&deps, &asset)? {
-        QueryAnswer::Balance { amount } => amount,
+    record_entry(
+        &mut deps.storage,
+        env,
+        &asset,
+        LedgerCategory::Transfer,
+        recipient.clone(),
+        amount,
+        None,
+    )?;
+
+    Ok(vec![send_msg(
+        recipient,
+        amount,
+        None,
+        None,
+        None,
+        1,
+        full_asset.contract.code_hash.clone(),
+        full_asset.contract.address,
+    )?])
+}
+
+/* Outflows need signer approval once a multisig is set,
+ * until then they are admin only
+ */
+fn check_outflow_auth<S: Storage>(storage: &S, env: &Env) -> StdResult<()> {
+    if let Some(multisig) = multisig_r(storage).may_load()? {
+        if !multisig.signers.is_empty() {
+            return Err(StdError::generic_err(
+                "Requires signer approval, use ProposeOutflow",
+            ));
+        }
+    }
+
+    if env.message.sender != config_r(storage).load()?.admin {
+        return Err(StdError::unauthorized());
+    }
+
+    Ok(())
+}
+
+fn signer_multisig<S: Storage>(storage: &S, env: &Env) -> StdResult<Multisig> {
+    let multisig = match multisig_r(storage).may_load()? {
+        Some(m) if !m.signers.is_empty() => m,
         _ => {
-            return Err(StdError::generic_err("Unexpected response for balance"));
+            return Err(StdError::generic_err("No signers configured"));
         }
     };
 
-    let alloc_portion = match &alloc {
-        Allocation::Reserves { allocation } => *allocation,
+    if !multisig.signers.contains(&env.message.sender) {
+        return Err(StdError::unauthorized());
+    }
 
-        // TODO: Needs to be accounted for elsewhere
-        Allocation::Allowance {
-            address: _,
-            amount: _,
-        } => Uint128::zero(),
-
-        Allocation::Rewards {
-            contract: _,
-            allocation,
-        } => *allocation,
-        Allocation::Staking {
-            contract: _,
-            allocation,
-        } => *allocation,
-        Allocation::Application {
-            contract: _,
-            allocation,
-            token: _,
-        } => *allocation,
-        Allocation::Pool {
-            contract: _,
-            allocation,
-            secondary_asset: _,
-            token: _,
-        } => *allocation,
-    };
+    Ok(multisig)
+}
+
+pub fn set_multisig<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    signers: Vec<HumanAddr>,
+    threshold: u16,
+    expiry: u64,
+) -> StdResult<HandleResponse> {
+    check_outflow_auth(&deps.storage, env)?;
 
-    let alloc_address = match &alloc {
-        Allocation::Allowance { address, amount: _ } => Some(address.clone()),
-        Allocation::Staking {
-            contract,
-            allocation: _,
-        } => Some(contract.address.clone()),
-        Allocation::Application {
-            contract,
-            allocation: _,
-            token: _,
-        } => Some(contract.address.clone()),
-        Allocation::Pool {
-            contract,
-            allocation: _,
-            secondary_asset: _,
-            token: _,
-        } => Some(contract.address.clone()),
-        _ => None,
+    do_set_multisig(&mut deps.storage, signers, threshold, expiry)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetMultisig {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+fn do_set_multisig<S: Storage>(
+    storage: &mut S,
+    signers: Vec<HumanAddr>,
+    threshold: u16,
+    expiry: u64,
+) -> StdResult<()> {
+    let mut unique = vec![];
+    for signer in signers {
+        if !unique.contains(&signer) {
+            unique.push(signer);
+        }
+    }
+
+    // No signers disables the multisig
+    if !unique.is_empty() && (threshold == 0 || threshold as usize > unique.len()) {
+        return Err(StdError::generic_err(
+            "Threshold must be between 1 and the number of signers",
+        ));
+    }
+
+    if !unique.is_empty() && expiry == 0 {
+        return Err(StdError::generic_err("Proposal expiry cannot be 0 seconds"));
+    }
+
+    multisig_w(storage).save(&Multisig {
+        signers: unique,
+        threshold,
+        expiry,
+    })
+}
+
+pub fn propose_outflow<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    action: OutflowAction,
+) -> StdResult<HandleResponse> {
+    let multisig = signer_multisig(&deps.storage, env)?;
+
+    let proposal_id = total_outflow_proposals_w(&mut deps.storage).update(|mut id| {
+        id += Uint128(1);
+        Ok(id)
+    })?;
+
+    // Proposing counts as an approval
+    outflow_proposals_w(&mut deps.storage).save(
+        proposal_id.to_string().as_bytes(),
+        &OutflowProposal {
+            id: proposal_id,
+            action,
+            proposer: env.message.sender.clone(),
+            approvals: vec![env.message.sender.clone()],
+            expiration: env.block.time + multisig.expiry,
+            executed: false,
+        },
+    )?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::ProposeOutflow {
+            status: ResponseStatus::Success,
+            proposal_id,
+        })?),
+    })
+}
+
+fn pending_outflow<S: Storage>(
+    storage: &S,
+    env: &Env,
+    proposal_id: Uint128,
+) -> StdResult<OutflowProposal> {
+    let proposal = outflow_proposals_r(storage)
+        .may_load(proposal_id.to_string().as_bytes())?
+        .ok_or_else(|| StdError::not_found("Outflow proposal"))?;
+
+    if proposal.executed {
+        return Err(StdError::generic_err("Proposal has already been executed"));
+    }
+    if env.block.time >= proposal.expiration {
+        return Err(StdError::generic_err("Proposal has expired"));
+    }
+
+    Ok(proposal)
+}
+
+pub fn approve_outflow<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    proposal_id: Uint128,
+) -> StdResult<HandleResponse> {
+    signer_multisig(&deps.storage, env)?;
+
+    let mut proposal = pending_outflow(&deps.storage, env, proposal_id)?;
+
+    if proposal.approvals.contains(&env.message.sender) {
+        return Err(StdError::generic_err("Already approved"));
+    }
+    proposal.approvals.push(env.message.sender.clone());
+
+    outflow_proposals_w(&mut deps.storage).save(proposal_id.to_string().as_bytes(), &proposal)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::ApproveOutflow {
+            status: ResponseStatus::Success,
+            approvals: proposal.approvals.len() as u16,
+        })?),
+    })
+}
+
+pub fn execute_outflow<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    proposal_id: Uint128,
+) -> StdResult<HandleResponse> {
+    let multisig = signer_multisig(&deps.storage, env)?;
+
+    let mut proposal = pending_outflow(&deps.storage, env, proposal_id)?;
+
+    // Only approvals from current signers count
+    let approvals = proposal
+        .approvals
+        .iter()
+        .filter(|approval| multisig.signers.contains(approval))
+        .count();
+    if approvals < multisig.threshold as usize {
+        return Err(StdError::generic_err(format!(
+            "Proposal has {} of {} required approvals",
+            approvals, multisig.threshold
+        )));
+    }
+
+    proposal.executed = true;
+    outflow_proposals_w(&mut deps.storage).save(proposal_id.to_string().as_bytes(), &proposal)?;
+
+    let messages = match proposal.action {
+        OutflowAction::OneTimeAllowance {
+            asset,
+            spender,
+            amount,
+            expiration,
+        } => do_one_time_allowance(deps, env, asset, spender, amount, expiration)?,
+        OutflowAction::RegisterAllocation { asset, allocation } => {
+            do_register_allocation(deps, asset, allocation)?;
+            vec![]
+        }
+        OutflowAction::UpdateAllocation { asset, allocation } => {
+            do_update_allocation(deps, asset, allocation)?;
+            vec![]
+        }
+        OutflowAction::Transfer {
+            asset,
+            recipient,
+            amount,
+        } => do_transfer(deps, env, asset, recipient, amount)?,
+        OutflowAction::SetMultisig {
+            signers,
+            threshold,
+            expiry,
+        } => {
+            do_set_multisig(&mut deps.storage, signers, threshold, expiry)?;
+            vec![]
+        }
     };
 
-    let mut allocated_portion = Uint128::zero();
+    Ok(HandleResponse {
+        messages,
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::ExecuteOutflow {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
 
-    allocations_w(&mut deps.storage).update(asset.to_string().as_bytes(), |apps| {
-        // Initialize list if it doesn't exist
-        let mut app_list = match apps {
-            None => {
-                vec![]
-            }
-            Some(a) => a,
-        };
+pub fn try_register_asset<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    contract: &Contract,
+    reserves: Option<Uint128>,
+) -> StdResult<HandleResponse> {
+    let config = config_r(&deps.storage).load()?;
+    if env.message.sender != config.admin {
+        return Err(StdError::unauthorized());
+    }
 
-        // Search for old instance of this contract
-        // A given contract can only have 1 allocation per asset
-        let mut existing_index = None;
-
-        for (i, app) in app_list.iter_mut().enumerate() {
-            if let Some(address) = match app {
-                Allocation::Rewards {
-                    contract,
-                    allocation: _,
-                } => Some(contract.address.clone()),
-                Allocation::Staking {
-                    contract,
-                    allocation: _,
-                } => Some(contract.address.clone()),
-                Allocation::Application {
-                    contract,
-                    allocation: _,
-                    token: _,
-                } => Some(contract.address.clone()),
-                Allocation::Pool {
-                    contract,
-                    allocation: _,
-                    secondary_asset: _,
-                    token: _,
-                } => Some(contract.address.clone()),
-                _ => None,
-            } {
-                match &alloc_address {
-                    Some(a) => {
-                        // Found the address, mark index and break from scan loop
-                        if address == *a {
-                            existing_index = Option::from(i);
-                            break;
-                        }
-                    }
-                    None => {}
-                }
-            } else {
-                /*
-                 * I think this is not needed, must have been a late night
-                match alloc_address {
-                    Some(_) => {}
-                    None => {
-                        existing_index = Option::from(i);
-                        break;
-                    }
-                }
-                */
-            }
+    if assets_r(&deps.storage)
+        .may_load(contract.address.to_string().as_bytes())?
+        .is_some()
+    {
+        return Err(StdError::generic_err("Asset already registered"));
+    }
+
+    let mut messages = vec![];
+
+    push_asset(&mut deps.storage, &contract.address)?;
+    assets_w(&mut deps.storage).save(
+        contract.address.to_string().as_bytes(),
+        &snip20::fetch_snip20(&contract, &deps.querier)?,
+    )?;
+
+    if let Some(r) = reserves {
+        push_allocation(
+            &mut deps.storage,
+            &contract.address,
+            &Allocation::Reserves { allocation: r },
+        )?;
+    }
+
+    // Register contract in asset
+    messages.push(register_receive_msg(
+        env.contract_code_hash.clone(),
+        None,
+        256,
+        contract.code_hash.clone(),
+        contract.address.clone(),
+    )?);
+
+    // Set viewing key
+    messages.push(set_viewing_key_msg(
+        viewing_key_r(&deps.storage).load()?,
+        None,
+        1,
+        contract.code_hash.clone(),
+        contract.address.clone(),
+    )?);
+
+    Ok(HandleResponse {
+        messages,
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::RegisterAsset {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+/* Portion of received funds claimed by an allocation, out of 10^18
+ * Allowances are a fixed amount and do not claim a portion
+ */
+pub(crate) fn allocation_portion(alloc: &Allocation) -> Uint128 {
+    match alloc {
+        Allocation::Reserves { allocation } => *allocation,
+        Allocation::Allowance { .. } => Uint128::zero(),
+        Allocation::Rewards { allocation, .. } => *allocation,
+        Allocation::Staking { allocation, .. } => *allocation,
+        Allocation::Application { allocation, .. } => *allocation,
+        Allocation::Pool { allocation, .. } => *allocation,
+    }
+}
+
+/* Address an allocation is identified by, None for reserves
+ * A given address can only have 1 allocation per asset
+ */
+pub(crate) fn allocation_address(alloc: &Allocation) -> Option<HumanAddr> {
+    match alloc {
+        Allocation::Reserves { .. } => None,
+        Allocation::Allowance { address, .. } => Some(address.clone()),
+        Allocation::Rewards { contract, .. } => Some(contract.address.clone()),
+        Allocation::Staking { contract, .. } => Some(contract.address.clone()),
+        Allocation::Application { contract, .. } => Some(contract.address.clone()),
+        Allocation::Pool { contract, .. } => Some(contract.address.clone()),
+    }
+}
+
+pub(crate) fn is_unwinding<S: Storage>(
+    storage: &S,
+    asset: &HumanAddr,
+    alloc: &Allocation,
+) -> StdResult<bool> {
+    Ok(match allocation_address(alloc) {
+        Some(address) => unwinding_r(storage, asset)
+            .may_load(address.to_string().as_bytes())?
+            .is_some(),
+        None => false,
+    })
+}
+
+// Allocations that receive funds and refreshes
+pub(crate) fn active_allocations<S: Storage>(
+    storage: &S,
+    asset: &HumanAddr,
+) -> StdResult<Vec<Allocation>> {
+    let mut active = vec![];
+    for alloc in allocation_list(storage, asset)? {
+        if !is_unwinding(storage, asset, &alloc)? {
+            active.push(alloc);
         }
+    }
+    Ok(active)
+}
 
-        // If an element was marked, remove it from the list
-        match existing_index {
-            Some(i) => {
-                app_list.remove(i);
-            }
-            _ => {}
+pub(crate) fn allocated_portion(allocations: &[Allocation]) -> Uint128 {
+    let mut total = Uint128::zero();
+    for alloc in allocations {
+        total += allocation_portion(alloc);
+    }
+    total
+}
+
+pub(crate) fn validate_allocations(allocations: &[Allocation]) -> StdResult<()> {
+    let mut addresses = vec![];
+
+    for alloc in allocations {
+        let address = allocation_address(alloc);
+        if addresses.contains(&address) {
+            return Err(StdError::generic_err(match address {
+                Some(a) => format!("Duplicate allocation for {}", a),
+                None => "Duplicate reserves allocation".to_string(),
+            }));
         }
+        addresses.push(address);
 
-        // Validate addition does not exceed 100%
-        for app in &app_list {
-            allocated_portion = allocated_portion
-                + match app {
-                    Allocation::Rewards {
-                        contract: _,
-                        allocation: _,
-                    } => Uint128::zero(),
-                    Allocation::Staking {
-                        contract: _,
-                        allocation,
-                    } => *allocation,
-                    Allocation::Application {
-                        contract: _,
-                        allocation,
-                        token: _,
-                    } => *allocation,
-                    Allocation::Pool {
-                        contract: _,
-                        allocation,
-                        secondary_asset: _,
-                        token: _,
-                    } => *allocation,
-                    _ => Uint128::zero(),
-                };
+        let empty = match alloc {
+            Allocation::Allowance { amount, .. } => amount.is_zero(),
+            _ => allocation_portion(alloc).is_zero(),
+        };
+        if empty {
+            return Err(StdError::generic_err("Allocation cannot be zero"));
         }
 
-        if (allocated_portion + alloc_portion) >= Uint128(10u128.pow(18)) {
-            return Err(StdError::generic_err(
-                "Invalid allocation total exceeding 100%",
-            ));
+        match alloc {
+            Allocation::Allowance {
+                cycle: Cycle::Epoch { seconds },
+                ..
+            } if *seconds == 0 => {
+                return Err(StdError::generic_err("Epoch cannot be 0 seconds"));
+            }
+            Allocation::Allowance {
+                cycle: Cycle::Vesting { start, end },
+                ..
+            } if start >= end => {
+                return Err(StdError::generic_err("Vesting must end after it starts"));
+            }
+            _ => {}
         }
+    }
 
-        app_list.push(alloc);
+    if allocated_portion(allocations) > Uint128(10u128.pow(18)) {
+        return Err(StdError::generic_err(
+            "Invalid allocation total exceeding 100%",
+        ));
+    }
 
-        Ok(app_list)
-    })?;
+    Ok(())
+}
+
+pub fn register_allocation<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    asset: HumanAddr,
+    alloc: Allocation,
+) -> StdResult<HandleResponse> {
+    check_outflow_auth(&deps.storage, env)?;
+
+    do_register_allocation(deps, asset, alloc)?;
 
     /*TODO: Need to re-allocate/re-balance funds based on the new addition
      * get Uint128 math functions to do these things (untested)
-     * re-add send_msg below
      */
 
-    /*
-    let liquid_portion = (allocated_portion * liquid_balance) / allocated_portion;
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::RegisterApp {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+fn do_register_allocation<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    asset: HumanAddr,
+    alloc: Allocation,
+) -> StdResult<()> {
+    if assets_r(&deps.storage)
+        .may_load(asset.to_string().as_bytes())?
+        .is_none()
+    {
+        return Err(StdError::generic_err("Unregistered asset"));
+    }
+
+    let mut app_list = allocation_list(&deps.storage, &asset)?;
+
+    let address = allocation_address(&alloc);
+    if app_list
+        .iter()
+        .any(|app| allocation_address(app) == address)
+    {
+        return Err(StdError::generic_err(
+            "Allocation already registered, use UpdateAllocation",
+        ));
+    }
+
+    app_list.push(alloc.clone());
+    validate_allocations(&app_list)?;
+
+    push_allocation(&mut deps.storage, &asset, &alloc)
+}
+
+pub fn update_allocation<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    asset: HumanAddr,
+    alloc: Allocation,
+) -> StdResult<HandleResponse> {
+    check_outflow_auth(&deps.storage, env)?;
 
-    // Determine how much of current balance is to be allocated
-    let to_allocate = liquid_balance - (alloc_portion / liquid_portion);
-    */
+    do_update_allocation(deps, asset, alloc)?;
 
     Ok(HandleResponse {
-        messages: vec![
-            /*
-            send_msg(
-                    alloc_address,
-                    to_allocate,
-                    None,
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::UpdateAllocation {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+fn do_update_allocation<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    asset: HumanAddr,
+    alloc: Allocation,
+) -> StdResult<()> {
+    if assets_r(&deps.storage)
+        .may_load(asset.to_string().as_bytes())?
+        .is_none()
+    {
+        return Err(StdError::generic_err("Unregistered asset"));
+    }
+
+    let mut app_list = allocation_list(&deps.storage, &asset)?;
+
+    let address = allocation_address(&alloc);
+    let index = match app_list
+        .iter()
+        .position(|app| allocation_address(app) == address)
+    {
+        Some(i) => i,
+        None => return Err(StdError::not_found("Allocation")),
+    };
+
+    app_list[index] = alloc.clone();
+    validate_allocations(&app_list)?;
+
+    // Resumes an unwound allocation
+    if let Some(address) = address {
+        unwinding_w(&mut deps.storage, &asset).remove(address.to_string().as_bytes());
+    }
+
+    set_allocation(&mut deps.storage, &asset, index as u64, &alloc)
+}
+
+pub fn remove_allocation<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    asset: HumanAddr,
+    address: Option<HumanAddr>,
+) -> StdResult<HandleResponse> {
+    let config = config_r(&deps.storage).load()?;
+
+    /* ADMIN ONLY */
+    if env.message.sender != config.admin {
+        return Err(StdError::unauthorized());
+    }
+
+    if assets_r(&deps.storage)
+        .may_load(asset.to_string().as_bytes())?
+        .is_none()
+    {
+        return Err(StdError::generic_err("Unregistered asset"));
+    }
+
+    match allocation_position(&deps.storage, &asset, &address)? {
+        Some(i) => remove_allocation_at(&mut deps.storage, &asset, i)?,
+        None => return Err(StdError::not_found("Allocation")),
+    }
+
+    if let Some(address) = address {
+        unwinding_w(&mut deps.storage, &asset).remove(address.to_string().as_bytes());
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::RemoveAllocation {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+// Wrapped tokens must be tracked by the treasury
+fn registered_wrapper<S: Storage>(storage: &S, wrapper: &Contract) -> StdResult<()> {
+    if assets_r(storage)
+        .may_load(wrapper.address.to_string().as_bytes())?
+        .is_none()
+    {
+        return Err(StdError::generic_err(format!(
+            "Wrapper {} is not a registered asset",
+            wrapper.address
+        )));
+    }
+    Ok(())
+}
+
+pub fn register_wrapper<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    denom: String,
+    contract: Contract,
+) -> StdResult<HandleResponse> {
+    // Wrappers receive native outflows, so they follow the outflow approval
+    check_outflow_auth(&deps.storage, env)?;
+    registered_wrapper(&deps.storage, &contract)?;
+
+    native_denoms_w(&mut deps.storage).update(|mut denoms| {
+        if !denoms.contains(&denom) {
+            denoms.push(denom.clone());
+        }
+        Ok(denoms)
+    })?;
+    native_wrappers_w(&mut deps.storage).save(denom.as_bytes(), &contract)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::RegisterWrapper {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+pub fn wrap<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    denom: String,
+    amount: Option<Uint128>,
+) -> StdResult<HandleResponse> {
+    check_outflow_auth(&deps.storage, env)?;
+
+    let wrapper = match native_wrappers_r(&deps.storage).may_load(denom.as_bytes())? {
+        Some(w) => w,
+        None => {
+            return Err(StdError::generic_err(format!("No wrapper for {}", denom)));
+        }
+    };
+    registered_wrapper(&deps.storage, &wrapper)?;
+
+    let balance = deps
+        .querier
+        .query_balance(env.contract.address.clone(), &denom)?
+        .amount;
+    let amount = amount.unwrap_or(balance);
+
+    if amount.is_zero() || amount > balance {
+        return Err(StdError::generic_err(format!(
+            "Cannot wrap {} of {} {}",
+            amount, balance, denom
+        )));
+    }
+
+    // Built by hand since the toolkit deposit only sends uscrt
+    let messages = vec![CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: wrapper.address,
+        callback_code_hash: wrapper.code_hash,
+        msg: to_binary(&snip20::HandleMsg::Deposit { padding: None })?,
+        send: vec![Coin { denom, amount }],
+    })];
+
+    Ok(HandleResponse {
+        messages,
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::Wrap {
+            status: ResponseStatus::Success,
+            amount,
+        })?),
+    })
+}
+
+pub fn unwrap<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    denom: String,
+    amount: Uint128,
+) -> StdResult<HandleResponse> {
+    let config = config_r(&deps.storage).load()?;
+
+    /* ADMIN ONLY */
+    if env.message.sender != config.admin {
+        return Err(StdError::unauthorized());
+    }
+
+    let wrapper = match native_wrappers_r(&deps.storage).may_load(denom.as_bytes())? {
+        Some(w) => w,
+        None => {
+            return Err(StdError::generic_err(format!("No wrapper for {}", denom)));
+        }
+    };
+
+    Ok(HandleResponse {
+        messages: vec![redeem_msg(
+            amount,
+            Some(denom),
+            None,
+            256,
+            wrapper.code_hash,
+            wrapper.address,
+        )?],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::Unwrap {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+pub fn unwind<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    asset: HumanAddr,
+    allocation: Option<HumanAddr>,
+) -> StdResult<HandleResponse> {
+    let config = config_r(&deps.storage).load()?;
+
+    /* ADMIN ONLY */
+    if env.message.sender != config.admin {
+        return Err(StdError::unauthorized());
+    }
+
+    let full_asset = match assets_r(&deps.storage).may_load(asset.to_string().as_bytes())? {
+        Some(a) => a,
+        None => {
+            return Err(StdError::generic_err("Unregistered asset"));
+        }
+    };
+
+    // Reserves are already held by treasury
+    let targets: Vec<Allocation> = allocation_list(&deps.storage, &asset)?
+        .into_iter()
+        .filter(|alloc| match alloc {
+            Allocation::Reserves { .. } => false,
+            _ => allocation.is_none() || allocation_address(alloc) == allocation,
+        })
+        .collect();
+
+    if targets.is_empty() {
+        return Err(StdError::not_found("Allocation"));
+    }
+
+    let mut messages = vec![];
+    let mut unwinding = vec![];
+
+    for alloc in targets {
+        let expected = unwind_outstanding(deps, &full_asset, &alloc)?;
+
+        match &alloc {
+            Allocation::Staking { contract, .. } => {
+                messages.push(scrt_staking::HandleMsg::UnbondAll {}.to_cosmos_msg(
+                    contract.code_hash.clone(),
+                    contract.address.clone(),
                     None,
+                )?);
+            }
+            Allocation::Allowance { address, .. } => {
+                let cur_allowance = allowance_query(
+                    &deps.querier,
+                    env.contract.address.clone(),
+                    address.clone(),
+                    viewing_key_r(&deps.storage).load()?,
                     1,
                     full_asset.contract.code_hash.clone(),
                     full_asset.contract.address.clone(),
-            )?
-            */
-        ],
+                )?;
+
+                let mut status = allowance_status_r(&deps.storage, &asset)
+                    .may_load(address.to_string().as_bytes())?
+                    .unwrap_or_default();
+
+                let newly_spent = settle_allowance(
+                    &mut status,
+                    cur_allowance.allowance,
+                    is_expired(cur_allowance.expiration, env.block.time),
+                );
+                if !newly_spent.is_zero() {
+                    record_entry(
+                        &mut deps.storage,
+                        env,
+                        &asset,
+                        LedgerCategory::AllowanceSpend,
+                        address.clone(),
+                        newly_spent,
+                        None,
+                    )?;
+                }
+
+                if !expected.is_zero() {
+                    messages.push(decrease_allowance_msg(
+                        address.clone(),
+                        expected,
+                        None,
+                        None,
+                        1,
+                        full_asset.contract.code_hash.clone(),
+                        full_asset.contract.address.clone(),
+                    )?);
+                }
+
+                status.granted = Uint128::zero();
+                status.one_time = Uint128::zero();
+                allowance_status_w(&mut deps.storage, &asset)
+                    .save(address.to_string().as_bytes(), &status)?;
+            }
+            // Nothing to withdraw from, these stay outstanding until repaid
+            _ => {}
+        }
+
+        // Unwound allocations no longer receive funds or refresh
+        if let Some(address) = allocation_address(&alloc) {
+            unwinding_w(&mut deps.storage, &asset)
+                .save(address.to_string().as_bytes(), &env.block.time)?;
+        }
+
+        unwinding.push(UnwindStatus {
+            allocation: alloc,
+            started: env.block.time,
+            expected,
+            outstanding: expected,
+            complete: false,
+        });
+    }
+
+    unwinds_w(&mut deps.storage).update(asset.to_string().as_bytes(), |unwinds| {
+        let mut unwinds = unwinds.unwrap_or_default();
+        for status in &unwinding {
+            unwinds.retain(|unwind| {
+                allocation_address(&unwind.allocation) != allocation_address(&status.allocation)
+            });
+            unwinds.push(status.clone());
+        }
+        Ok(unwinds)
+    })?;
+
+    Ok(HandleResponse {
+        messages,
         log: vec![],
-        data: Some(to_binary(&HandleAnswer::RegisterApp {
+        data: Some(to_binary(&HandleAnswer::Unwind {
+            status: ResponseStatus::Success,
+            unwinding,
+        })?),
+    })
+}
+
+/* Moves assets and allocations stored as lists into their indexes,
+ * does nothing once migrated
+ */
+pub fn migrate_storage<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+) -> StdResult<HandleResponse> {
+    let config = config_r(&deps.storage).load()?;
+
+    /* ADMIN ONLY */
+    if env.message.sender != config.admin {
+        return Err(StdError::unauthorized());
+    }
+
+    if asset_count_r(&deps.storage).may_load()?.is_none() {
+        asset_count_w(&mut deps.storage).save(&0)?;
+    }
+
+    let assets = asset_list_r(&deps.storage).may_load()?.unwrap_or_default();
+    for asset in &assets {
+        push_asset(&mut deps.storage, asset)?;
+
+        let key = asset.to_string();
+        for alloc in allocations_r(&deps.storage)
+            .may_load(key.as_bytes())?
+            .unwrap_or_default()
+        {
+            push_allocation(&mut deps.storage, asset, &alloc)?;
+        }
+        allocations_w(&mut deps.storage).remove(key.as_bytes());
+    }
+    asset_list_w(&mut deps.storage).save(&vec![])?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::MigrateStorage {
             status: ResponseStatus::Success,
         })?),
     })
@@ -1,12 +1,31 @@
-use cosmwasm_std::{Api, Extern, HumanAddr, Querier, StdError, StdResult, Storage};
+use cosmwasm_std::{Api, Coin, Extern, HumanAddr, Querier, StdError, StdResult, Storage, Uint128};
 use secret_toolkit::{snip20::allowance_query, utils::Query};
-use shade_protocol::{snip20, treasury};
+use shade_protocol::{
+    band::ReferenceData,
+    oracle, scrt_staking,
+    snip20::{self, Snip20Asset},
+    treasury::{
+        self, Allocation, AllowanceData, AssetBalanceSheet, Cycle, Holdings, IncomeSource,
+        LedgerCategory, LedgerEntry, NativeWrapper, Repayment,
+    },
+    utils::asset::Contract,
+};
 
-use crate::state::{
-    allocations_r, asset_list_r, assets_r, config_r, last_allowance_refresh_r, self_address_r,
-    viewing_key_r,
+use crate::{
+    handle::{next_refresh, settle_allowance},
+    state::{
+        allocated_r, allocation_count, allocation_index_r, allocation_list, allocation_position,
+        allocation_range, allowance_count, allowance_range, allowance_status_r, asset_count_r,
+        asset_list, asset_range, assets_r, config_r, deployed_r, income_r, income_sources_r,
+        last_allowance_refresh_r, ledger_count_r, ledger_index_count_r, ledger_index_r, ledger_r,
+        multisig_r, native_denoms_r, native_wrappers_r, outflow_proposals_r, repayment_sources_r,
+        repayments_r, self_address_r, unwinds_r, viewing_key_r,
+    },
 };
 
+const DEFAULT_PAGE_LIMIT: u32 = 30;
+const MAX_PAGE_LIMIT: u32 = 100;
+
 pub fn config<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
 ) -> StdResult<treasury::QueryAnswer> {
@@ -30,9 +49,10 @@ pub fn balance<S: Storage, A: Api, Q: Querier>(
             .query(&deps.querier, a.contract.code_hash, a.contract.address)?;
 
             match resp {
-                snip20::QueryAnswer::Balance { amount } => {
-                    Ok(treasury::QueryAnswer::Balance { amount })
-                }
+                snip20::QueryAnswer::Balance { amount } => Ok(treasury::QueryAnswer::Balance {
+                    amount,
+                    native: native_balances(deps, asset)?,
+                }),
                 _ => Err(StdError::GenericErr {
                     msg: "Unexpected Response".to_string(),
                     backtrace: None,
@@ -46,62 +66,519 @@ pub fn balance<S: Storage, A: Api, Q: Querier>(
     }
 }
 
-pub fn allowances<S: Storage, A: Api, Q: Querier>(
+// Bank balances of the denoms wrapped by asset
+fn native_balances<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
     asset: &HumanAddr,
-    spender: &HumanAddr,
+) -> StdResult<Vec<Coin>> {
+    let self_address = self_address_r(&deps.storage).load()?;
+    let mut coins = vec![];
+
+    for denom in native_denoms_r(&deps.storage).load()? {
+        let wrapper = native_wrappers_r(&deps.storage).load(denom.as_bytes())?;
+        if wrapper.address == *asset {
+            coins.push(deps.querier.query_balance(self_address.clone(), &denom)?);
+        }
+    }
+
+    Ok(coins)
+}
+
+fn asset_price<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    oracle: &Contract,
+    symbol: String,
+) -> StdResult<Uint128> {
+    let answer: ReferenceData = oracle::QueryMsg::Price { symbol }.query(
+        &deps.querier,
+        oracle.code_hash.clone(),
+        oracle.address.clone(),
+    )?;
+    Ok(answer.rate)
+}
+
+fn usd_value(amount: Uint128, price: Uint128, decimals: u8) -> Uint128 {
+    amount.multiply_ratio(price, 10u128.pow(decimals as u32))
+}
+
+pub fn balance_sheet<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
 ) -> StdResult<treasury::QueryAnswer> {
     let self_address = self_address_r(&deps.storage).load()?;
     let key = viewing_key_r(&deps.storage).load()?;
+    let oracle = config_r(&deps.storage).load()?.oracle;
 
-    if let Some(full_asset) = assets_r(&deps.storage).may_load(asset.to_string().as_bytes())? {
-        let cur_allowance = allowance_query(
-            &deps.querier,
-            self_address,
-            spender.clone(),
-            key,
-            1,
-            full_asset.contract.code_hash.clone(),
-            full_asset.contract.address.clone(),
-        )?;
+    let mut assets = vec![];
+
+    for asset in asset_list(&deps.storage)? {
+        let full_asset = assets_r(&deps.storage).load(asset.to_string().as_bytes())?;
+
+        let mut native = Holdings {
+            // Unwrapped coins count as liquid
+            liquid: match balance(deps, &asset)? {
+                treasury::QueryAnswer::Balance { amount, native } => native
+                    .iter()
+                    .fold(amount, |total, coin| total + coin.amount),
+                _ => {
+                    return Err(StdError::generic_err("Unexpected response for balance"));
+                }
+            },
+            staked: Uint128::zero(),
+            allocated: Uint128::zero(),
+            allowances: Uint128::zero(),
+            rewards: Uint128::zero(),
+            total: Uint128::zero(),
+        };
+
+        for alloc in allocation_list(&deps.storage, &asset)? {
+            match alloc {
+                Allocation::Staking { contract, .. } => {
+                    let answer: scrt_staking::QueryAnswer = scrt_staking::QueryMsg::Balance {}
+                        .query(
+                            &deps.querier,
+                            contract.code_hash.clone(),
+                            contract.address.clone(),
+                        )?;
+                    if let scrt_staking::QueryAnswer::Balance { amount } = answer {
+                        native.staked += amount;
+                    }
+
+                    // Still ours while unbonding
+                    let answer: scrt_staking::QueryAnswer = scrt_staking::QueryMsg::Unbonding {}
+                        .query(
+                            &deps.querier,
+                            contract.code_hash.clone(),
+                            contract.address.clone(),
+                        )?;
+                    if let scrt_staking::QueryAnswer::Unbonding { amount } = answer {
+                        native.staked += amount;
+                    }
+
+                    let rewards: Uint128 = scrt_staking::QueryMsg::Rewards {}.query(
+                        &deps.querier,
+                        contract.code_hash,
+                        contract.address,
+                    )?;
+                    native.rewards += rewards;
+                }
+                Allocation::Allowance { address, .. } => {
+                    native.allowances += allowance_query(
+                        &deps.querier,
+                        self_address.clone(),
+                        address,
+                        key.clone(),
+                        1,
+                        full_asset.contract.code_hash.clone(),
+                        full_asset.contract.address.clone(),
+                    )?
+                    .allowance;
+                }
+                Allocation::Rewards { contract, .. }
+                | Allocation::Application { contract, .. }
+                | Allocation::Pool { contract, .. } => {
+                    native.allocated += deployed_r(&deps.storage, &asset)
+                        .may_load(contract.address.to_string().as_bytes())?
+                        .unwrap_or_default();
+                }
+                Allocation::Reserves { .. } => {}
+            }
+        }
+
+        native.total = native.liquid + native.staked + native.allocated + native.rewards;
+
+        // A missing price shouldn't hide the rest of the balance sheet
+        let (price, price_error) = match &oracle {
+            Some(oracle) => match asset_price(deps, oracle, full_asset.token_info.symbol.clone()) {
+                Ok(price) => (Some(price), None),
+                Err(e) => (None, Some(e.to_string())),
+            },
+            None => (None, None),
+        };
+        let decimals = full_asset.token_info.decimals;
+
+        assets.push(AssetBalanceSheet {
+            asset,
+            usd: price.map(|price| Holdings {
+                liquid: usd_value(native.liquid, price, decimals),
+                staked: usd_value(native.staked, price, decimals),
+                allocated: usd_value(native.allocated, price, decimals),
+                allowances: usd_value(native.allowances, price, decimals),
+                rewards: usd_value(native.rewards, price, decimals),
+                total: usd_value(native.total, price, decimals),
+            }),
+            native,
+            price,
+            price_error,
+        });
+    }
+
+    Ok(treasury::QueryAnswer::BalanceSheet { assets })
+}
+
+fn page_limit(limit: Option<u32>) -> u64 {
+    limit.unwrap_or(DEFAULT_PAGE_LIMIT).min(MAX_PAGE_LIMIT) as u64
+}
+
+// Index range of the page following start_after
+fn page(start_after: Option<u64>, limit: Option<u32>) -> (u64, u64) {
+    let start = start_after.map_or(0, |i| i + 1);
+    (start, start + page_limit(limit))
+}
+
+fn allowance_data<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    asset: &Snip20Asset,
+    spender: &HumanAddr,
+    alloc: Option<&Allocation>,
+) -> StdResult<AllowanceData> {
+    let cur_allowance = allowance_query(
+        &deps.querier,
+        self_address_r(&deps.storage).load()?,
+        spender.clone(),
+        viewing_key_r(&deps.storage).load()?,
+        1,
+        asset.contract.code_hash.clone(),
+        asset.contract.address.clone(),
+    )?;
+
+    let mut status = allowance_status_r(&deps.storage, &asset.contract.address)
+        .may_load(spender.to_string().as_bytes())?
+        .unwrap_or_default();
+
+    // Includes spending since the last update, queries can't tell if it has expired
+    settle_allowance(&mut status, cur_allowance.allowance, false);
+
+    let mut allowance = AllowanceData {
+        spender: spender.clone(),
+        amount: cur_allowance.allowance,
+        one_time: status.one_time,
+        cycle: None,
+        cap: None,
+        spent: status.spent,
+        remaining: None,
+        last_refresh: status.last_refresh,
+        next_refresh: None,
+        expiration: cur_allowance.expiration,
+    };
+
+    if let Some(Allocation::Allowance {
+        amount, cycle, cap, ..
+    }) = alloc
+    {
+        let limit = match cycle {
+            Cycle::Vesting { .. } => Some(cap.map_or(*amount, |cap| cap.min(*amount))),
+            _ => *cap,
+        };
+
+        allowance.remaining =
+            limit.map(|limit| (limit - status.spent).unwrap_or_else(|_| Uint128::zero()));
+        allowance.next_refresh = next_refresh(cycle, status.last_refresh.unwrap_or(0));
+        allowance.cycle = Some(cycle.clone());
+        allowance.cap = *cap;
+    }
+
+    Ok(allowance)
+}
+
+pub fn allowances<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    asset: &HumanAddr,
+    spender: Option<HumanAddr>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<treasury::QueryAnswer> {
+    let full_asset = match assets_r(&deps.storage).may_load(asset.to_string().as_bytes())? {
+        Some(a) => a,
+        None => {
+            return Err(StdError::generic_err(format!("Unknown Asset: {}", asset)));
+        }
+    };
+
+    if let Some(spender) = spender {
+        let alloc = match allocation_position(&deps.storage, asset, &Some(spender.clone()))? {
+            Some(index) => {
+                Some(allocation_index_r(&deps.storage, asset).load(&index.to_be_bytes())?)
+            }
+            None => None,
+        };
 
         return Ok(treasury::QueryAnswer::Allowances {
-            allowances: vec![treasury::AllowanceData {
-                spender: spender.clone(),
-                amount: cur_allowance.allowance,
-            }],
+            allowances: vec![allowance_data(deps, &full_asset, &spender, alloc.as_ref())?],
+            total: 1,
         });
     }
 
-    Err(StdError::generic_err(format!("Unknown Asset: {}", asset)))
+    // Indexes are within the asset's allowance allocations
+    let (start, end) = page(start_after, limit);
+    let mut allowances = vec![];
+
+    for alloc in allowance_range(&deps.storage, asset, start, end)? {
+        if let Allocation::Allowance { address, .. } = &alloc {
+            allowances.push(allowance_data(deps, &full_asset, address, Some(&alloc))?);
+        }
+    }
+
+    Ok(treasury::QueryAnswer::Allowances {
+        allowances,
+        total: allowance_count(&deps.storage, asset)?,
+    })
 }
 
 pub fn assets<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
 ) -> StdResult<treasury::QueryAnswer> {
+    let (start, end) = page(start_after, limit);
+
     Ok(treasury::QueryAnswer::Assets {
-        assets: asset_list_r(&deps.storage).load()?,
+        assets: asset_range(&deps.storage, start, end)?,
+        total: asset_count_r(&deps.storage).load()?,
     })
 }
 
 pub fn allocations<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
     asset: HumanAddr,
+    start_after: Option<u64>,
+    limit: Option<u32>,
 ) -> StdResult<treasury::QueryAnswer> {
+    let (start, end) = page(start_after, limit);
+
     Ok(treasury::QueryAnswer::Allocations {
-        allocations: match allocations_r(&deps.storage).may_load(asset.to_string().as_bytes())? {
-            None => {
-                vec![]
-            }
-            Some(a) => a,
-        },
+        allocations: allocation_range(&deps.storage, &asset, start, end)?,
+        total: allocation_count(&deps.storage, &asset)?,
+        unallocated: (Uint128(10u128.pow(18))
+            - allocated_r(&deps.storage)
+                .may_load(asset.to_string().as_bytes())?
+                .unwrap_or_default())
+        .unwrap_or_else(|_| Uint128::zero()),
     })
 }
 
 pub fn last_allowance_refresh<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
 ) -> StdResult<treasury::QueryAnswer> {
-    Ok(treasury::QueryAnswer::Allowances { allowances: vec![] })
+    Ok(treasury::QueryAnswer::LastAllowanceRefresh {
+        datetime: last_allowance_refresh_r(&deps.storage).load()?,
+    })
+}
+
+pub fn multisig<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<treasury::QueryAnswer> {
+    Ok(treasury::QueryAnswer::Multisig {
+        multisig: multisig_r(&deps.storage).may_load()?,
+    })
+}
+
+pub fn outflow_proposal<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    proposal_id: Uint128,
+) -> StdResult<treasury::QueryAnswer> {
+    match outflow_proposals_r(&deps.storage).may_load(proposal_id.to_string().as_bytes())? {
+        Some(proposal) => Ok(treasury::QueryAnswer::OutflowProposal { proposal }),
+        None => Err(StdError::not_found("Outflow proposal")),
+    }
+}
+
+pub fn native_wrappers<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<treasury::QueryAnswer> {
+    let self_address = self_address_r(&deps.storage).load()?;
+    let mut wrappers = vec![];
+
+    for denom in native_denoms_r(&deps.storage).load()? {
+        wrappers.push(NativeWrapper {
+            contract: native_wrappers_r(&deps.storage).load(denom.as_bytes())?,
+            balance: deps
+                .querier
+                .query_balance(self_address.clone(), &denom)?
+                .amount,
+            denom,
+        });
+    }
+
+    Ok(treasury::QueryAnswer::NativeWrappers { wrappers })
+}
+
+pub fn income<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    asset: HumanAddr,
+) -> StdResult<treasury::QueryAnswer> {
+    if assets_r(&deps.storage)
+        .may_load(asset.to_string().as_bytes())?
+        .is_none()
+    {
+        return Err(StdError::not_found(asset.to_string()));
+    }
+
+    let mut income = vec![];
+    for source in income_sources_r(&deps.storage)
+        .may_load(asset.to_string().as_bytes())?
+        .unwrap_or_default()
+    {
+        income.push(IncomeSource {
+            amount: income_r(&deps.storage, &asset).load(source.as_bytes())?,
+            source,
+        });
+    }
+
+    let mut repayments = vec![];
+    for address in repayment_sources_r(&deps.storage)
+        .may_load(asset.to_string().as_bytes())?
+        .unwrap_or_default()
+    {
+        repayments.push(Repayment {
+            amount: repayments_r(&deps.storage, &asset).load(address.to_string().as_bytes())?,
+            address,
+        });
+    }
+
+    Ok(treasury::QueryAnswer::Income { income, repayments })
+}
+
+pub fn ledger<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    asset: HumanAddr,
+    category: Option<LedgerCategory>,
+    start: Option<u64>,
+    end: Option<u64>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<treasury::QueryAnswer> {
+    if assets_r(&deps.storage)
+        .may_load(asset.to_string().as_bytes())?
+        .is_none()
+    {
+        return Err(StdError::not_found(asset.to_string()));
+    }
+
+    let total = match &category {
+        Some(category) => ledger_index_count_r(&deps.storage, &asset)
+            .may_load(format!("{:?}", category).as_bytes())?,
+        None => ledger_count_r(&deps.storage).may_load(asset.to_string().as_bytes())?,
+    }
+    .unwrap_or(0);
+
+    let entry_at = |position: u64| -> StdResult<LedgerEntry> {
+        let id = match &category {
+            Some(category) => {
+                ledger_index_r(&deps.storage, &asset, category).load(&position.to_be_bytes())?
+            }
+            None => position,
+        };
+        ledger_r(&deps.storage, &asset).load(&id.to_be_bytes())
+    };
+
+    // Ids and times only grow, so search for the first entry past the cursor and start
+    let (mut low, mut high) = (0, total);
+    while low < high {
+        let mid = (low + high) / 2;
+        let entry = entry_at(mid)?;
+
+        if start.map_or(false, |start| entry.time < start)
+            || start_after.map_or(false, |id| entry.id <= id)
+        {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+
+    let limit = page_limit(limit) as usize;
+    let mut entries = vec![];
+
+    for position in low..total {
+        if entries.len() >= limit {
+            break;
+        }
+
+        let entry = entry_at(position)?;
+        if end.map_or(false, |end| entry.time > end) {
+            break;
+        }
+        entries.push(entry);
+    }
+
+    Ok(treasury::QueryAnswer::Ledger { entries, total })
+}
+
+// Funds of an allocation that have not come back to treasury
+pub fn unwind_outstanding<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    asset: &Snip20Asset,
+    alloc: &Allocation,
+) -> StdResult<Uint128> {
+    match alloc {
+        Allocation::Staking { contract, .. } => {
+            let mut outstanding = Uint128::zero();
+
+            let answer: scrt_staking::QueryAnswer = scrt_staking::QueryMsg::Balance {}.query(
+                &deps.querier,
+                contract.code_hash.clone(),
+                contract.address.clone(),
+            )?;
+            if let scrt_staking::QueryAnswer::Balance { amount } = answer {
+                outstanding += amount;
+            }
+
+            let answer: scrt_staking::QueryAnswer = scrt_staking::QueryMsg::Unbonding {}.query(
+                &deps.querier,
+                contract.code_hash.clone(),
+                contract.address.clone(),
+            )?;
+            if let scrt_staking::QueryAnswer::Unbonding { amount } = answer {
+                outstanding += amount;
+            }
+
+            Ok(outstanding)
+        }
+        Allocation::Allowance { address, .. } => Ok(allowance_query(
+            &deps.querier,
+            self_address_r(&deps.storage).load()?,
+            address.clone(),
+            viewing_key_r(&deps.storage).load()?,
+            1,
+            asset.contract.code_hash.clone(),
+            asset.contract.address.clone(),
+        )?
+        .allowance),
+        // Can't be withdrawn from, outstanding until repaid
+        Allocation::Rewards { contract, .. }
+        | Allocation::Application { contract, .. }
+        | Allocation::Pool { contract, .. } => {
+            Ok(deployed_r(&deps.storage, &asset.contract.address)
+                .may_load(contract.address.to_string().as_bytes())?
+                .unwrap_or_default())
+        }
+        Allocation::Reserves { .. } => Ok(Uint128::zero()),
+    }
+}
+
+pub fn unwinds<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    asset: HumanAddr,
+) -> StdResult<treasury::QueryAnswer> {
+    let full_asset = match assets_r(&deps.storage).may_load(asset.to_string().as_bytes())? {
+        Some(a) => a,
+        None => {
+            return Err(StdError::not_found(asset.to_string()));
+        }
+    };
+
+    let mut unwinds = unwinds_r(&deps.storage)
+        .may_load(asset.to_string().as_bytes())?
+        .unwrap_or_default();
+
+    for unwind in &mut unwinds {
+        if !unwind.complete {
+            unwind.outstanding = unwind_outstanding(deps, &full_asset, &unwind.allocation)?;
+            unwind.complete = unwind.outstanding.is_zero();
+        }
+    }
+
+    Ok(treasury::QueryAnswer::Unwinds { unwinds })
 }
 
 /*
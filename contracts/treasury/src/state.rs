@@ -1,17 +1,46 @@
-use cosmwasm_std::{HumanAddr, Storage, Uint128};
+use cosmwasm_std::{HumanAddr, StdResult, Storage, Uint128};
 use cosmwasm_storage::{
     bucket, bucket_read, singleton, singleton_read, Bucket, ReadonlyBucket, ReadonlySingleton,
     Singleton,
 };
-use shade_protocol::{snip20::Snip20Asset, treasury};
+use shade_protocol::{snip20::Snip20Asset, treasury, utils::asset::Contract};
+
+use crate::handle::{allocation_address, allocation_portion};
 
 pub static CONFIG_KEY: &[u8] = b"config";
 pub static ASSETS: &[u8] = b"assets";
-pub static ASSET_LIST: &[u8] = b"asset_list";
+pub static ASSET_COUNT: &[u8] = b"asset_count";
+pub static ASSET_INDEX: &[u8] = b"asset_index";
 pub static VIEWING_KEY: &[u8] = b"viewing_key";
 pub static SELF_ADDRESS: &[u8] = b"self_address";
+pub static ALLOCATION_COUNT: &[u8] = b"allocation_count";
+pub static ALLOCATION_INDEX: &str = "allocation_index";
+pub static ALLOCATION_POSITION: &str = "allocation_position";
+pub static ALLOCATED: &[u8] = b"allocated";
+pub static ALLOWANCE_COUNT: &[u8] = b"allowance_count";
+pub static ALLOWANCE_INDEX: &str = "allowance_index";
+pub static ALLOWANCE_POSITION: &str = "allowance_position";
+// Storage from before assets and allocations were indexed
+pub static ASSET_LIST: &[u8] = b"asset_list";
 pub static ALLOCATIONS: &[u8] = b"allocations";
 pub static ALLOWANCE_REFRESH: &[u8] = b"allowance_refresh";
+pub static ALLOWANCE_STATUS: &str = "allowance_status";
+pub static MULTISIG: &[u8] = b"multisig";
+pub static OUTFLOW_PROPOSALS: &[u8] = b"outflow_proposals";
+pub static TOTAL_OUTFLOW_PROPOSALS: &[u8] = b"total_outflow_proposals";
+pub static NATIVE_WRAPPERS: &[u8] = b"native_wrappers";
+pub static NATIVE_DENOMS: &[u8] = b"native_denoms";
+pub static INCOME: &str = "income";
+pub static INCOME_SOURCES: &[u8] = b"income_sources";
+pub static REPAYMENTS: &str = "repayments";
+pub static REPAYMENT_SOURCES: &[u8] = b"repayment_sources";
+pub static UNWINDS: &[u8] = b"unwinds";
+pub static DEPLOYED: &str = "deployed";
+pub static UNWINDING: &str = "unwinding";
+pub static LEDGER: &str = "ledger";
+pub static LEDGER_COUNT: &[u8] = b"ledger_count";
+pub static LEDGER_INDEX: &str = "ledger_index";
+pub static LEDGER_INDEX_COUNT: &str = "ledger_index_count";
 
 pub fn config_w<S: Storage>(storage: &mut S) -> Singleton<S, treasury::Config> {
     singleton(storage, CONFIG_KEY)
@@ -21,12 +50,40 @@ pub fn config_r<S: Storage>(storage: &S) -> ReadonlySingleton<S, treasury::Confi
     singleton_read(storage, CONFIG_KEY)
 }
 
-pub fn asset_list_r<S: Storage>(storage: &S) -> ReadonlySingleton<S, Vec<HumanAddr>> {
-    singleton_read(storage, ASSET_LIST)
+pub fn asset_count_r<S: Storage>(storage: &S) -> ReadonlySingleton<S, u64> {
+    singleton_read(storage, ASSET_COUNT)
 }
 
-pub fn asset_list_w<S: Storage>(storage: &mut S) -> Singleton<S, Vec<HumanAddr>> {
-    singleton(storage, ASSET_LIST)
+pub fn asset_count_w<S: Storage>(storage: &mut S) -> Singleton<S, u64> {
+    singleton(storage, ASSET_COUNT)
+}
+
+// index -> asset address
+pub fn asset_index_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, HumanAddr> {
+    bucket_read(ASSET_INDEX, storage)
+}
+
+pub fn asset_index_w<S: Storage>(storage: &mut S) -> Bucket<S, HumanAddr> {
+    bucket(ASSET_INDEX, storage)
+}
+
+pub fn asset_list<S: Storage>(storage: &S) -> StdResult<Vec<HumanAddr>> {
+    let count = asset_count_r(storage).load()?;
+    asset_range(storage, 0, count)
+}
+
+pub fn asset_range<S: Storage>(storage: &S, start: u64, end: u64) -> StdResult<Vec<HumanAddr>> {
+    let mut assets = vec![];
+    for i in start..end.min(asset_count_r(storage).load()?) {
+        assets.push(asset_index_r(storage).load(&i.to_be_bytes())?);
+    }
+    Ok(assets)
+}
+
+pub fn push_asset<S: Storage>(storage: &mut S, asset: &HumanAddr) -> StdResult<()> {
+    let count = asset_count_r(storage).load()?;
+    asset_index_w(storage).save(&count.to_be_bytes(), asset)?;
+    asset_count_w(storage).save(&(count + 1))
 }
 
 pub fn assets_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, Snip20Asset> {
@@ -53,6 +110,275 @@ pub fn self_address_w<S: Storage>(storage: &mut S) -> Singleton<S, HumanAddr> {
     singleton(storage, SELF_ADDRESS)
 }
 
+// asset -> allocations
+pub fn allocation_count_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, u64> {
+    bucket_read(ALLOCATION_COUNT, storage)
+}
+
+pub fn allocation_count_w<S: Storage>(storage: &mut S) -> Bucket<S, u64> {
+    bucket(ALLOCATION_COUNT, storage)
+}
+
+// index -> allocation, per asset
+pub fn allocation_index_r<S: Storage>(
+    storage: &S,
+    asset: &HumanAddr,
+) -> ReadonlyBucket<S, treasury::Allocation> {
+    bucket_read((asset.to_string() + ALLOCATION_INDEX).as_bytes(), storage)
+}
+
+pub fn allocation_index_w<S: Storage>(
+    storage: &mut S,
+    asset: &HumanAddr,
+) -> Bucket<S, treasury::Allocation> {
+    bucket((asset.to_string() + ALLOCATION_INDEX).as_bytes(), storage)
+}
+
+pub fn allocation_count<S: Storage>(storage: &S, asset: &HumanAddr) -> StdResult<u64> {
+    Ok(allocation_count_r(storage)
+        .may_load(asset.to_string().as_bytes())?
+        .unwrap_or(0))
+}
+
+pub fn allocation_list<S: Storage>(
+    storage: &S,
+    asset: &HumanAddr,
+) -> StdResult<Vec<treasury::Allocation>> {
+    let count = allocation_count(storage, asset)?;
+    allocation_range(storage, asset, 0, count)
+}
+
+pub fn allocation_range<S: Storage>(
+    storage: &S,
+    asset: &HumanAddr,
+    start: u64,
+    end: u64,
+) -> StdResult<Vec<treasury::Allocation>> {
+    let mut allocations = vec![];
+    for i in start..end.min(allocation_count(storage, asset)?) {
+        allocations.push(allocation_index_r(storage, asset).load(&i.to_be_bytes())?);
+    }
+    Ok(allocations)
+}
+
+// Allocations are identified by their address, reserves by an empty key
+fn allocation_key(allocation: &treasury::Allocation) -> String {
+    allocation_address(allocation).map_or(String::new(), |address| address.to_string())
+}
+
+// allocation address -> index, per asset
+pub fn allocation_position_r<S: Storage>(storage: &S, asset: &HumanAddr) -> ReadonlyBucket<S, u64> {
+    bucket_read(
+        (asset.to_string() + ALLOCATION_POSITION).as_bytes(),
+        storage,
+    )
+}
+
+pub fn allocation_position_w<S: Storage>(storage: &mut S, asset: &HumanAddr) -> Bucket<S, u64> {
+    bucket(
+        (asset.to_string() + ALLOCATION_POSITION).as_bytes(),
+        storage,
+    )
+}
+
+pub fn allocation_position<S: Storage>(
+    storage: &S,
+    asset: &HumanAddr,
+    address: &Option<HumanAddr>,
+) -> StdResult<Option<u64>> {
+    let key = address.as_ref().map_or(String::new(), |a| a.to_string());
+    allocation_position_r(storage, asset).may_load(key.as_bytes())
+}
+
+// asset -> total portion of its allocations
+pub fn allocated_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, Uint128> {
+    bucket_read(ALLOCATED, storage)
+}
+
+pub fn allocated_w<S: Storage>(storage: &mut S) -> Bucket<S, Uint128> {
+    bucket(ALLOCATED, storage)
+}
+
+fn update_allocated<S: Storage>(
+    storage: &mut S,
+    asset: &HumanAddr,
+    added: &Option<treasury::Allocation>,
+    removed: &Option<treasury::Allocation>,
+) -> StdResult<()> {
+    allocated_w(storage).update(asset.to_string().as_bytes(), |total| {
+        let mut total = total.unwrap_or_default();
+        if let Some(alloc) = added {
+            total += allocation_portion(alloc);
+        }
+        if let Some(alloc) = removed {
+            total = (total - allocation_portion(alloc))?;
+        }
+        Ok(total)
+    })?;
+    Ok(())
+}
+
+pub fn push_allocation<S: Storage>(
+    storage: &mut S,
+    asset: &HumanAddr,
+    allocation: &treasury::Allocation,
+) -> StdResult<()> {
+    let count = allocation_count(storage, asset)?;
+    allocation_index_w(storage, asset).save(&count.to_be_bytes(), allocation)?;
+    allocation_position_w(storage, asset).save(allocation_key(allocation).as_bytes(), &count)?;
+    allocation_count_w(storage).save(asset.to_string().as_bytes(), &(count + 1))?;
+
+    update_allocated(storage, asset, &Some(allocation.clone()), &None)?;
+    if let treasury::Allocation::Allowance { address, .. } = allocation {
+        push_allowance(storage, asset, address)?;
+    }
+    Ok(())
+}
+
+// Replaces the allocation at index, which must have the same address
+pub fn set_allocation<S: Storage>(
+    storage: &mut S,
+    asset: &HumanAddr,
+    index: u64,
+    allocation: &treasury::Allocation,
+) -> StdResult<()> {
+    let old = allocation_index_r(storage, asset).load(&index.to_be_bytes())?;
+    allocation_index_w(storage, asset).save(&index.to_be_bytes(), allocation)?;
+
+    update_allocated(
+        storage,
+        asset,
+        &Some(allocation.clone()),
+        &Some(old.clone()),
+    )?;
+    match (&old, allocation) {
+        (treasury::Allocation::Allowance { .. }, treasury::Allocation::Allowance { .. }) => {}
+        (treasury::Allocation::Allowance { address, .. }, _) => {
+            remove_allowance(storage, asset, address)?
+        }
+        (_, treasury::Allocation::Allowance { address, .. }) => {
+            push_allowance(storage, asset, address)?
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+// Moves the last allocation into the removed index
+pub fn remove_allocation_at<S: Storage>(
+    storage: &mut S,
+    asset: &HumanAddr,
+    index: u64,
+) -> StdResult<()> {
+    let count = allocation_count(storage, asset)?;
+    let removed = allocation_index_r(storage, asset).load(&index.to_be_bytes())?;
+
+    if index + 1 < count {
+        let last = allocation_index_r(storage, asset).load(&(count - 1).to_be_bytes())?;
+        allocation_index_w(storage, asset).save(&index.to_be_bytes(), &last)?;
+        allocation_position_w(storage, asset).save(allocation_key(&last).as_bytes(), &index)?;
+    }
+    allocation_index_w(storage, asset).remove(&(count - 1).to_be_bytes());
+    allocation_position_w(storage, asset).remove(allocation_key(&removed).as_bytes());
+    allocation_count_w(storage).save(asset.to_string().as_bytes(), &(count - 1))?;
+
+    update_allocated(storage, asset, &None, &Some(removed.clone()))?;
+    if let treasury::Allocation::Allowance { address, .. } = &removed {
+        remove_allowance(storage, asset, address)?;
+    }
+    Ok(())
+}
+
+// asset -> allowance allocations
+pub fn allowance_count_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, u64> {
+    bucket_read(ALLOWANCE_COUNT, storage)
+}
+
+pub fn allowance_count_w<S: Storage>(storage: &mut S) -> Bucket<S, u64> {
+    bucket(ALLOWANCE_COUNT, storage)
+}
+
+pub fn allowance_count<S: Storage>(storage: &S, asset: &HumanAddr) -> StdResult<u64> {
+    Ok(allowance_count_r(storage)
+        .may_load(asset.to_string().as_bytes())?
+        .unwrap_or(0))
+}
+
+// index -> spender, per asset
+pub fn allowance_index_r<S: Storage>(
+    storage: &S,
+    asset: &HumanAddr,
+) -> ReadonlyBucket<S, HumanAddr> {
+    bucket_read((asset.to_string() + ALLOWANCE_INDEX).as_bytes(), storage)
+}
+
+pub fn allowance_index_w<S: Storage>(storage: &mut S, asset: &HumanAddr) -> Bucket<S, HumanAddr> {
+    bucket((asset.to_string() + ALLOWANCE_INDEX).as_bytes(), storage)
+}
+
+// spender -> index, per asset
+pub fn allowance_position_r<S: Storage>(storage: &S, asset: &HumanAddr) -> ReadonlyBucket<S, u64> {
+    bucket_read((asset.to_string() + ALLOWANCE_POSITION).as_bytes(), storage)
+}
+
+pub fn allowance_position_w<S: Storage>(storage: &mut S, asset: &HumanAddr) -> Bucket<S, u64> {
+    bucket((asset.to_string() + ALLOWANCE_POSITION).as_bytes(), storage)
+}
+
+fn push_allowance<S: Storage>(
+    storage: &mut S,
+    asset: &HumanAddr,
+    spender: &HumanAddr,
+) -> StdResult<()> {
+    let count = allowance_count(storage, asset)?;
+    allowance_index_w(storage, asset).save(&count.to_be_bytes(), spender)?;
+    allowance_position_w(storage, asset).save(spender.to_string().as_bytes(), &count)?;
+    allowance_count_w(storage).save(asset.to_string().as_bytes(), &(count + 1))
+}
+
+fn remove_allowance<S: Storage>(
+    storage: &mut S,
+    asset: &HumanAddr,
+    spender: &HumanAddr,
+) -> StdResult<()> {
+    let count = allowance_count(storage, asset)?;
+    let index = allowance_position_r(storage, asset).load(spender.to_string().as_bytes())?;
+
+    if index + 1 < count {
+        let last = allowance_index_r(storage, asset).load(&(count - 1).to_be_bytes())?;
+        allowance_index_w(storage, asset).save(&index.to_be_bytes(), &last)?;
+        allowance_position_w(storage, asset).save(last.to_string().as_bytes(), &index)?;
+    }
+    allowance_index_w(storage, asset).remove(&(count - 1).to_be_bytes());
+    allowance_position_w(storage, asset).remove(spender.to_string().as_bytes());
+    allowance_count_w(storage).save(asset.to_string().as_bytes(), &(count - 1))
+}
+
+// Allowance allocations from start up to end
+pub fn allowance_range<S: Storage>(
+    storage: &S,
+    asset: &HumanAddr,
+    start: u64,
+    end: u64,
+) -> StdResult<Vec<treasury::Allocation>> {
+    let mut allowances = vec![];
+    for i in start..end.min(allowance_count(storage, asset)?) {
+        let spender = allowance_index_r(storage, asset).load(&i.to_be_bytes())?;
+        let index = allocation_position_r(storage, asset).load(spender.to_string().as_bytes())?;
+        allowances.push(allocation_index_r(storage, asset).load(&index.to_be_bytes())?);
+    }
+    Ok(allowances)
+}
+
+// Only read when migrating to the indexes
+pub fn asset_list_r<S: Storage>(storage: &S) -> ReadonlySingleton<S, Vec<HumanAddr>> {
+    singleton_read(storage, ASSET_LIST)
+}
+
+pub fn asset_list_w<S: Storage>(storage: &mut S) -> Singleton<S, Vec<HumanAddr>> {
+    singleton(storage, ASSET_LIST)
+}
+
 pub fn allocations_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, Vec<treasury::Allocation>> {
     bucket_read(ALLOCATIONS, storage)
 }
@@ -68,3 +394,178 @@ pub fn last_allowance_refresh_r<S: Storage>(storage: &S) -> ReadonlySingleton<S,
 pub fn last_allowance_refresh_w<S: Storage>(storage: &mut S) -> Singleton<S, String> {
     singleton(storage, ALLOWANCE_REFRESH)
 }
+
+// Allowance bookkeeping of an asset, keyed by spender
+pub fn allowance_status_r<S: Storage>(
+    storage: &S,
+    asset: &HumanAddr,
+) -> ReadonlyBucket<S, treasury::AllowanceStatus> {
+    bucket_read((asset.to_string() + ALLOWANCE_STATUS).as_bytes(), storage)
+}
+
+pub fn allowance_status_w<S: Storage>(
+    storage: &mut S,
+    asset: &HumanAddr,
+) -> Bucket<S, treasury::AllowanceStatus> {
+    bucket((asset.to_string() + ALLOWANCE_STATUS).as_bytes(), storage)
+}
+
+pub fn multisig_r<S: Storage>(storage: &S) -> ReadonlySingleton<S, treasury::Multisig> {
+    singleton_read(storage, MULTISIG)
+}
+
+pub fn multisig_w<S: Storage>(storage: &mut S) -> Singleton<S, treasury::Multisig> {
+    singleton(storage, MULTISIG)
+}
+
+pub fn outflow_proposals_r<S: Storage>(
+    storage: &S,
+) -> ReadonlyBucket<S, treasury::OutflowProposal> {
+    bucket_read(OUTFLOW_PROPOSALS, storage)
+}
+
+pub fn outflow_proposals_w<S: Storage>(storage: &mut S) -> Bucket<S, treasury::OutflowProposal> {
+    bucket(OUTFLOW_PROPOSALS, storage)
+}
+
+pub fn total_outflow_proposals_r<S: Storage>(storage: &S) -> ReadonlySingleton<S, Uint128> {
+    singleton_read(storage, TOTAL_OUTFLOW_PROPOSALS)
+}
+
+pub fn total_outflow_proposals_w<S: Storage>(storage: &mut S) -> Singleton<S, Uint128> {
+    singleton(storage, TOTAL_OUTFLOW_PROPOSALS)
+}
+
+// denom -> wrapping snip20
+pub fn native_wrappers_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, Contract> {
+    bucket_read(NATIVE_WRAPPERS, storage)
+}
+
+pub fn native_wrappers_w<S: Storage>(storage: &mut S) -> Bucket<S, Contract> {
+    bucket(NATIVE_WRAPPERS, storage)
+}
+
+pub fn native_denoms_r<S: Storage>(storage: &S) -> ReadonlySingleton<S, Vec<String>> {
+    singleton_read(storage, NATIVE_DENOMS)
+}
+
+pub fn native_denoms_w<S: Storage>(storage: &mut S) -> Singleton<S, Vec<String>> {
+    singleton(storage, NATIVE_DENOMS)
+}
+
+// source -> total received, per asset
+pub fn income_r<S: Storage>(storage: &S, asset: &HumanAddr) -> ReadonlyBucket<S, Uint128> {
+    bucket_read((asset.to_string() + INCOME).as_bytes(), storage)
+}
+
+pub fn income_w<S: Storage>(storage: &mut S, asset: &HumanAddr) -> Bucket<S, Uint128> {
+    bucket((asset.to_string() + INCOME).as_bytes(), storage)
+}
+
+// asset -> sources received from
+pub fn income_sources_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, Vec<String>> {
+    bucket_read(INCOME_SOURCES, storage)
+}
+
+pub fn income_sources_w<S: Storage>(storage: &mut S) -> Bucket<S, Vec<String>> {
+    bucket(INCOME_SOURCES, storage)
+}
+
+// allocation -> total repaid, per asset
+pub fn repayments_r<S: Storage>(storage: &S, asset: &HumanAddr) -> ReadonlyBucket<S, Uint128> {
+    bucket_read((asset.to_string() + REPAYMENTS).as_bytes(), storage)
+}
+
+pub fn repayments_w<S: Storage>(storage: &mut S, asset: &HumanAddr) -> Bucket<S, Uint128> {
+    bucket((asset.to_string() + REPAYMENTS).as_bytes(), storage)
+}
+
+// allocation -> sent and not yet repaid, per asset
+pub fn deployed_r<S: Storage>(storage: &S, asset: &HumanAddr) -> ReadonlyBucket<S, Uint128> {
+    bucket_read((asset.to_string() + DEPLOYED).as_bytes(), storage)
+}
+
+pub fn deployed_w<S: Storage>(storage: &mut S, asset: &HumanAddr) -> Bucket<S, Uint128> {
+    bucket((asset.to_string() + DEPLOYED).as_bytes(), storage)
+}
+
+// asset -> allocations repaid from
+pub fn repayment_sources_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, Vec<HumanAddr>> {
+    bucket_read(REPAYMENT_SOURCES, storage)
+}
+
+pub fn repayment_sources_w<S: Storage>(storage: &mut S) -> Bucket<S, Vec<HumanAddr>> {
+    bucket(REPAYMENT_SOURCES, storage)
+}
+
+// id -> entry, per asset
+pub fn ledger_r<S: Storage>(
+    storage: &S,
+    asset: &HumanAddr,
+) -> ReadonlyBucket<S, treasury::LedgerEntry> {
+    bucket_read((asset.to_string() + LEDGER).as_bytes(), storage)
+}
+
+pub fn ledger_w<S: Storage>(
+    storage: &mut S,
+    asset: &HumanAddr,
+) -> Bucket<S, treasury::LedgerEntry> {
+    bucket((asset.to_string() + LEDGER).as_bytes(), storage)
+}
+
+// asset -> entries
+pub fn ledger_count_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, u64> {
+    bucket_read(LEDGER_COUNT, storage)
+}
+
+pub fn ledger_count_w<S: Storage>(storage: &mut S) -> Bucket<S, u64> {
+    bucket(LEDGER_COUNT, storage)
+}
+
+fn ledger_index_key(asset: &HumanAddr, category: &treasury::LedgerCategory) -> String {
+    format!("{}{:?}{}", asset, category, LEDGER_INDEX)
+}
+
+// position -> entry id, per asset and category
+pub fn ledger_index_r<S: Storage>(
+    storage: &S,
+    asset: &HumanAddr,
+    category: &treasury::LedgerCategory,
+) -> ReadonlyBucket<S, u64> {
+    bucket_read(ledger_index_key(asset, category).as_bytes(), storage)
+}
+
+pub fn ledger_index_w<S: Storage>(
+    storage: &mut S,
+    asset: &HumanAddr,
+    category: &treasury::LedgerCategory,
+) -> Bucket<S, u64> {
+    bucket(ledger_index_key(asset, category).as_bytes(), storage)
+}
+
+// category -> entries, per asset
+pub fn ledger_index_count_r<S: Storage>(storage: &S, asset: &HumanAddr) -> ReadonlyBucket<S, u64> {
+    bucket_read((asset.to_string() + LEDGER_INDEX_COUNT).as_bytes(), storage)
+}
+
+pub fn ledger_index_count_w<S: Storage>(storage: &mut S, asset: &HumanAddr) -> Bucket<S, u64> {
+    bucket((asset.to_string() + LEDGER_INDEX_COUNT).as_bytes(), storage)
+}
+
+// asset -> unwound allocations
+pub fn unwinds_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, Vec<treasury::UnwindStatus>> {
+    bucket_read(UNWINDS, storage)
+}
+
+pub fn unwinds_w<S: Storage>(storage: &mut S) -> Bucket<S, Vec<treasury::UnwindStatus>> {
+    bucket(UNWINDS, storage)
+}
+
+// allocation -> time its unwind started, per asset
+pub fn unwinding_r<S: Storage>(storage: &S, asset: &HumanAddr) -> ReadonlyBucket<S, u64> {
+    bucket_read((asset.to_string() + UNWINDING).as_bytes(), storage)
+}
+
+pub fn unwinding_w<S: Storage>(storage: &mut S, asset: &HumanAddr) -> Bucket<S, u64> {
+    bucket((asset.to_string() + UNWINDING).as_bytes(), storage)
+}
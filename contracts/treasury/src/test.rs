@@ -1,5 +1,1158 @@
 #[cfg(test)]
 pub mod tests {
+    use crate::{
+        contract::{handle, init},
+        handle::{
+            allocated_portion, allowance_target, next_refresh, record_entry, validate_allocations,
+        },
+        query,
+        state::{
+            allocated_r, allocation_list, allocation_position, allocation_range, allocations_w,
+            allowance_range, allowance_status_r, allowance_status_w, asset_list, asset_list_w,
+            assets_w, deployed_r, push_allocation, push_asset, remove_allocation_at,
+            set_allocation, ALLOCATIONS,
+        },
+    };
+    use cosmwasm_std::{
+        coins, from_slice,
+        testing::{mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage},
+        to_binary, Binary, CosmosMsg, Empty, Extern, HandleResponse, HumanAddr, Querier,
+        QuerierResult, QueryRequest, StdError, StdResult, Storage, Uint128, WasmMsg, WasmQuery,
+    };
+    use cosmwasm_storage::to_length_prefixed;
+    use secret_toolkit::snip20::{Allowance, TokenInfo};
+    use shade_protocol::{
+        snip20::{self, Snip20Asset},
+        treasury::{
+            Allocation, AllowanceStatus, Cycle, HandleMsg, InitMsg, LedgerCategory, OutflowAction,
+            QueryAnswer, TreasuryReceiveMsg,
+        },
+        utils::asset::Contract,
+    };
+
+    // Answers smart queries from a fixed response per contract
+    struct TestQuerier {
+        base: MockQuerier,
+        responses: Vec<(HumanAddr, Binary)>,
+    }
+
+    impl Querier for TestQuerier {
+        fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+            let request: QueryRequest<Empty> = from_slice(bin_request).unwrap();
+            if let QueryRequest::Wasm(WasmQuery::Smart { contract_addr, .. }) = &request {
+                return Ok(
+                    match self
+                        .responses
+                        .iter()
+                        .find(|(address, _)| address == contract_addr)
+                    {
+                        Some((_, response)) => Ok(response.clone()),
+                        None => Err(StdError::generic_err(format!(
+                            "No response from {}",
+                            contract_addr
+                        ))),
+                    },
+                );
+            }
+            self.base.handle_query(&request)
+        }
+    }
+
+    fn test_deps(responses: Vec<(HumanAddr, Binary)>) -> Extern<MockStorage, MockApi, TestQuerier> {
+        Extern {
+            storage: MockStorage::new(),
+            api: MockApi::new(20),
+            querier: TestQuerier {
+                base: MockQuerier::new(&[]),
+                responses,
+            },
+        }
+    }
+
+    fn contract(address: &str) -> Contract {
+        Contract {
+            address: HumanAddr::from(address),
+            code_hash: "".to_string(),
+        }
+    }
+
+    fn staking(address: &str, allocation: u128) -> Allocation {
+        Allocation::Staking {
+            contract: contract(address),
+            allocation: Uint128(allocation),
+        }
+    }
+
+    fn init_treasury<Q: Querier>(
+        deps: &mut Extern<MockStorage, MockApi, Q>,
+        oracle: Option<Contract>,
+    ) {
+        init(
+            deps,
+            mock_env("admin", &[]),
+            InitMsg {
+                admin: None,
+                viewing_key: "key".to_string(),
+                sscrt: contract("sscrt"),
+                oracle,
+                upgrade: None,
+            },
+        )
+        .unwrap();
+    }
+
+    // Registered without querying the token
+    fn register_asset(storage: &mut MockStorage, address: &str) {
+        let asset = HumanAddr::from(address);
+        push_asset(storage, &asset).unwrap();
+        assets_w(storage)
+            .save(
+                asset.to_string().as_bytes(),
+                &Snip20Asset {
+                    contract: contract(address),
+                    token_info: TokenInfo {
+                        name: address.to_string(),
+                        symbol: address.to_uppercase(),
+                        decimals: 6,
+                        total_supply: None,
+                    },
+                    token_config: None,
+                },
+            )
+            .unwrap();
+    }
+
+    fn spender(address: &str, amount: u128) -> Allocation {
+        Allocation::Allowance {
+            address: HumanAddr::from(address),
+            amount: Uint128(amount),
+            cycle: Cycle::Daily,
+            cap: None,
+        }
+    }
+
+    #[test]
+    fn allocation_index() {
+        let mut storage = MockStorage::new();
+        let asset = HumanAddr::from("asset");
+
+        for alloc in vec![
+            staking("a", 10),
+            spender("x", 100),
+            staking("b", 20),
+            spender("y", 100),
+            staking("c", 30),
+        ] {
+            push_allocation(&mut storage, &asset, &alloc).unwrap();
+        }
+
+        assert_eq!(
+            vec![staking("b", 20), spender("y", 100)],
+            allocation_range(&storage, &asset, 2, 4).unwrap()
+        );
+        assert_eq!(
+            vec![spender("y", 100)],
+            allowance_range(&storage, &asset, 1, 10).unwrap()
+        );
+        assert_eq!(
+            Uint128(60),
+            allocated_r(&storage)
+                .load(asset.to_string().as_bytes())
+                .unwrap()
+        );
+
+        // The last allocation takes the removed index
+        remove_allocation_at(&mut storage, &asset, 0).unwrap();
+        remove_allocation_at(&mut storage, &asset, 1).unwrap();
+        assert_eq!(
+            vec![staking("c", 30), spender("y", 100), staking("b", 20)],
+            allocation_list(&storage, &asset).unwrap()
+        );
+        assert_eq!(
+            Some(2),
+            allocation_position(&storage, &asset, &Some(HumanAddr::from("b"))).unwrap()
+        );
+        assert_eq!(
+            vec![spender("y", 100)],
+            allowance_range(&storage, &asset, 0, 10).unwrap()
+        );
+        assert_eq!(
+            Uint128(50),
+            allocated_r(&storage)
+                .load(asset.to_string().as_bytes())
+                .unwrap()
+        );
+
+        // Replacing keeps the totals and allowances in step
+        set_allocation(&mut storage, &asset, 2, &staking("b", 5)).unwrap();
+        set_allocation(&mut storage, &asset, 1, &staking("y", 10)).unwrap();
+        assert_eq!(
+            Uint128(45),
+            allocated_r(&storage)
+                .load(asset.to_string().as_bytes())
+                .unwrap()
+        );
+        assert!(allowance_range(&storage, &asset, 0, 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn migrate_storage() {
+        let mut deps = mock_dependencies(20, &[]);
+        init_treasury(&mut deps, None);
+
+        let asset = HumanAddr::from("asset");
+        asset_list_w(&mut deps.storage)
+            .save(&vec![asset.clone()])
+            .unwrap();
+        allocations_w(&mut deps.storage)
+            .save(
+                asset.to_string().as_bytes(),
+                &vec![staking("a", 10), spender("x", 100)],
+            )
+            .unwrap();
+
+        assert!(handle(
+            &mut deps,
+            mock_env("other", &[]),
+            HandleMsg::MigrateStorage {}
+        )
+        .is_err());
+
+        // Running it again changes nothing
+        for _ in 0..2 {
+            handle(
+                &mut deps,
+                mock_env("admin", &[]),
+                HandleMsg::MigrateStorage {},
+            )
+            .unwrap();
+        }
+
+        assert_eq!(vec![asset.clone()], asset_list(&deps.storage).unwrap());
+        assert_eq!(
+            vec![staking("a", 10), spender("x", 100)],
+            allocation_list(&deps.storage, &asset).unwrap()
+        );
+        assert_eq!(
+            vec![spender("x", 100)],
+            allowance_range(&deps.storage, &asset, 0, 10).unwrap()
+        );
+    }
+
+    #[test]
+    fn migrate_legacy_allowance() {
+        let mut deps = mock_dependencies(20, &[]);
+        init_treasury(&mut deps, None);
+
+        // Stored before allowances had a cycle
+        let asset = HumanAddr::from("asset");
+        asset_list_w(&mut deps.storage)
+            .save(&vec![asset.clone()])
+            .unwrap();
+        deps.storage.set(
+            &[to_length_prefixed(ALLOCATIONS).as_slice(), b"asset"].concat(),
+            br#"[{"allowance":{"address":"x","amount":"100"}}]"#,
+        );
+
+        handle(
+            &mut deps,
+            mock_env("admin", &[]),
+            HandleMsg::MigrateStorage {},
+        )
+        .unwrap();
+
+        // Legacy allowances refreshed monthly
+        assert_eq!(
+            vec![Allocation::Allowance {
+                address: HumanAddr::from("x"),
+                amount: Uint128(100),
+                cycle: Cycle::Monthly,
+                cap: None,
+            }],
+            allocation_list(&deps.storage, &asset).unwrap()
+        );
+    }
+
+    #[test]
+    fn ledger_pagination() {
+        let mut deps = mock_dependencies(20, &[]);
+        let asset = HumanAddr::from("asset");
+        init_treasury(&mut deps, None);
+        register_asset(&mut deps.storage, "asset");
+
+        let categories = vec![
+            LedgerCategory::Deposit,
+            LedgerCategory::FeeIncome,
+            LedgerCategory::Transfer,
+            LedgerCategory::FeeIncome,
+            LedgerCategory::FeeIncome,
+        ];
+        for (i, category) in categories.into_iter().enumerate() {
+            let mut env = mock_env("asset", &[]);
+            env.block.time = 100 * (i as u64 + 1);
+            record_entry(
+                &mut deps.storage,
+                &env,
+                &asset,
+                category,
+                HumanAddr::from("minter"),
+                Uint128(i as u128),
+                None,
+            )
+            .unwrap();
+        }
+
+        let ids = |answer: QueryAnswer| match answer {
+            QueryAnswer::Ledger { entries, .. } => {
+                entries.iter().map(|entry| entry.id).collect::<Vec<u64>>()
+            }
+            _ => panic!("Unexpected response"),
+        };
+
+        // Time range
+        let answer = query::ledger(&deps, asset.clone(), None, Some(200), Some(400), None, None);
+        assert_eq!(vec![1, 2, 3], ids(answer.unwrap()));
+
+        // Category with cursor
+        let answer = query::ledger(
+            &deps,
+            asset.clone(),
+            Some(LedgerCategory::FeeIncome),
+            None,
+            None,
+            Some(1),
+            Some(1),
+        );
+        assert_eq!(vec![3], ids(answer.unwrap()));
+
+        let answer = query::ledger(
+            &deps,
+            asset.clone(),
+            Some(LedgerCategory::FeeIncome),
+            Some(450),
+            None,
+            None,
+            None,
+        );
+        assert_eq!(vec![4], ids(answer.unwrap()));
+    }
+
+    #[test]
+    fn outflow_requires_threshold() {
+        let mut deps = mock_dependencies(20, &[]);
+        init_treasury(&mut deps, None);
+        register_asset(&mut deps.storage, "sscrt");
+
+        // Proposals would expire immediately
+        assert!(handle(
+            &mut deps,
+            mock_env("admin", &[]),
+            HandleMsg::SetMultisig {
+                signers: vec![HumanAddr::from("a"), HumanAddr::from("b")],
+                threshold: 2,
+                expiry: 0,
+            }
+        )
+        .is_err());
+
+        // Threshold above signer count
+        assert!(handle(
+            &mut deps,
+            mock_env("admin", &[]),
+            HandleMsg::SetMultisig {
+                signers: vec![HumanAddr::from("a"), HumanAddr::from("b")],
+                threshold: 3,
+                expiry: 100,
+            }
+        )
+        .is_err());
+
+        handle(
+            &mut deps,
+            mock_env("admin", &[]),
+            HandleMsg::SetMultisig {
+                signers: vec![HumanAddr::from("a"), HumanAddr::from("b")],
+                threshold: 2,
+                expiry: 100,
+            },
+        )
+        .unwrap();
+
+        let transfer = OutflowAction::Transfer {
+            asset: HumanAddr::from("sscrt"),
+            recipient: HumanAddr::from("admin"),
+            amount: Uint128(100),
+        };
+
+        // Native coins can't be routed around the signers either
+        assert!(handle(
+            &mut deps,
+            mock_env("admin", &[]),
+            HandleMsg::RegisterWrapper {
+                denom: "uscrt".to_string(),
+                contract: contract("sscrt"),
+            }
+        )
+        .is_err());
+        assert!(handle(
+            &mut deps,
+            mock_env("admin", &[]),
+            HandleMsg::Wrap {
+                denom: "uscrt".to_string(),
+                amount: None,
+            }
+        )
+        .is_err());
+
+        // Admin can no longer move funds directly
+        assert!(handle(
+            &mut deps,
+            mock_env("admin", &[]),
+            HandleMsg::Transfer {
+                asset: HumanAddr::from("sscrt"),
+                recipient: HumanAddr::from("admin"),
+                amount: Uint128(100),
+            }
+        )
+        .is_err());
+
+        handle(
+            &mut deps,
+            mock_env("a", &[]),
+            HandleMsg::ProposeOutflow { action: transfer },
+        )
+        .unwrap();
+
+        // Only one of two approvals
+        assert!(handle(
+            &mut deps,
+            mock_env("a", &[]),
+            HandleMsg::ExecuteOutflow {
+                proposal_id: Uint128(1),
+            }
+        )
+        .is_err());
+
+        // Non signers cannot approve
+        assert!(handle(
+            &mut deps,
+            mock_env("admin", &[]),
+            HandleMsg::ApproveOutflow {
+                proposal_id: Uint128(1),
+            }
+        )
+        .is_err());
+
+        handle(
+            &mut deps,
+            mock_env("b", &[]),
+            HandleMsg::ApproveOutflow {
+                proposal_id: Uint128(1),
+            },
+        )
+        .unwrap();
+
+        let response = handle(
+            &mut deps,
+            mock_env("b", &[]),
+            HandleMsg::ExecuteOutflow {
+                proposal_id: Uint128(1),
+            },
+        )
+        .unwrap();
+        assert_eq!(1, response.messages.len());
+
+        match query::outflow_proposal(&deps, Uint128(1)).unwrap() {
+            QueryAnswer::OutflowProposal { proposal } => assert!(proposal.executed),
+            _ => panic!("Unexpected response"),
+        }
+
+        // Cannot be replayed
+        assert!(handle(
+            &mut deps,
+            mock_env("a", &[]),
+            HandleMsg::ExecuteOutflow {
+                proposal_id: Uint128(1),
+            }
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn balance_sheet_allocated() {
+        let mut deps = test_deps(vec![(
+            HumanAddr::from("shd"),
+            to_binary(&snip20::QueryAnswer::Balance {
+                amount: Uint128(500),
+            })
+            .unwrap(),
+        )]);
+        init_treasury(&mut deps, Some(contract("oracle")));
+        register_asset(&mut deps.storage, "shd");
+
+        let asset = HumanAddr::from("shd");
+        push_allocation(
+            &mut deps.storage,
+            &asset,
+            &Allocation::Rewards {
+                contract: contract("rewards"),
+                allocation: Uint128(5 * 10u128.pow(17)),
+            },
+        )
+        .unwrap();
+
+        handle(
+            &mut deps,
+            mock_env("shd", &[]),
+            HandleMsg::Receive {
+                sender: HumanAddr::from("minter"),
+                from: HumanAddr::from("minter"),
+                amount: Uint128(1000),
+                memo: None,
+                msg: None,
+            },
+        )
+        .unwrap();
+
+        let deployed = |deps: &Extern<MockStorage, MockApi, TestQuerier>| {
+            deployed_r(&deps.storage, &asset).load(b"rewards").unwrap()
+        };
+        assert_eq!(Uint128(500), deployed(&deps));
+
+        match query::balance_sheet(&deps).unwrap() {
+            QueryAnswer::BalanceSheet { assets } => {
+                assert_eq!(Uint128(500), assets[0].native.liquid);
+                assert_eq!(Uint128(500), assets[0].native.allocated);
+                assert_eq!(Uint128(1000), assets[0].native.total);
+                // The oracle has no price for it
+                assert_eq!(None, assets[0].price);
+                assert!(assets[0].price_error.is_some());
+            }
+            _ => panic!("Unexpected response"),
+        }
+
+        handle(
+            &mut deps,
+            mock_env("shd", &[]),
+            HandleMsg::Receive {
+                sender: HumanAddr::from("rewards"),
+                from: HumanAddr::from("rewards"),
+                amount: Uint128(200),
+                memo: None,
+                msg: Some(
+                    to_binary(&TreasuryReceiveMsg::Repayment {
+                        address: HumanAddr::from("rewards"),
+                    })
+                    .unwrap(),
+                ),
+            },
+        )
+        .unwrap();
+        assert_eq!(Uint128(300), deployed(&deps));
+    }
+
+    fn allowance_answer(allowance: u128, expiration: Option<u64>) -> Binary {
+        to_binary(&snip20::QueryAnswer::Allowance {
+            allowance: Allowance {
+                spender: HumanAddr::from("spender"),
+                owner: HumanAddr::from("treasury"),
+                allowance: Uint128(allowance),
+                expiration,
+            },
+        })
+        .unwrap()
+    }
+
+    // Refreshes a daily allowance of 100 after 30 of a one time allowance of 50 was spent
+    fn refresh_after_one_time(expiration: Option<u64>) -> (Vec<CosmosMsg>, AllowanceStatus) {
+        let mut deps = test_deps(vec![(
+            HumanAddr::from("shd"),
+            allowance_answer(120, expiration),
+        )]);
+        init_treasury(&mut deps, None);
+        register_asset(&mut deps.storage, "shd");
+
+        let asset = HumanAddr::from("shd");
+        push_allocation(
+            &mut deps.storage,
+            &asset,
+            &Allocation::Allowance {
+                address: HumanAddr::from("spender"),
+                amount: Uint128(100),
+                cycle: Cycle::Daily,
+                cap: None,
+            },
+        )
+        .unwrap();
+        allowance_status_w(&mut deps.storage, &asset)
+            .save(
+                b"spender",
+                &AllowanceStatus {
+                    granted: Uint128(100),
+                    one_time: Uint128(50),
+                    spent: Uint128::zero(),
+                    last_refresh: Some(0),
+                },
+            )
+            .unwrap();
+
+        let mut env = mock_env("admin", &[]);
+        env.block.time = 100_000;
+        let messages = handle(&mut deps, env, HandleMsg::RefreshAllowance {})
+            .unwrap()
+            .messages;
+
+        (
+            messages,
+            allowance_status_r(&deps.storage, &asset)
+                .load(b"spender")
+                .unwrap(),
+        )
+    }
+
+    #[test]
+    fn refresh_keeps_one_time_allowance() {
+        let (messages, status) = refresh_after_one_time(None);
+
+        // Spending is drawn from the one time allowance, nothing to top up
+        assert!(messages.is_empty());
+        assert_eq!(Uint128(100), status.granted);
+        assert_eq!(Uint128(20), status.one_time);
+        assert_eq!(Uint128::zero(), status.spent);
+    }
+
+    #[test]
+    fn refresh_drops_expired_one_time_allowance() {
+        let (messages, status) = refresh_after_one_time(Some(50_000));
+
+        // The token resets an expired allowance, so the whole cycle is given again
+        assert_eq!(1, messages.len());
+        assert_eq!(Uint128(100), status.granted);
+        assert_eq!(Uint128::zero(), status.one_time);
+        // Unspent expired allowances don't count as spent
+        assert_eq!(Uint128::zero(), status.spent);
+    }
+
+    #[test]
+    fn wrap_native() {
+        let mut deps = mock_dependencies(20, &coins(1000, "uscrt"));
+        init_treasury(&mut deps, None);
+        register_asset(&mut deps.storage, "sscrt");
+
+        // Admin only
+        assert!(handle(
+            &mut deps,
+            mock_env("other", &[]),
+            HandleMsg::Wrap {
+                denom: "uscrt".to_string(),
+                amount: None,
+            }
+        )
+        .is_err());
+
+        // More than the balance
+        assert!(handle(
+            &mut deps,
+            mock_env("admin", &[]),
+            HandleMsg::Wrap {
+                denom: "uscrt".to_string(),
+                amount: Some(Uint128(1001)),
+            }
+        )
+        .is_err());
+
+        // No wrapper registered
+        assert!(handle(
+            &mut deps,
+            mock_env("admin", &[]),
+            HandleMsg::Wrap {
+                denom: "uatom".to_string(),
+                amount: None,
+            }
+        )
+        .is_err());
+
+        let response = handle(
+            &mut deps,
+            mock_env("admin", &[]),
+            HandleMsg::Wrap {
+                denom: "uscrt".to_string(),
+                amount: None,
+            },
+        )
+        .unwrap();
+        match &response.messages[..] {
+            [CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr,
+                send,
+                ..
+            })] => {
+                assert_eq!(HumanAddr::from("sscrt"), *contract_addr);
+                assert_eq!(coins(1000, "uscrt"), *send);
+            }
+            _ => panic!("Expected a deposit"),
+        }
+
+        // Wrappers must be registered assets
+        let register = HandleMsg::RegisterWrapper {
+            denom: "uatom".to_string(),
+            contract: contract("satom"),
+        };
+        assert!(handle(&mut deps, mock_env("admin", &[]), register.clone()).is_err());
+        register_asset(&mut deps.storage, "satom");
+        handle(&mut deps, mock_env("admin", &[]), register).unwrap();
+
+        let response = handle(
+            &mut deps,
+            mock_env("admin", &[]),
+            HandleMsg::Unwrap {
+                denom: "uatom".to_string(),
+                amount: Uint128(10),
+            },
+        )
+        .unwrap();
+        match &response.messages[..] {
+            [CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, .. })] => {
+                assert_eq!(HumanAddr::from("satom"), *contract_addr);
+            }
+            _ => panic!("Expected a redeem"),
+        }
+    }
+
+    #[test]
+    fn receive_native_income() {
+        let mut deps = mock_dependencies(20, &[]);
+        init_treasury(&mut deps, None);
+        register_asset(&mut deps.storage, "sscrt");
+
+        let asset = HumanAddr::from("sscrt");
+        push_allocation(&mut deps.storage, &asset, &staking("scrt_staking", 10)).unwrap();
+
+        handle(
+            &mut deps,
+            mock_env("scrt_staking", &coins(100, "uscrt")),
+            HandleMsg::ReceiveNative {
+                msg: Some(TreasuryReceiveMsg::FeeIncome {
+                    source: "scrt_staking".to_string(),
+                }),
+            },
+        )
+        .unwrap();
+        handle(
+            &mut deps,
+            mock_env("scrt_staking", &coins(40, "uscrt")),
+            HandleMsg::ReceiveNative {
+                msg: Some(TreasuryReceiveMsg::Repayment {
+                    address: HumanAddr::from("scrt_staking"),
+                }),
+            },
+        )
+        .unwrap();
+
+        // Held until wrapped
+        assert!(handle(
+            &mut deps,
+            mock_env("scrt_staking", &coins(40, "uscrt")),
+            HandleMsg::ReceiveNative {
+                msg: Some(TreasuryReceiveMsg::AllocateTo { address: None }),
+            }
+        )
+        .is_err());
+
+        // No wrapper for the denom
+        assert!(handle(
+            &mut deps,
+            mock_env("scrt_staking", &coins(40, "uatom")),
+            HandleMsg::ReceiveNative { msg: None }
+        )
+        .is_err());
+
+        match query::income(&deps, asset.clone()).unwrap() {
+            QueryAnswer::Income { income, repayments } => {
+                assert_eq!("scrt_staking", income[0].source);
+                assert_eq!(Uint128(100), income[0].amount);
+                assert_eq!(Uint128(40), repayments[0].amount);
+            }
+            _ => panic!("Unexpected response"),
+        }
+    }
+
+    fn receive(
+        deps: &mut Extern<MockStorage, MockApi, MockQuerier>,
+        amount: u128,
+        msg: TreasuryReceiveMsg,
+    ) -> StdResult<HandleResponse> {
+        handle(
+            deps,
+            mock_env("shd", &[]),
+            HandleMsg::Receive {
+                sender: HumanAddr::from("minter"),
+                from: HumanAddr::from("minter"),
+                amount: Uint128(amount),
+                memo: None,
+                msg: Some(to_binary(&msg).unwrap()),
+            },
+        )
+    }
+
+    #[test]
+    fn receive_msgs() {
+        let mut deps = mock_dependencies(20, &[]);
+        init_treasury(&mut deps, None);
+        register_asset(&mut deps.storage, "shd");
+
+        let asset = HumanAddr::from("shd");
+        for alloc in vec![
+            Allocation::Reserves {
+                allocation: Uint128(2 * 10u128.pow(17)),
+            },
+            Allocation::Rewards {
+                contract: contract("rewards"),
+                allocation: Uint128(5 * 10u128.pow(17)),
+            },
+            Allocation::Allowance {
+                address: HumanAddr::from("spender"),
+                amount: Uint128(100),
+                cycle: Cycle::Daily,
+                cap: None,
+            },
+        ] {
+            push_allocation(&mut deps.storage, &asset, &alloc).unwrap();
+        }
+
+        // All of it to the one allocation
+        let response = receive(
+            &mut deps,
+            100,
+            TreasuryReceiveMsg::AllocateTo {
+                address: Some(HumanAddr::from("rewards")),
+            },
+        )
+        .unwrap();
+        assert_eq!(1, response.messages.len());
+
+        // Allowances and reserves are held by treasury
+        let response = receive(
+            &mut deps,
+            100,
+            TreasuryReceiveMsg::AllocateTo {
+                address: Some(HumanAddr::from("spender")),
+            },
+        )
+        .unwrap();
+        assert!(response.messages.is_empty());
+        let response = receive(
+            &mut deps,
+            100,
+            TreasuryReceiveMsg::AllocateTo { address: None },
+        )
+        .unwrap();
+        assert!(response.messages.is_empty());
+
+        assert!(receive(
+            &mut deps,
+            100,
+            TreasuryReceiveMsg::AllocateTo {
+                address: Some(HumanAddr::from("unknown")),
+            }
+        )
+        .is_err());
+
+        // Only allocations can repay
+        assert!(receive(
+            &mut deps,
+            100,
+            TreasuryReceiveMsg::Repayment {
+                address: HumanAddr::from("unknown"),
+            }
+        )
+        .is_err());
+
+        let response = receive(&mut deps, 100, TreasuryReceiveMsg::Unallocated {}).unwrap();
+        assert!(response.messages.is_empty());
+
+        // Allocated as normal
+        let response = receive(
+            &mut deps,
+            1000,
+            TreasuryReceiveMsg::FeeIncome {
+                source: "mint".to_string(),
+            },
+        )
+        .unwrap();
+        assert_eq!(1, response.messages.len());
+        assert!(receive(
+            &mut deps,
+            1000,
+            TreasuryReceiveMsg::FeeIncome {
+                source: "".to_string(),
+            }
+        )
+        .is_err());
+
+        match query::income(&deps, asset.clone()).unwrap() {
+            QueryAnswer::Income { income, .. } => {
+                assert_eq!("mint", income[0].source);
+                assert_eq!(Uint128(1000), income[0].amount);
+            }
+            _ => panic!("Unexpected response"),
+        }
+
+        let categories = match query::ledger(&deps, asset, None, None, None, None, None).unwrap() {
+            QueryAnswer::Ledger { entries, .. } => entries
+                .into_iter()
+                .map(|entry| entry.category)
+                .collect::<Vec<LedgerCategory>>(),
+            _ => panic!("Unexpected response"),
+        };
+        assert_eq!(
+            vec![
+                LedgerCategory::Deposit,
+                LedgerCategory::Allocation,
+                LedgerCategory::Deposit,
+                LedgerCategory::Deposit,
+                LedgerCategory::Deposit,
+                LedgerCategory::FeeIncome,
+                LedgerCategory::Allocation,
+            ],
+            categories
+        );
+    }
+
+    #[test]
+    fn unwind_allocations() {
+        let mut deps = test_deps(vec![(HumanAddr::from("shd"), allowance_answer(100, None))]);
+        init_treasury(&mut deps, None);
+        register_asset(&mut deps.storage, "shd");
+
+        let asset = HumanAddr::from("shd");
+        let rewards = Allocation::Rewards {
+            contract: contract("rewards"),
+            allocation: Uint128(5 * 10u128.pow(17)),
+        };
+        push_allocation(&mut deps.storage, &asset, &rewards).unwrap();
+        push_allocation(
+            &mut deps.storage,
+            &asset,
+            &Allocation::Allowance {
+                address: HumanAddr::from("spender"),
+                amount: Uint128(100),
+                cycle: Cycle::Daily,
+                cap: None,
+            },
+        )
+        .unwrap();
+
+        let deposit = |deps: &mut Extern<MockStorage, MockApi, TestQuerier>| {
+            handle(
+                deps,
+                mock_env("shd", &[]),
+                HandleMsg::Receive {
+                    sender: HumanAddr::from("minter"),
+                    from: HumanAddr::from("minter"),
+                    amount: Uint128(1000),
+                    memo: None,
+                    msg: None,
+                },
+            )
+            .unwrap()
+            .messages
+        };
+        assert_eq!(1, deposit(&mut deps).len());
+
+        let unwind = HandleMsg::Unwind {
+            asset: asset.clone(),
+            allocation: None,
+        };
+        assert!(handle(&mut deps, mock_env("other", &[]), unwind.clone()).is_err());
+
+        // Only the allowance can be pulled back
+        let response = handle(&mut deps, mock_env("admin", &[]), unwind).unwrap();
+        assert_eq!(1, response.messages.len());
+
+        // Still registered, but no longer receiving funds
+        assert_eq!(2, allocation_list(&deps.storage, &asset).unwrap().len());
+        assert!(deposit(&mut deps).is_empty());
+
+        let rewards_unwind =
+            |deps: &Extern<MockStorage, MockApi, TestQuerier>| match query::unwinds(
+                deps,
+                HumanAddr::from("shd"),
+            )
+            .unwrap()
+            {
+                QueryAnswer::Unwinds { unwinds } => unwinds
+                    .into_iter()
+                    .find(|unwind| unwind.allocation == rewards)
+                    .unwrap(),
+                _ => panic!("Unexpected response"),
+            };
+
+        // Outstanding until it is repaid
+        let status = rewards_unwind(&deps);
+        assert_eq!(Uint128(500), status.expected);
+        assert_eq!(Uint128(500), status.outstanding);
+        assert!(!status.complete);
+
+        handle(
+            &mut deps,
+            mock_env("shd", &[]),
+            HandleMsg::Receive {
+                sender: HumanAddr::from("rewards"),
+                from: HumanAddr::from("rewards"),
+                amount: Uint128(500),
+                memo: None,
+                msg: Some(
+                    to_binary(&TreasuryReceiveMsg::Repayment {
+                        address: HumanAddr::from("rewards"),
+                    })
+                    .unwrap(),
+                ),
+            },
+        )
+        .unwrap();
+
+        let status = rewards_unwind(&deps);
+        assert_eq!(Uint128::zero(), status.outstanding);
+        assert!(status.complete);
+
+        // Updating resumes it
+        handle(
+            &mut deps,
+            mock_env("admin", &[]),
+            HandleMsg::UpdateAllocation {
+                asset: asset.clone(),
+                allocation: rewards.clone(),
+            },
+        )
+        .unwrap();
+        assert_eq!(1, deposit(&mut deps).len());
+    }
+
+    #[test]
+    fn allocations_within_total() {
+        let allocations = vec![
+            Allocation::Reserves {
+                allocation: Uint128(2 * 10u128.pow(17)),
+            },
+            staking("scrt_staking", 5 * 10u128.pow(17)),
+            Allocation::Allowance {
+                address: HumanAddr::from("spender"),
+                amount: Uint128(100),
+                cycle: Cycle::Monthly,
+                cap: None,
+            },
+        ];
+
+        assert!(validate_allocations(&allocations).is_ok());
+        assert_eq!(Uint128(7 * 10u128.pow(17)), allocated_portion(&allocations));
+    }
+
+    #[test]
+    fn allocations_exceeding_total() {
+        let allocations = vec![
+            staking("scrt_staking", 6 * 10u128.pow(17)),
+            staking("other_staking", 5 * 10u128.pow(17)),
+        ];
+
+        assert!(validate_allocations(&allocations).is_err());
+    }
+
+    #[test]
+    fn duplicate_allocations() {
+        let allocations = vec![
+            staking("scrt_staking", 10u128.pow(17)),
+            staking("scrt_staking", 10u128.pow(17)),
+        ];
+        assert!(validate_allocations(&allocations).is_err());
+
+        let allocations = vec![
+            Allocation::Reserves {
+                allocation: Uint128(10u128.pow(17)),
+            },
+            Allocation::Reserves {
+                allocation: Uint128(10u128.pow(17)),
+            },
+        ];
+        assert!(validate_allocations(&allocations).is_err());
+    }
+
+    #[test]
+    fn zero_allocation() {
+        assert!(validate_allocations(&[staking("scrt_staking", 0)]).is_err());
+    }
+
+    #[test]
+    fn invalid_vesting() {
+        let allocations = vec![Allocation::Allowance {
+            address: HumanAddr::from("spender"),
+            amount: Uint128(100),
+            cycle: Cycle::Vesting { start: 10, end: 10 },
+            cap: None,
+        }];
+
+        assert!(validate_allocations(&allocations).is_err());
+    }
+
+    #[test]
+    fn refresh_schedule() {
+        assert_eq!(Some(86_400 + 5), next_refresh(&Cycle::Daily, 5));
+        assert_eq!(Some(604_800 + 5), next_refresh(&Cycle::Weekly, 5));
+        assert_eq!(Some(25), next_refresh(&Cycle::Epoch { seconds: 20 }, 5));
+        assert_eq!(None, next_refresh(&Cycle::Vesting { start: 0, end: 10 }, 5));
+
+        // 2021-12-15T00:00:00Z refreshes on 2022-01-01T00:00:00Z
+        assert_eq!(Some(1640995200), next_refresh(&Cycle::Monthly, 1639526400));
+        // Never refreshed is 1970-02-01T00:00:00Z
+        assert_eq!(Some(2678400), next_refresh(&Cycle::Monthly, 0));
+    }
+
+    #[test]
+    fn periodic_target() {
+        let amount = Uint128(100);
+
+        assert_eq!(
+            amount,
+            allowance_target(amount, &Cycle::Daily, None, Uint128(1000), 0)
+        );
+        // Capped by whats left to spend
+        assert_eq!(
+            Uint128(50),
+            allowance_target(amount, &Cycle::Daily, Some(Uint128(1000)), Uint128(950), 0)
+        );
+        assert_eq!(
+            Uint128::zero(),
+            allowance_target(amount, &Cycle::Daily, Some(Uint128(1000)), Uint128(1000), 0)
+        );
+    }
+
+    #[test]
+    fn vesting_target() {
+        let amount = Uint128(1000);
+        let cycle = Cycle::Vesting {
+            start: 100,
+            end: 200,
+        };
+
+        assert_eq!(
+            Uint128::zero(),
+            allowance_target(amount, &cycle, None, Uint128::zero(), 50)
+        );
+        assert_eq!(
+            Uint128(500),
+            allowance_target(amount, &cycle, None, Uint128::zero(), 150)
+        );
+        // Spent amounts are no longer available
+        assert_eq!(
+            Uint128(300),
+            allowance_target(amount, &cycle, None, Uint128(200), 150)
+        );
+        assert_eq!(
+            Uint128(800),
+            allowance_target(amount, &cycle, None, Uint128(200), 300)
+        );
+    }
+
     /*
     use cosmwasm_std::{
         testing::{
@@ -140,6 +140,7 @@ pub fn create_proposal<Handle: serde::Serialize>(
             None => "Custom proposal".to_string(),
             Some(description) => description.to_string(),
         },
+        metadata: None,
     };
 
     //let proposals = get_latest_proposal(governance)?;
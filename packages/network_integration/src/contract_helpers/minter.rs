@@ -33,6 +33,7 @@ pub fn initialize_minter(
                 supply_portion: Uint128(1_000_000_000_000),
                 days: Uint128(1),
             }),
+            upgrade: None,
         },
         report,
     )?;
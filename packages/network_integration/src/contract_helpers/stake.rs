@@ -33,6 +33,8 @@ pub fn setup_staker(
                 address: shade.address.clone(),
                 code_hash: shade.code_hash.clone(),
             },
+            treasury: None,
+            upgrade: None,
         },
         report,
     )?;
@@ -237,6 +239,7 @@ pub fn get_user_stake(staker: &NetContract, address: String, key: String) -> Tes
         pending_rewards,
         unbonding,
         unbonded,
+        ..
     } = query
     {
         return TestUserStake {
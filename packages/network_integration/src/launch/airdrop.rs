@@ -106,6 +106,7 @@ fn main() -> serde_json::Result<()> {
         default_claim: DEFAULT_CLAIM,
         task_claim: vec![],
         query_rounding: QUERY_ROUNDING,
+        upgrade: None,
     };
 
     let airdrop = init(
@@ -142,7 +142,8 @@ fn main() -> Result<()> {
         admin: None,
         band: Contract { address: HumanAddr::from(band.address), code_hash: band.code_hash },
         sscrt: Contract { address: HumanAddr::from(sSCRT.address.clone()),
-            code_hash: sSCRT.code_hash.clone() }
+            code_hash: sSCRT.code_hash.clone() },
+        upgrade: None,
     }.inst_init("../../compiled/oracle.wasm.gz", &*generate_label(8),
                 ACCOUNT_KEY, Some(STORE_GAS), Some(GAS),
                 Some("test"))?;
@@ -160,6 +161,7 @@ fn main() -> Result<()> {
         treasury: None,
         epoch_frequency: Some(Uint128(120)),
         epoch_mint_limit: Some(Uint128(1000000000)),
+        upgrade: None,
     }.inst_init("../../compiled/mint.wasm.gz", &*generate_label(8),
                 ACCOUNT_KEY, Some(STORE_GAS), Some(GAS),
                 Some("test"))?;
@@ -179,6 +181,7 @@ fn main() -> Result<()> {
         treasury: None,
         epoch_frequency: Some(Uint128(120)),
         epoch_mint_limit: Some(Uint128(1000000000)),
+        upgrade: None,
     }.inst_init("../../compiled/mint.wasm.gz", &*generate_label(8),
                 ACCOUNT_KEY, Some(STORE_GAS), Some(GAS),
                 Some("test"))?;
@@ -178,6 +178,7 @@ fn setup_contracts(
         default_claim,
         task_claim,
         query_rounding,
+        upgrade: None,
     };
 
     let airdrop = init(
@@ -133,10 +133,18 @@ fn run_testnet() -> Result<()> {
             code_hash: shade.code_hash.clone(),
         },
         funding_amount: Uint128(1000000),
+        funding_policy: None,
         funding_deadline: 180,
         voting_deadline: 180,
-        // 5 shade is the minimum
-        quorum: Uint128(5000000),
+        vote_settings: governance::VoteSettings {
+            // All of the stake must vote
+            quorum: Uint128(governance::ONE_HUNDRED_PERCENT),
+            threshold: Uint128(governance::ONE_HUNDRED_PERCENT / 2),
+            veto: None,
+        },
+        guardian: None,
+        timelock: None,
+        execution_window: None,
     };
 
     let governance = init(
@@ -232,6 +240,7 @@ fn run_testnet() -> Result<()> {
                 address: HumanAddr::from(s_sCRT.address.clone()),
                 code_hash: s_sCRT.code_hash.clone(),
             },
+            upgrade: None,
         },
         &mut reports,
     )?;
@@ -280,8 +289,12 @@ fn run_testnet() -> Result<()> {
             }),
             proposal_deadline: None,
             funding_amount: None,
+            funding_policy: None,
             funding_deadline: None,
-            minimum_votes: None,
+            vote_settings: None,
+            guardian: None,
+            timelock: None,
+            execution_window: None,
         },
         Some("Remove control from admin and initialize governance"),
         &mut reports,
@@ -289,7 +302,11 @@ fn run_testnet() -> Result<()> {
 
     // Proposal admin command
     print_header("Creating proposal expected to fail");
-    let admin_command = "{\"update_config\":{\"unbond_time\": {}, \"admin\": null}}";
+    let admin_command = "{\"update_config\":{\"unbond_time\": {{unbond_time}}, \"admin\": null}}";
+    let admin_command_params = vec![governance::AdminCommandParam {
+        name: "unbond_time".to_string(),
+        param_type: governance::AdminCommandParamType::Json,
+    }];
 
     // Create a proposal and vote half of the votes
     create_proposal(
@@ -298,6 +315,7 @@ fn run_testnet() -> Result<()> {
         governance::HandleMsg::AddAdminCommand {
             name: "stake_unbond_time".to_string(),
             proposal: admin_command.to_string(),
+            params: admin_command_params.clone(),
         },
         Some("Staker unbond time can be updated by admin whenever"),
         &mut reports,
@@ -357,8 +375,8 @@ fn run_testnet() -> Result<()> {
             }
         }
 
-        print_warning("Checking that funds are returned");
-        assert_eq!(balance, get_balance(&shade, account.clone()));
+        print_warning("Checking that funds are held until voting ends");
+        assert_ne!(balance, get_balance(&shade, account.clone()));
     }
 
     print_warning("Voting on proposal");
@@ -448,6 +466,7 @@ fn run_testnet() -> Result<()> {
         governance::HandleMsg::AddAdminCommand {
             name: "stake_unbond_time".to_string(),
             proposal: admin_command.to_string(),
+            params: admin_command_params.clone(),
         },
         Some("Staker unbond time can be updated by admin whenever"),
         &mut reports,
@@ -518,7 +537,7 @@ fn run_testnet() -> Result<()> {
             let query: governance::QueryAnswer = query(&governance, msg, None)?;
 
             if let governance::QueryAnswer::Proposal { proposal } = query {
-                assert_eq!(proposal.status, ProposalStatus::Passed);
+                assert_eq!(proposal.status, ProposalStatus::Executed);
                 assert_eq!(proposal.run_status, Some(ResponseStatus::Success));
             } else {
                 assert!(false, "Query returned unexpected response")
@@ -565,6 +584,7 @@ fn run_testnet() -> Result<()> {
         governance::HandleMsg::AddAdminCommand {
             name: "stake_unbond_time".to_string(),
             proposal: admin_command.to_string(),
+            params: admin_command_params.clone(),
         },
         Some("This wont be funded :("),
         &mut reports,
@@ -637,6 +657,22 @@ fn run_testnet() -> Result<()> {
                 assert!(false, "Query returned unexpected response")
             }
         }
+
+        print_warning("Claiming lost funding");
+        handle(
+            &governance::HandleMsg::ClaimFunding {
+                proposal_id: proposal,
+            },
+            &governance,
+            ACCOUNT_KEY,
+            Some(GAS),
+            Some("test"),
+            None,
+            &mut reports,
+            None,
+        )?;
+
+        assert_eq!(get_balance(&shade, account.clone()), balance_before);
     }
 
     store_struct("run_testnet.json", &mut reports);
@@ -72,6 +72,7 @@ fn main() -> Result<()> {
         admin: account,
         treasury: account,
         sscrt: sSCRT.address,
+        upgrade: None,
     }.inst_init("../../compiled/scrt_staking.wasm.gz", &*generate_label(8),
                 ACCOUNT_KEY, Some(STORE_GAS), Some(GAS),
                 Some("test"))?;
@@ -165,7 +166,8 @@ fn main() -> Result<()> {
         admin: None,
         band: Contract { address: HumanAddr::from(band.address), code_hash: band.code_hash },
         sscrt: Contract { address: HumanAddr::from(sSCRT.address.clone()),
-            code_hash: sSCRT.code_hash.clone() }
+            code_hash: sSCRT.code_hash.clone() },
+        upgrade: None,
     }.inst_init("../../compiled/oracle.wasm.gz", &*generate_label(8),
                 ACCOUNT_KEY, Some(STORE_GAS), Some(GAS),
                 Some("test"))?;
@@ -196,6 +198,7 @@ fn main() -> Result<()> {
         start_epoch: None,
         epoch_frequency: Some(Uint128(120)),
         epoch_mint_limit: Some(Uint128(1000000000)),
+        upgrade: None,
     }.inst_init("../../compiled/mint.wasm.gz", &*generate_label(8),
                 ACCOUNT_KEY, Some(STORE_GAS), Some(GAS),
                 Some("test"))?;
@@ -215,7 +218,8 @@ fn main() -> Result<()> {
                 code_hash: mint_shade.code_hash.clone()
             }
         })?,
-        description: "This is some description".to_string()
+        description: "This is some description".to_string(),
+        metadata: None,
     }.t_handle(
         &governance, ACCOUNT_KEY, Some(GAS), Some("test"), None)?;
 
@@ -268,7 +272,8 @@ fn main() -> Result<()> {
         governance::HandleMsg::CreateProposal {
             target_contract: "mint-shade".to_string(),
             proposal: msg,
-            description: "Extend mint limit because of x and y reason".to_string()
+            description: "Extend mint limit because of x and y reason".to_string(),
+            metadata: None,
         }.t_handle(&governance, ACCOUNT_KEY, Some(GAS), Some("test"), None)?;
 
         governance::HandleMsg::TriggerProposal { proposal_id: Uint128(2)
@@ -293,9 +298,14 @@ fn main() -> Result<()> {
             target_contract: "SELF".to_string(),
             proposal: serde_json::to_string(&governance::HandleMsg::AddAdminCommand {
                 name: "update-mint-limit".to_string(),
-                proposal: "{\"update_mint_limit\":{\"start_epoch\":null,\"epoch_frequency\":null,\"epoch_limit\":\"{}\"}}".to_string()
+                proposal: "{\"update_mint_limit\":{\"start_epoch\":null,\"epoch_frequency\":null,\"epoch_limit\":{{epoch_limit}}}}".to_string(),
+                params: vec![governance::AdminCommandParam {
+                    name: "epoch_limit".to_string(),
+                    param_type: governance::AdminCommandParamType::Uint128,
+                }],
             })?,
-            description: "Give admin power to modify whenever for x and y reason".to_string()
+            description: "Give admin power to modify whenever for x and y reason".to_string(),
+            metadata: None,
         }.t_handle(&governance, ACCOUNT_KEY, Some(GAS), Some("test"), None)?;
 
         governance::HandleMsg::TriggerProposal { proposal_id: Uint128(3)
@@ -307,7 +317,7 @@ fn main() -> Result<()> {
         }.t_query(&governance)?;
 
         if let governance::QueryAnswer::AdminCommand { command } = query {
-            println!("\t{}\n\tTotal commands: {}", command.msg, command.total_arguments);
+            println!("\t{}\n\tTotal params: {}", command.msg, command.params.len());
         }
     }
     print_header("Run admin command");
@@ -6,6 +6,7 @@ use crate::airdrop::{
     account::{AccountPermit, AddressProofPermit},
     claim_info::RequiredTask,
 };
+use crate::governance::upgrade::UpgradeHook;
 use crate::utils::asset::Contract;
 use crate::utils::generic_response::ResponseStatus;
 use cosmwasm_std::{Binary, HumanAddr, Uint128};
@@ -69,6 +70,8 @@ pub struct InitMsg {
     pub task_claim: Vec<RequiredTask>,
     // Protects from leaking user information by limiting amount detail
     pub query_rounding: Uint128,
+    // Registers with governance when instantiated by an upgrade proposal
+    pub upgrade: Option<UpgradeHook>,
 }
 
 impl InitCallback for InitMsg {
@@ -1,4 +1,6 @@
 pub mod proposal;
+pub mod schema;
+pub mod upgrade;
 pub mod vote;
 
 use crate::utils::asset::Contract;
@@ -11,8 +13,58 @@ use serde::{Deserialize, Serialize};
 // This is used when calling itself
 pub const GOVERNANCE_SELF: &str = "SELF";
 
-// Admin command variable spot
-pub const ADMIN_COMMAND_VARIABLE: &str = "{}";
+// Admin command parameters are written as {{name}}
+pub const ADMIN_COMMAND_PARAM_OPEN: &str = "{{";
+pub const ADMIN_COMMAND_PARAM_CLOSE: &str = "}}";
+
+// Vote setting fractions are out of this
+pub const ONE_HUNDRED_PERCENT: u128 = 1_000_000_000_000_000_000;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct VoteSettings {
+    // Share of the total stake at the snapshot that must vote
+    pub quorum: Uint128,
+    // Share of the non abstaining votes that must be yes to pass
+    pub threshold: Uint128,
+    // Share of all votes that rejects the proposal, disabled when None
+    pub veto: Option<Uint128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct FundingPolicy {
+    // Receives slashed deposits, deposits are always refunded when None
+    pub treasury: Option<Contract>,
+    // Outcomes where deposits are sent to treasury instead of refunded, deposits are refunded
+    // anyway when treasury does not hold the funding token as an asset
+    pub slash_on: Vec<proposal::ProposalStatus>,
+}
+
+impl Default for FundingPolicy {
+    fn default() -> Self {
+        Self {
+            treasury: None,
+            slash_on: vec![
+                proposal::ProposalStatus::Expired,
+                proposal::ProposalStatus::Vetoed,
+            ],
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct EmergencySettings {
+    // Addresses allowed to create emergency proposals
+    pub council: Vec<HumanAddr>,
+    // Voting period, emergency proposals skip funding
+    pub voting_deadline: u64,
+    // Applied on top of the target settings, quorum cannot be below the default
+    pub vote_settings: VoteSettings,
+    // Delay between passing and execution, replaces the target timelocks
+    pub timelock: u64,
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
@@ -23,19 +75,45 @@ pub struct Config {
     pub funding_token: Contract,
     // The amount required to fund a proposal
     pub funding_amount: Uint128,
+    // What happens to deposits once voting ends
+    pub funding_policy: FundingPolicy,
     // Proposal funding period deadline
     pub funding_deadline: u64,
     // Proposal voting period deadline
     pub voting_deadline: u64,
-    // The minimum total amount of votes needed to approve deadline
-    pub minimum_votes: Uint128,
+    // Default voting rules, can be overridden per target
+    pub vote_settings: VoteSettings,
+    // Can cancel queued proposals along with the admin
+    pub guardian: Option<HumanAddr>,
+    // Default delay between passing and execution, can be overridden per target
+    pub timelock: u64,
+    // Time after the timelock in which a queued proposal can be executed
+    pub execution_window: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct AdminCommand {
     pub msg: String,
-    pub total_arguments: u16,
+    pub params: Vec<AdminCommandParam>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct AdminCommandParam {
+    pub name: String,
+    pub param_type: AdminCommandParamType,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AdminCommandParamType {
+    Address,
+    Uint128,
+    String,
+    Bool,
+    // Inserted as is, must be valid JSON
+    Json,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -45,9 +123,13 @@ pub struct InitMsg {
     pub staker: Option<Contract>,
     pub funding_token: Contract,
     pub funding_amount: Uint128,
+    pub funding_policy: Option<FundingPolicy>,
     pub funding_deadline: u64,
     pub voting_deadline: u64,
-    pub quorum: Uint128,
+    pub vote_settings: VoteSettings,
+    pub guardian: Option<HumanAddr>,
+    pub timelock: Option<u64>,
+    pub execution_window: Option<u64>,
 }
 
 impl InitCallback for InitMsg {
@@ -64,6 +146,19 @@ pub enum HandleMsg {
         // This will be saved as binary
         proposal: String,
         description: String,
+        metadata: Option<proposal::ProposalMetadata>,
+    },
+    /// Proposal running several messages as one batch
+    CreateMultiProposal {
+        actions: Vec<proposal::ProposalAction>,
+        description: String,
+        metadata: Option<proposal::ProposalMetadata>,
+    },
+    /// Proposal that skips funding and uses the emergency settings - can only be done by the council
+    CreateEmergencyProposal {
+        actions: Vec<proposal::ProposalAction>,
+        description: String,
+        metadata: Option<proposal::ProposalMetadata>,
     },
 
     /// Proposal funding
@@ -73,12 +168,17 @@ pub enum HandleMsg {
         // Proposal ID
         msg: Option<Binary>,
     },
+    /// Reclaim deposits of a proposal that was never fully funded
+    ClaimFunding {
+        proposal_id: Uint128,
+    },
 
     /// Admin Command
     /// These commands can be run by admins any time
     AddAdminCommand {
         name: String,
         proposal: String,
+        params: Vec<AdminCommandParam>,
     },
     RemoveAdminCommand {
         name: String,
@@ -86,10 +186,12 @@ pub enum HandleMsg {
     UpdateAdminCommand {
         name: String,
         proposal: String,
+        params: Vec<AdminCommandParam>,
     },
     TriggerAdminCommand {
         target: String,
         command: String,
+        // In the same order as the command params
         variables: Vec<String>,
         description: String,
     },
@@ -100,8 +202,33 @@ pub enum HandleMsg {
         staker: Option<Contract>,
         proposal_deadline: Option<u64>,
         funding_amount: Option<Uint128>,
+        funding_policy: Option<FundingPolicy>,
         funding_deadline: Option<u64>,
-        minimum_votes: Option<Uint128>,
+        vote_settings: Option<VoteSettings>,
+        guardian: Option<HumanAddr>,
+        timelock: Option<u64>,
+        execution_window: Option<u64>,
+    },
+
+    /// Override the vote settings for proposals on a target, None restores the default
+    SetVoteSettings {
+        target: String,
+        settings: Option<VoteSettings>,
+    },
+    /// Override the timelock for proposals on a target, None restores the default
+    SetTimelock {
+        target: String,
+        delay: Option<u64>,
+    },
+    /// Messages proposed to a target are checked against its schema, None disables the check
+    SetContractSchema {
+        target: String,
+        schema: Option<schema::MessageSchema>,
+    },
+
+    /// Enables emergency proposals, None disables them
+    SetEmergencySettings {
+        settings: Option<EmergencySettings>,
     },
 
     DisableStaker {},
@@ -131,6 +258,31 @@ pub enum HandleMsg {
     TriggerProposal {
         proposal_id: Uint128,
     },
+    /// Veto a queued proposal - can only be done by the guardian or admin
+    CancelProposal {
+        proposal_id: Uint128,
+    },
+    /// Sent by an upgraded contract from its init, the sender replaces the supported contract
+    /// pending under the label, the run reverts if the new contract never registers
+    RegisterUpgrade {
+        label: String,
+        code_hash: String,
+    },
+    /// Records the execution result, governance confirms successful runs after the last action.
+    /// A failing action reverts the trigger, so the admin may only report a failure for a queued
+    /// proposal past its timelock, the report is advisory and the proposal stays runnable
+    ConfirmExecution {
+        proposal_id: Uint128,
+        error: Option<String>,
+    },
+
+    /// Voter authentication
+    SetViewingKey {
+        key: String,
+    },
+    DisablePermitKey {
+        key: String,
+    },
 }
 
 impl HandleCallback for HandleMsg {
@@ -148,6 +300,10 @@ pub enum HandleAnswer {
         status: ResponseStatus,
         total_funding: Uint128,
     },
+    ClaimFunding {
+        status: ResponseStatus,
+        amount: Uint128,
+    },
     AddAdminCommand {
         status: ResponseStatus,
     },
@@ -167,6 +323,18 @@ pub enum HandleAnswer {
     DisableStaker {
         status: ResponseStatus,
     },
+    SetVoteSettings {
+        status: ResponseStatus,
+    },
+    SetContractSchema {
+        status: ResponseStatus,
+    },
+    SetTimelock {
+        status: ResponseStatus,
+    },
+    SetEmergencySettings {
+        status: ResponseStatus,
+    },
     AddSupportedContract {
         status: ResponseStatus,
     },
@@ -182,6 +350,21 @@ pub enum HandleAnswer {
     TriggerProposal {
         status: ResponseStatus,
     },
+    CancelProposal {
+        status: ResponseStatus,
+    },
+    RegisterUpgrade {
+        status: ResponseStatus,
+    },
+    ConfirmExecution {
+        status: ResponseStatus,
+    },
+    SetViewingKey {
+        status: ResponseStatus,
+    },
+    DisablePermitKey {
+        status: ResponseStatus,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -190,6 +373,15 @@ pub enum QueryMsg {
     GetProposalVotes {
         proposal_id: Uint128,
     },
+    GetVoterVote {
+        proposal_id: Uint128,
+        voter: HumanAddr,
+        key: String,
+    },
+    GetVoterVoteWithPermit {
+        proposal_id: Uint128,
+        permit: vote::VoterPermit,
+    },
     GetProposals {
         start: Uint128,
         end: Uint128,
@@ -198,6 +390,12 @@ pub enum QueryMsg {
     GetProposal {
         proposal_id: Uint128,
     },
+    /// Pages through matching proposals, pass the returned cursor to continue
+    SearchProposals {
+        filter: proposal::ProposalFilter,
+        cursor: Option<u64>,
+        limit: Option<u8>,
+    },
     GetTotalProposals {},
     GetSupportedContracts {},
     GetSupportedContract {
@@ -207,6 +405,22 @@ pub enum QueryMsg {
     GetAdminCommand {
         name: String,
     },
+    GetAdminCommandParams {
+        name: String,
+    },
+    GetVoteSettings {
+        target: String,
+    },
+    GetTimelock {
+        target: String,
+    },
+    GetContractSchema {
+        target: String,
+    },
+    GetPendingUpgrade {
+        label: String,
+    },
+    GetEmergencySettings {},
 }
 
 impl Query for QueryMsg {
@@ -219,12 +433,20 @@ pub enum QueryAnswer {
     ProposalVotes {
         status: vote::VoteTally,
     },
+    VoterVote {
+        votes: Option<vote::VoteTally>,
+    },
     Proposals {
         proposals: Vec<proposal::QueriedProposal>,
     },
     Proposal {
         proposal: proposal::QueriedProposal,
     },
+    SearchProposals {
+        proposals: Vec<proposal::QueriedProposal>,
+        // None when there is nothing left to search
+        cursor: Option<u64>,
+    },
     TotalProposals {
         total: Uint128,
     },
@@ -240,4 +462,22 @@ pub enum QueryAnswer {
     AdminCommand {
         command: AdminCommand,
     },
+    AdminCommandParams {
+        params: Vec<AdminCommandParam>,
+    },
+    VoteSettings {
+        settings: VoteSettings,
+    },
+    Timelock {
+        delay: u64,
+    },
+    ContractSchema {
+        schema: Option<schema::MessageSchema>,
+    },
+    PendingUpgrade {
+        upgrade: Option<proposal::PendingUpgrade>,
+    },
+    EmergencySettings {
+        settings: Option<EmergencySettings>,
+    },
 }
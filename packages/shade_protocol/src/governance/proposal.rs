@@ -1,5 +1,5 @@
 use crate::utils::generic_response::ResponseStatus;
-use cosmwasm_std::{Binary, Uint128};
+use cosmwasm_std::{Binary, Coin, HumanAddr, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -8,26 +8,94 @@ use serde::{Deserialize, Serialize};
 pub struct Proposal {
     // Proposal ID
     pub id: Uint128,
+    // Messages to execute in order
+    pub actions: Vec<ProposalAction>,
+    // Description of proposal
+    pub description: String,
+    // Address that created the proposal
+    pub proposer: HumanAddr,
+    pub metadata: ProposalMetadata,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ProposalMetadata {
+    pub title: String,
+    // Off-chain discussion or document
+    pub link: Option<String>,
+    // Hash of the linked document
+    pub document_hash: Option<String>,
+    pub category: Option<String>,
+}
+
+// Every set field must match, proposals are looked up through the first set index
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ProposalFilter {
+    pub proposer: Option<HumanAddr>,
+    pub target: Option<String>,
+    pub category: Option<String>,
+    pub status: Option<ProposalStatus>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ProposalAction {
     // Target smart contract
     pub target: String,
     // Message to execute
     pub msg: Binary,
-    // Description of proposal
-    pub description: String,
+    // Native coins sent with the message
+    pub funds: Vec<Coin>,
+    // Instantiate msg as a new contract instead, target is the supported contract it replaces
+    pub instantiate: Option<InstantiateAction>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct InstantiateAction {
+    pub code_id: u64,
+    pub code_hash: String,
+    // Must be unique, the new contract registers itself with it on init
+    pub label: String,
+}
+
+// New contract waiting to replace a supported contract
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct PendingUpgrade {
+    pub proposal_id: Uint128,
+    pub target: String,
+    pub code_hash: String,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct QueriedProposal {
     pub id: Uint128,
-    pub target: String,
-    pub msg: Binary,
+    pub actions: Vec<ProposalAction>,
     pub description: String,
+    pub proposer: HumanAddr,
+    pub metadata: ProposalMetadata,
+    // Created by the council, skipped funding
+    pub emergency: bool,
     pub funding_deadline: u64,
     pub voting_deadline: Option<u64>,
     pub total_funding: Uint128,
+    // Block height voting power is measured at
+    pub snapshot: Option<u64>,
+    // Timelock end when queued
+    pub execution_time: Option<u64>,
+    // Execution is not allowed after this time
+    pub expiration_time: Option<u64>,
     pub status: ProposalStatus,
+    // Success once governance confirms the run, Failure when it refused to run the actions
+    // or when the admin reported a reverted run, the latter is advisory only
     pub run_status: Option<ResponseStatus>,
+    // Reported reason of a failed execution
+    pub run_error: Option<String>,
+    // Status of each action, they run as one batch so all share the run status
+    pub action_status: Option<Vec<ResponseStatus>>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -39,10 +107,18 @@ pub enum ProposalStatus {
     Funding,
     // Voting in progress
     Voting,
-    // Total votes did not reach minimum total votes
+    // Total votes did not reach quorum or it was not executed in time
     Expired,
-    // Majority voted No
+    // Yes votes did not reach the pass threshold
     Rejected,
-    // Majority votes yes
+    // Veto votes reached the veto threshold
+    Vetoed,
+    // Yes votes reached the pass threshold
     Passed,
+    // Passed and waiting for its timelock to end
+    Queued,
+    // Actions were sent
+    Executed,
+    // Vetoed by the guardian or admin while queued
+    Cancelled,
 }
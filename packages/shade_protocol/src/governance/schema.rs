@@ -0,0 +1,67 @@
+use schemars::schema::{RootSchema, Schema, SchemaObject};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Messages a supported contract accepts, used to check proposals before they are stored
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct MessageSchema {
+    pub variants: Vec<MessageVariant>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct MessageVariant {
+    pub name: String,
+    pub fields: Vec<String>,
+    pub required: Vec<String>,
+}
+
+impl MessageSchema {
+    /// Builds the schema from a schemars derived enum like schema_for!(HandleMsg)
+    pub fn from_root(root: &RootSchema) -> Self {
+        let mut variants = vec![];
+
+        if let Some(subschemas) = &root.schema.subschemas {
+            let options = subschemas.any_of.iter().chain(subschemas.one_of.iter());
+            for schema in options.flatten() {
+                if let Schema::Object(SchemaObject {
+                    object: Some(object),
+                    ..
+                }) = schema
+                {
+                    for (name, fields) in object.properties.iter() {
+                        variants.push(MessageVariant::from_schema(name, fields));
+                    }
+                }
+            }
+        }
+
+        MessageSchema { variants }
+    }
+
+    pub fn variant(&self, name: &str) -> Option<&MessageVariant> {
+        self.variants.iter().find(|variant| variant.name == name)
+    }
+}
+
+impl MessageVariant {
+    fn from_schema(name: &str, schema: &Schema) -> Self {
+        let (fields, required) = match schema {
+            Schema::Object(SchemaObject {
+                object: Some(object),
+                ..
+            }) => (
+                object.properties.keys().cloned().collect(),
+                object.required.iter().cloned().collect(),
+            ),
+            _ => (vec![], vec![]),
+        };
+
+        MessageVariant {
+            name: name.to_string(),
+            fields,
+            required,
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use crate::governance::HandleMsg;
+use crate::utils::asset::Contract;
+use cosmwasm_std::{CosmosMsg, Env, StdResult};
+use schemars::JsonSchema;
+use secret_toolkit::utils::HandleCallback;
+use serde::{Deserialize, Serialize};
+
+// Set in the init msg of a contract instantiated by an upgrade proposal
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct UpgradeHook {
+    pub governance: Contract,
+    // Label the contract is instantiated with
+    pub label: String,
+}
+
+impl UpgradeHook {
+    // Sent from init so governance points the replaced contract to the sender
+    pub fn register_msg(&self, env: &Env) -> StdResult<CosmosMsg> {
+        HandleMsg::RegisterUpgrade {
+            label: self.label.clone(),
+            code_hash: env.contract_code_hash.clone(),
+        }
+        .to_cosmos_msg(
+            self.governance.code_hash.clone(),
+            self.governance.address.clone(),
+            None,
+        )
+    }
+}
+
+// Supported contracts take the hook as an `upgrade` field of their init msg
+#[derive(Deserialize)]
+pub struct UpgradeInitMsg {
+    pub upgrade: Option<UpgradeHook>,
+}
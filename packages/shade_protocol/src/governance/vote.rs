@@ -1,4 +1,6 @@
-use cosmwasm_std::Uint128;
+use cosmwasm_std::{HumanAddr, Uint128};
+use query_authentication::permit::Permit;
+use query_authentication::viewing_keys::ViewingKey;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -8,6 +10,7 @@ pub struct VoteTally {
     pub yes: Uint128,
     pub no: Uint128,
     pub abstain: Uint128,
+    pub veto: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -16,6 +19,8 @@ pub enum Vote {
     Yes,
     No,
     Abstain,
+    // Counts against the proposal and can reject it when vetoes are enabled
+    Veto,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -25,3 +30,26 @@ pub struct UserVote {
     pub vote: Vote,
     pub weight: u8,
 }
+
+// Used for querying a voter's votes
+pub type VoterPermit = Permit<VoterPermitMsg>;
+
+#[remain::sorted]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct VoterPermitMsg {
+    pub contract: HumanAddr,
+    pub key: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct VoterKey(pub String);
+
+impl ToString for VoterKey {
+    fn to_string(&self) -> String {
+        self.0.clone()
+    }
+}
+
+impl ViewingKey<32> for VoterKey {}
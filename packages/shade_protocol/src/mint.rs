@@ -1,3 +1,4 @@
+use crate::governance::upgrade::UpgradeHook;
 use crate::snip20::Snip20Asset;
 use crate::utils::asset::Contract;
 use crate::utils::generic_response::ResponseStatus;
@@ -58,6 +59,8 @@ pub struct InitMsg {
     pub secondary_burn: Option<HumanAddr>,
 
     pub limit: Option<Limit>,
+    // Registers with governance when instantiated by an upgrade proposal
+    pub upgrade: Option<UpgradeHook>,
 }
 
 impl InitCallback for InitMsg {
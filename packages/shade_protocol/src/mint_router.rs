@@ -1,3 +1,4 @@
+use crate::governance::upgrade::UpgradeHook;
 use crate::snip20::Snip20Asset;
 use crate::utils::asset::Contract;
 use crate::utils::generic_response::ResponseStatus;
@@ -35,6 +36,8 @@ pub struct PathNode {
 pub struct InitMsg {
     pub admin: Option<HumanAddr>,
     pub path: Vec<Contract>,
+    // Registers with governance when instantiated by an upgrade proposal
+    pub upgrade: Option<UpgradeHook>,
 }
 
 impl InitCallback for InitMsg {
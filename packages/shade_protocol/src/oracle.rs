@@ -4,6 +4,7 @@ use schemars::JsonSchema;
 use secret_toolkit::utils::{HandleCallback, InitCallback, Query};
 use serde::{Deserialize, Serialize};
 
+use crate::governance::upgrade::UpgradeHook;
 use crate::utils::asset::Contract;
 use crate::utils::generic_response::ResponseStatus;
 
@@ -34,6 +35,8 @@ pub struct InitMsg {
     pub admin: Option<HumanAddr>,
     pub band: Contract,
     pub sscrt: Contract,
+    // Registers with governance when instantiated by an upgrade proposal
+    pub upgrade: Option<UpgradeHook>,
 }
 
 impl InitCallback for InitMsg {
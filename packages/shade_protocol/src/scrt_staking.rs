@@ -1,3 +1,4 @@
+use crate::governance::upgrade::UpgradeHook;
 use crate::utils::{asset::Contract, generic_response::ResponseStatus};
 use cosmwasm_std::{Binary, Decimal, Delegation, HumanAddr, Uint128, Validator};
 use schemars::JsonSchema;
@@ -8,7 +9,7 @@ use serde::{Deserialize, Serialize};
 #[serde(rename_all = "snake_case")]
 pub struct Config {
     pub admin: HumanAddr,
-    pub treasury: HumanAddr,
+    pub treasury: Contract,
     pub sscrt: Contract,
     pub validator_bounds: Option<ValidatorBounds>,
 }
@@ -25,10 +26,12 @@ pub struct ValidatorBounds {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InitMsg {
     pub admin: Option<HumanAddr>,
-    pub treasury: HumanAddr,
+    pub treasury: Contract,
     pub sscrt: Contract,
     pub validator_bounds: Option<ValidatorBounds>,
     pub viewing_key: String,
+    // Registers with governance when instantiated by an upgrade proposal
+    pub upgrade: Option<UpgradeHook>,
 }
 
 impl InitCallback for InitMsg {
@@ -52,6 +55,8 @@ pub enum HandleMsg {
     Unbond {
         validator: HumanAddr,
     },
+    // Begin unbonding from every validator
+    UnbondAll {},
     //TODO: switch to this interface for standardization
     //Claim { amount: Uint128 },
 
@@ -86,6 +91,10 @@ pub enum HandleAnswer {
         status: ResponseStatus,
         delegation: Delegation,
     },
+    UnbondAll {
+        status: ResponseStatus,
+        amount: Uint128,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -95,6 +104,10 @@ pub enum QueryMsg {
     //TODO: find a way to query this and return
     //Unbondings {},
     Delegations {},
+    // Total amount delegated
+    Balance {},
+    // Undelegated but not yet claimed
+    Unbonding {},
     //Delegation { validator: HumanAddr },
     Rewards {},
 }
@@ -108,4 +121,5 @@ impl Query for QueryMsg {
 pub enum QueryAnswer {
     Config { config: Config },
     Balance { amount: Uint128 },
+    Unbonding { amount: Uint128 },
 }
@@ -1,4 +1,5 @@
 pub mod stake;
+use crate::governance::upgrade::UpgradeHook;
 use crate::governance::vote::UserVote;
 use crate::utils::asset::Contract;
 use crate::utils::generic_response::ResponseStatus;
@@ -7,6 +8,9 @@ use schemars::JsonSchema;
 use secret_toolkit::utils::{HandleCallback, Query};
 use serde::{Deserialize, Serialize};
 
+// Scale of the reward per token accumulator and the APR
+pub const REWARD_PRECISION: u128 = 1_000_000_000_000_000_000;
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct Config {
@@ -15,6 +19,8 @@ pub struct Config {
     pub unbond_time: u64,
     // Supported staking token
     pub staked_token: Contract,
+    // Tokens received from the treasury fund the reward emissions
+    pub treasury: Option<HumanAddr>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -23,6 +29,9 @@ pub struct InitMsg {
     pub admin: Option<Contract>,
     pub unbond_time: u64,
     pub staked_token: Contract,
+    pub treasury: Option<HumanAddr>,
+    // Registers with governance when instantiated by an upgrade proposal
+    pub upgrade: Option<UpgradeHook>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -31,6 +40,16 @@ pub enum HandleMsg {
     UpdateConfig {
         admin: Option<Contract>,
         unbond_time: Option<u64>,
+        treasury: Option<HumanAddr>,
+    },
+    // Tokens received from this contract are distributed to stakers
+    RegisterRewardToken {
+        token: Contract,
+    },
+    // Replaces the emission schedule of a reward token, None distributes rewards as they arrive
+    SetEmissions {
+        token: HumanAddr,
+        schedule: Option<Vec<stake::EmissionPeriod>>,
     },
     // Stake
     Receive {
@@ -47,10 +66,23 @@ pub enum HandleMsg {
         votes: Vec<UserVote>,
     },
     ClaimUnbond {},
-    ClaimRewards {},
+    // Claims every reward token when None
+    ClaimRewards {
+        tokens: Option<Vec<HumanAddr>>,
+    },
     SetViewingKey {
         key: String,
     },
+    // Stakes the pending staked token rewards
+    Compound {},
+    // Compounds the staked token rewards whenever the position is touched
+    SetAutoCompound {
+        enabled: bool,
+    },
+    // Let another address vote with your stake, None removes the delegation
+    Delegate {
+        delegate: Option<HumanAddr>,
+    },
 }
 
 impl HandleCallback for HandleMsg {
@@ -67,6 +99,12 @@ pub enum HandleAnswer {
     ClaimUnbond { status: ResponseStatus },
     ClaimRewards { status: ResponseStatus },
     SetViewingKey { status: ResponseStatus },
+    Delegate { status: ResponseStatus },
+    Compound { status: ResponseStatus },
+    SetAutoCompound { status: ResponseStatus },
+    FundRewards { status: ResponseStatus },
+    RegisterRewardToken { status: ResponseStatus },
+    SetEmissions { status: ResponseStatus },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -74,6 +112,10 @@ pub enum HandleAnswer {
 pub enum QueryMsg {
     Config {},
     TotalStaked {},
+    // Total voting stake held before the given block
+    TotalStakedAt {
+        height: u64,
+    },
     TotalUnbonding {
         start: Option<u64>,
         end: Option<u64>,
@@ -83,6 +125,30 @@ pub enum QueryMsg {
         key: String,
         time: u64,
     },
+    Delegation {
+        address: HumanAddr,
+        key: String,
+    },
+    // Voting power held before the given block, the latest when None
+    VotingPower {
+        address: HumanAddr,
+        key: String,
+        height: Option<u64>,
+    },
+    RewardTokens {},
+    Emissions {
+        token: HumanAddr,
+    },
+    // Yearly return of the staked token emissions of the emissions scheduled at the given time
+    Apr {
+        time: u64,
+    },
+    // Staked token rewards an amount staked over the given time would earn, assuming the schedule stays funded
+    ProjectedRewards {
+        amount: Uint128,
+        start: u64,
+        end: u64,
+    },
 }
 
 impl Query for QueryMsg {
@@ -98,13 +164,47 @@ pub enum QueryAnswer {
     TotalStaked {
         total: Uint128,
     },
+    TotalStakedAt {
+        total: Uint128,
+    },
     TotalUnbonding {
         total: Uint128,
     },
     UserStake {
         staked: Uint128,
+        // Staked token rewards
         pending_rewards: Uint128,
         unbonding: Uint128,
         unbonded: Uint128,
+        // Pending rewards of every reward token
+        token_rewards: Vec<stake::TokenReward>,
+        auto_compound: bool,
+    },
+    Delegation {
+        delegate: Option<HumanAddr>,
+        // Stake delegated to the address
+        delegated: Uint128,
+    },
+    VotingPower {
+        staked: Uint128,
+        delegated: Uint128,
+        // Own stake is also counted by the delegate until this address votes itself
+        delegate: Option<HumanAddr>,
+        // Own plus delegated stake, the weight a vote from this address carries
+        power: Uint128,
+    },
+    RewardTokens {
+        tokens: Vec<Contract>,
+    },
+    Emissions {
+        schedule: Option<Vec<stake::EmissionPeriod>>,
+        pool: stake::RewardPool,
+    },
+    Apr {
+        // Out of REWARD_PRECISION
+        apr: Uint128,
+    },
+    ProjectedRewards {
+        rewards: Uint128,
     },
 }
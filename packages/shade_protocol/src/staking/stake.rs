@@ -1,4 +1,4 @@
-use cosmwasm_std::Uint128;
+use cosmwasm_std::{HumanAddr, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
@@ -25,6 +25,57 @@ pub struct Unbonding {
     pub unbond_time: u64,
 }
 
+// Staked amount recorded at the end of a block
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct Checkpoint {
+    pub height: u64,
+    pub amount: Uint128,
+}
+
+// Delegate chosen at the end of a block
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct DelegationCheckpoint {
+    pub height: u64,
+    pub delegate: Option<HumanAddr>,
+}
+
+// Rewards emitted per second between start and end
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct EmissionPeriod {
+    pub start: u64,
+    pub end: u64,
+    pub rate: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct RewardPool {
+    // Rewards emitted per staked token, scaled by REWARD_PRECISION
+    pub reward_per_token: Uint128,
+    pub last_update: u64,
+    // Funded rewards that are not emitted yet
+    pub available: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct UserReward {
+    // Accumulator value when the user was last updated
+    pub reward_per_token_paid: Uint128,
+    // Earned and not claimed
+    pub pending: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TokenReward {
+    pub token: HumanAddr,
+    pub pending: Uint128,
+}
+
 impl Ord for Unbonding {
     fn cmp(&self, other: &Unbonding) -> Ordering {
         self.unbond_time.cmp(&other.unbond_time)
@@ -1,5 +1,6 @@
+use crate::governance::upgrade::UpgradeHook;
 use crate::utils::{asset::Contract, generic_response::ResponseStatus};
-use cosmwasm_std::{Binary, HumanAddr, Uint128};
+use cosmwasm_std::{Binary, Coin, HumanAddr, Uint128};
 use schemars::JsonSchema;
 use secret_toolkit::utils::{HandleCallback, InitCallback, Query};
 use serde::{Deserialize, Serialize};
@@ -9,6 +10,8 @@ pub struct Config {
     pub admin: HumanAddr,
     //pub account_holders: Vec<HumanAddr>,
     pub sscrt: Contract,
+    // Used to value the balance sheet in USD
+    pub oracle: Option<Contract>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -23,11 +26,16 @@ pub enum Allocation {
         contract: Contract,
         allocation: Uint128,
     },
-    // Monthly refresh, not counted in rebalance
+    // Refreshed every cycle, not counted in rebalance
     Allowance {
         address: HumanAddr,
-        // Unlike others, this is a direct number of uTKN to allow monthly
+        // Unlike others, this is a direct number of uTKN to allow each cycle
         amount: Uint128,
+        // Allowances stored before cycles were added refreshed monthly
+        #[serde(default)]
+        cycle: Cycle,
+        // Maximum total that can ever be spent
+        cap: Option<Uint128>,
     },
     // SCRT/ATOM/OSMO staking
     Staking {
@@ -49,17 +57,214 @@ pub enum Allocation {
     },
 }
 
-// Flag to be sent with funds
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub struct Flag {
-    pub flag: String,
+pub enum Cycle {
+    Daily,
+    Weekly,
+    // Calendar month
+    Monthly,
+    // Every given number of seconds
+    Epoch { seconds: u64 },
+    // Allowance amount unlocks linearly from start to end
+    Vesting { start: u64, end: u64 },
+}
+
+impl Default for Cycle {
+    fn default() -> Self {
+        Cycle::Monthly
+    }
+}
+
+// Allowance bookkeeping per spender, as of the last update
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct AllowanceStatus {
+    // Unspent allowance given by the allocation's cycle
+    pub granted: Uint128,
+    // Unspent one time allowances, these are drawn before the cycle's
+    #[serde(default)]
+    pub one_time: Uint128,
+    // Total spent from the cycle's allowance
+    pub spent: Uint128,
+    pub last_refresh: Option<u64>,
+}
+
+// Signers approving outflows, disabled when there are no signers
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct Multisig {
+    pub signers: Vec<HumanAddr>,
+    // Approvals needed to execute
+    pub threshold: u16,
+    // Seconds a proposal stays valid
+    pub expiry: u64,
+}
+
+// Actions that need signer approval once a multisig is set
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OutflowAction {
+    OneTimeAllowance {
+        asset: HumanAddr,
+        spender: HumanAddr,
+        amount: Uint128,
+        expiration: Option<u64>,
+    },
+    RegisterAllocation {
+        asset: HumanAddr,
+        allocation: Allocation,
+    },
+    UpdateAllocation {
+        asset: HumanAddr,
+        allocation: Allocation,
+    },
+    Transfer {
+        asset: HumanAddr,
+        recipient: HumanAddr,
+        amount: Uint128,
+    },
+    SetMultisig {
+        signers: Vec<HumanAddr>,
+        threshold: u16,
+        expiry: u64,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct OutflowProposal {
+    pub id: Uint128,
+    pub action: OutflowAction,
+    pub proposer: HumanAddr,
+    pub approvals: Vec<HumanAddr>,
+    pub expiration: u64,
+    pub executed: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct NativeWrapper {
+    pub denom: String,
+    pub contract: Contract,
+    pub balance: Uint128,
+}
+
+// Msg to be sent with funds
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TreasuryReceiveMsg {
+    // Hold the funds without allocating them
+    Unallocated {},
+    // Send it all to one allocation, None being reserves
+    AllocateTo { address: Option<HumanAddr> },
+    // Funds returned by an allocation, these are held
+    Repayment { address: HumanAddr },
+    // Allocated as normal and accounted to source
+    FeeIncome { source: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct IncomeSource {
+    pub source: String,
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct Repayment {
+    pub address: HumanAddr,
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LedgerCategory {
+    // Inflows
+    Deposit,
+    FeeIncome,
+    Repayment,
+    // Outflows
+    Allocation,
+    Transfer,
+    AllowanceSpend,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct LedgerEntry {
+    pub id: u64,
+    pub category: LedgerCategory,
+    pub counterparty: HumanAddr,
+    pub amount: Uint128,
+    pub time: u64,
+    // Fee income source
+    pub memo: Option<String>,
+}
+
+// An allocation pulled back by Unwind
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct UnwindStatus {
+    pub allocation: Allocation,
+    pub started: u64,
+    // Deployed when the unwind started
+    pub expected: Uint128,
+    // Yet to come back, updated when queried
+    pub outstanding: Uint128,
+    pub complete: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct AllowanceData {
     pub spender: HumanAddr,
+    // Available to spend until expiration
     pub amount: Uint128,
+    // Part of amount given by one time allowances
+    pub one_time: Uint128,
+    // None when only one time allowances were given
+    pub cycle: Option<Cycle>,
+    pub cap: Option<Uint128>,
+    // Total spent from the cycle's allowance, including since the last refresh
+    pub spent: Uint128,
+    // Left of the cap or vesting amount, None when unlimited
+    pub remaining: Option<Uint128>,
+    pub last_refresh: Option<u64>,
+    // None when there is no refresh schedule
+    pub next_refresh: Option<u64>,
+    pub expiration: Option<u64>,
+}
+
+// Amounts of an asset by where they are deployed
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct Holdings {
+    // Held directly by treasury
+    pub liquid: Uint128,
+    // Delegated or unbonding through staking allocations
+    pub staked: Uint128,
+    // Sent to rewards, application & pool allocations and not yet repaid
+    pub allocated: Uint128,
+    // Outstanding allowances, these are part of liquid
+    pub allowances: Uint128,
+    // Claimable from staking allocations
+    pub rewards: Uint128,
+    // liquid + staked + allocated + rewards
+    pub total: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct AssetBalanceSheet {
+    pub asset: HumanAddr,
+    pub native: Holdings,
+    // Oracle price, None when unavailable
+    pub price: Option<Uint128>,
+    // Why the oracle price is unavailable
+    pub price_error: Option<String>,
+    // USD valued holdings with 18 decimals
+    pub usd: Option<Holdings>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -67,6 +272,9 @@ pub struct InitMsg {
     pub admin: Option<HumanAddr>,
     pub viewing_key: String,
     pub sscrt: Contract,
+    pub oracle: Option<Contract>,
+    // Registers with governance when instantiated by an upgrade proposal
+    pub upgrade: Option<UpgradeHook>,
     //pub account_holders: Option<Vec<HumanAddr>>,
 }
 
@@ -104,7 +312,65 @@ pub enum HandleMsg {
         asset: HumanAddr,
         allocation: Allocation,
     },
+    // Replaces the existing allocation for the same contract/address
+    UpdateAllocation {
+        asset: HumanAddr,
+        allocation: Allocation,
+    },
+    // Removes the allocation for address, or the reserves when None
+    RemoveAllocation {
+        asset: HumanAddr,
+        address: Option<HumanAddr>,
+    },
     RefreshAllowance {},
+    Transfer {
+        asset: HumanAddr,
+        recipient: HumanAddr,
+        amount: Uint128,
+    },
+    /* Admin only until signers are set,
+     * after that it can only be changed through ProposeOutflow
+     */
+    SetMultisig {
+        signers: Vec<HumanAddr>,
+        threshold: u16,
+        expiry: u64,
+    },
+    ProposeOutflow {
+        action: OutflowAction,
+    },
+    ApproveOutflow {
+        proposal_id: Uint128,
+    },
+    ExecuteOutflow {
+        proposal_id: Uint128,
+    },
+    // Set the snip20 that wraps a native denom
+    RegisterWrapper {
+        denom: String,
+        contract: Contract,
+    },
+    // Deposit native coins into their wrapper (full balance if none)
+    Wrap {
+        denom: String,
+        amount: Option<Uint128>,
+    },
+    // Redeem a wrapper back to its native denom
+    Unwrap {
+        denom: String,
+        amount: Uint128,
+    },
+    // Native coins sent with a receive msg, held until wrapped
+    ReceiveNative {
+        msg: Option<TreasuryReceiveMsg>,
+    },
+    // Pull funds back from an allocation, all allocations if none
+    Unwind {
+        asset: HumanAddr,
+        allocation: Option<HumanAddr>,
+    },
+    // Moves assets and allocations stored by older versions into their indexes
+    MigrateStorage {},
     // Trigger to re-allocate asset (all if none)
     //Rebalance { asset: Option<HumanAddr> },
 }
@@ -132,12 +398,55 @@ pub enum HandleAnswer {
     RegisterApp {
         status: ResponseStatus,
     },
+    UpdateAllocation {
+        status: ResponseStatus,
+    },
+    RemoveAllocation {
+        status: ResponseStatus,
+    },
     RefreshAllowance {
         status: ResponseStatus,
     },
     OneTimeAllowance {
         status: ResponseStatus,
     },
+    Transfer {
+        status: ResponseStatus,
+    },
+    SetMultisig {
+        status: ResponseStatus,
+    },
+    ProposeOutflow {
+        status: ResponseStatus,
+        proposal_id: Uint128,
+    },
+    ApproveOutflow {
+        status: ResponseStatus,
+        approvals: u16,
+    },
+    ExecuteOutflow {
+        status: ResponseStatus,
+    },
+    RegisterWrapper {
+        status: ResponseStatus,
+    },
+    Wrap {
+        status: ResponseStatus,
+        amount: Uint128,
+    },
+    Unwrap {
+        status: ResponseStatus,
+    },
+    Unwind {
+        status: ResponseStatus,
+        unwinding: Vec<UnwindStatus>,
+    },
+    ReceiveNative {
+        status: ResponseStatus,
+    },
+    MigrateStorage {
+        status: ResponseStatus,
+    },
     //Rebalance { status: ResponseStatus },
 }
 
@@ -145,18 +454,48 @@ pub enum HandleAnswer {
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
-    Assets {},
+    // Pages start after the given index
+    Assets {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
     Balance {
         asset: HumanAddr,
     },
+    BalanceSheet {},
     Allocations {
         asset: HumanAddr,
+        start_after: Option<u64>,
+        limit: Option<u32>,
     },
+    // Every allowance allocation when no spender is given
     Allowances {
         asset: HumanAddr,
-        spender: HumanAddr,
+        spender: Option<HumanAddr>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
     },
     LastAllowanceRefresh {},
+    Multisig {},
+    OutflowProposal {
+        proposal_id: Uint128,
+    },
+    NativeWrappers {},
+    Income {
+        asset: HumanAddr,
+    },
+    // Entries in order, optionally filtered by category and time range
+    Ledger {
+        asset: HumanAddr,
+        category: Option<LedgerCategory>,
+        start: Option<u64>,
+        end: Option<u64>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    Unwinds {
+        asset: HumanAddr,
+    },
 }
 
 impl Query for QueryMsg {
@@ -166,10 +505,53 @@ impl Query for QueryMsg {
 #[derive(Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryAnswer {
-    Config { config: Config },
-    Assets { assets: Vec<HumanAddr> },
-    Allocations { allocations: Vec<Allocation> },
-    Balance { amount: Uint128 },
-    Allowances { allowances: Vec<AllowanceData> },
-    LastAllowanceRefresh { datetime: String },
+    Config {
+        config: Config,
+    },
+    Assets {
+        assets: Vec<HumanAddr>,
+        total: u64,
+    },
+    Allocations {
+        allocations: Vec<Allocation>,
+        total: u64,
+        // Portion (out of 10^18) not claimed by any allocation
+        unallocated: Uint128,
+    },
+    Balance {
+        amount: Uint128,
+        // Unwrapped balances of the denoms this asset wraps
+        native: Vec<Coin>,
+    },
+    BalanceSheet {
+        assets: Vec<AssetBalanceSheet>,
+    },
+    Allowances {
+        allowances: Vec<AllowanceData>,
+        total: u64,
+    },
+    LastAllowanceRefresh {
+        datetime: String,
+    },
+    Multisig {
+        multisig: Option<Multisig>,
+    },
+    OutflowProposal {
+        proposal: OutflowProposal,
+    },
+    NativeWrappers {
+        wrappers: Vec<NativeWrapper>,
+    },
+    Income {
+        income: Vec<IncomeSource>,
+        repayments: Vec<Repayment>,
+    },
+    Ledger {
+        entries: Vec<LedgerEntry>,
+        // Entries in the asset or category ledger, ignoring time range
+        total: u64,
+    },
+    Unwinds {
+        unwinds: Vec<UnwindStatus>,
+    },
 }
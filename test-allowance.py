@@ -79,6 +79,7 @@ print(treasury.execute({
             'allowance': {
                 'address': account,
                 'amount': '1000000', # (uscrt) = 1 SCRT
+                'cycle': 'monthly',
             },
         }
     }
@@ -96,7 +97,7 @@ print(treasury.query({'last_allowance_refresh': {}}))
 print('Treasury sSCRT Balance')
 print(treasury.query({'balance': {'asset': sscrt.address}}))
 
-print('Refreshing allowance (should fail/do nothing)')
+print('Refreshing allowance (should do nothing)')
 print(treasury.execute({'refresh_allowance': {}}))
 
 print('sSCRT Allowance')
@@ -48,21 +48,26 @@ print('Depositing', deposit_amount)
 sscrt.execute({'deposit': {}}, account, deposit_amount)
 print('SSCRT', sscrt.get_balance(account, viewing_key))
 
-'''
 treasury = Contract(
     '../compiled/treasury.wasm.gz',
     json.dumps({
         'admin': account,
         'viewing_key': viewing_key,
+        'sscrt': {
+            'address': sscrt.address,
+            'code_hash': sscrt.code_hash,
+        },
     }),
     gen_label(8),
 )
 print('TREASURY', treasury.address)
-'''
 
 staking_init = {
     'admin': account,
-    'treasury': account,
+    'treasury': {
+        'address': treasury.address,
+        'code_hash': treasury.code_hash,
+    },
     'sscrt': {
         'address': sscrt.address,
         'code_hash': sscrt.code_hash,
@@ -77,7 +82,7 @@ scrt_staking = Contract(
 )
 print('STAKING', scrt_staking.address)
 
-'''
+# Claims are repaid and booked against the staking allocation
 print('Configuring treasury')
 print(treasury.execute({
     'register_asset': {
@@ -112,7 +117,6 @@ print(treasury.query({'allocations': {'asset': sscrt.address}}))
 
 #print('config')
 #print(scrt_staking.query({'config': {}}))
-'''
 print('Sending 100000000 usscrt direct to staking')
 sscrt.execute({
         "send": {